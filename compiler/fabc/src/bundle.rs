@@ -151,7 +151,10 @@ mod tests {
         time::{SystemTime, UNIX_EPOCH},
     };
 
-    use fabc_llvm::ir::{Block, FunctionSpec, PartSpec, QuoteSpec, StepSpec, StoryProgram};
+    use fabc_llvm::ir::{
+        Block, Expr, FunctionSpec, Literal, MemberSegment, PartSpec, QuoteSpec, StepSpec, Stmt,
+        StoryProgram,
+    };
 
     use super::{CompiledBundle, CompiledBundleManifest, COMPILED_BUNDLE_FORMAT_VERSION};
     use crate::Error;
@@ -235,17 +238,67 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn round_trips_list_expressions_through_manifest_json() {
+        let root = temp_case_dir("bundle_list_round_trip");
+        fs::create_dir_all(&root).expect("create temp dir");
+
+        let mut program = minimal_program();
+        program.functions.push(FunctionSpec {
+            id: 0,
+            node_id: 0,
+            params: Vec::new(),
+            body: Block {
+                statements: vec![
+                    Stmt::Let {
+                        name: "items".to_string(),
+                        initializer: Expr::List(vec![
+                            Expr::Literal(Literal::String("torch".to_string())),
+                            Expr::List(vec![Expr::Literal(Literal::Number(1.0))]),
+                        ]),
+                    },
+                    Stmt::Expr(Expr::MemberAccess {
+                        base: Box::new(Expr::Identifier("items".to_string())),
+                        members: vec![MemberSegment::Expr(Box::new(Expr::Literal(
+                            Literal::Number(0.0),
+                        )))],
+                    }),
+                ],
+            },
+        });
+
+        write_manifest(
+            &root,
+            CompiledBundleManifest {
+                format_version: COMPILED_BUNDLE_FORMAT_VERSION,
+                module_name: "bundle_list_round_trip".to_string(),
+                program: program.clone(),
+                function_symbols: BTreeMap::from([(0, "fabc_fn_0".to_string())]),
+            },
+        );
+
+        let bundle = CompiledBundle::load(&root).expect("load bundle with lists");
+        assert_eq!(bundle.manifest.program, program);
+    }
+
     fn minimal_program() -> StoryProgram {
         StoryProgram {
             start_part: "intro".to_string(),
             metadata: BTreeMap::new(),
             parts: vec![PartSpec {
                 id: "intro".to_string(),
+                properties: BTreeMap::new(),
+                on_enter: None,
+                on_exit: None,
                 steps: vec![StepSpec::Narration(QuoteSpec {
                     node_id: 0,
                     text: "Hello".to_string(),
+                    segments: Vec::new(),
                     properties: BTreeMap::new(),
                     next_action: None,
+                    guard: None,
+                    once: false,
+                    fallback: false,
                 })],
             }],
             functions: Vec::new(),
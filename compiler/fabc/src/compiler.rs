@@ -8,6 +8,8 @@ use std::{
     time::{SystemTime, UNIX_EPOCH},
 };
 
+use fabc_analyzer::{HostFunctionDecl, StoryGraph};
+use fabc_error::{Diagnostic, Severity};
 use fabc_llvm::{
     compile::{CompiledLlvmArtifact, StoryCompiler},
     ir::StoryProgram,
@@ -25,6 +27,9 @@ pub struct CompileOptions {
     pub object_output: Option<PathBuf>,
     pub module_name: Option<String>,
     pub bundle_output: Option<PathBuf>,
+    /// Host functions the embedding application registers, so scripts calling them
+    /// type-check.
+    pub host_functions: Vec<HostFunctionDecl>,
 }
 
 impl CompileOptions {
@@ -35,6 +40,7 @@ impl CompileOptions {
             object_output: None,
             module_name: None,
             bundle_output: None,
+            host_functions: Vec::new(),
         }
     }
 }
@@ -54,6 +60,7 @@ pub struct CompileArtifact {
     pub module_name: String,
     pub llvm_ir: String,
     pub bundle: Option<CompileBundleArtifact>,
+    pub warnings: Vec<Diagnostic>,
 }
 
 #[derive(Debug, Clone)]
@@ -62,6 +69,9 @@ pub struct ExecutableOptions {
     pub output: Option<PathBuf>,
     pub module_name: Option<String>,
     pub release: bool,
+    /// Host functions the embedding application registers, so scripts calling them
+    /// type-check.
+    pub host_functions: Vec<HostFunctionDecl>,
 }
 
 impl ExecutableOptions {
@@ -71,6 +81,7 @@ impl ExecutableOptions {
             output: None,
             module_name: None,
             release: false,
+            host_functions: Vec::new(),
         }
     }
 }
@@ -80,6 +91,7 @@ pub struct ExecutableArtifact {
     pub entry: PathBuf,
     pub output_path: PathBuf,
     pub module_name: String,
+    pub warnings: Vec<Diagnostic>,
 }
 
 #[derive(Debug, Default, Clone, Copy)]
@@ -108,6 +120,83 @@ impl Compiler {
             .map_err(Error::from)
     }
 
+    pub fn build_program_with_warnings(
+        &self,
+        entry: impl AsRef<Path>,
+    ) -> Result<(StoryProgram, Vec<Diagnostic>)> {
+        StoryCompiler
+            .lower_entry_with_warnings(entry.as_ref())
+            .map_err(Error::from)
+    }
+
+    /// Like [`Compiler::build_program_with_warnings`], also accepting calls to the host functions
+    /// the embedding application registers with `StoryMachine::register_function`.
+    pub fn build_program_with_host_functions(
+        &self,
+        entry: impl AsRef<Path>,
+        host_functions: &[HostFunctionDecl],
+    ) -> Result<(StoryProgram, Vec<Diagnostic>)> {
+        StoryCompiler
+            .lower_entry_with_host_functions(entry.as_ref(), host_functions)
+            .map_err(Error::from)
+    }
+
+    /// Links, parses and analyzes `entry` without lowering or touching LLVM.
+    pub fn check(&self, entry: impl AsRef<Path>) -> Result<Vec<Diagnostic>> {
+        self.check_with_host_functions(entry, &[])
+    }
+
+    /// Like [`Compiler::check`], also accepting calls to the declared host functions.
+    pub fn check_with_host_functions(
+        &self,
+        entry: impl AsRef<Path>,
+        host_functions: &[HostFunctionDecl],
+    ) -> Result<Vec<Diagnostic>> {
+        StoryCompiler
+            .check_entry_with_host_functions(entry.as_ref(), host_functions)
+            .map_err(Error::from)
+    }
+
+    /// Links and analyzes `entry` and returns its part graph, along with any warnings.
+    pub fn graph(&self, entry: impl AsRef<Path>) -> Result<(StoryGraph, Vec<Diagnostic>)> {
+        self.graph_with_host_functions(entry, &[])
+    }
+
+    /// Like [`Compiler::graph`], also accepting calls to the declared host functions.
+    pub fn graph_with_host_functions(
+        &self,
+        entry: impl AsRef<Path>,
+        host_functions: &[HostFunctionDecl],
+    ) -> Result<(StoryGraph, Vec<Diagnostic>)> {
+        StoryCompiler
+            .graph_entry_with_host_functions(entry.as_ref(), host_functions)
+            .map_err(Error::from)
+    }
+
+    /// Reads `path` and returns its source in canonical layout. Imports are not followed.
+    pub fn format(&self, path: impl AsRef<Path>) -> Result<String> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path).map_err(|source| Error::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        fabc_fmt::format_source(&source).map_err(|errors| Error::ParseDiagnostics {
+            path: path.to_path_buf(),
+            diagnostics: errors
+                .into_iter()
+                .map(|error| {
+                    Diagnostic::new(
+                        Severity::Error,
+                        Some(path.to_path_buf()),
+                        source.as_str(),
+                        error,
+                    )
+                })
+                .collect(),
+        })
+    }
+
     pub fn emit_llvm_ir(
         &self,
         entry: impl AsRef<Path>,
@@ -127,7 +216,8 @@ impl Compiler {
         let module_name = options
             .module_name
             .unwrap_or_else(|| default_module_name(&options.entry));
-        let program = self.build_program(&options.entry)?;
+        let (program, warnings) =
+            self.build_program_with_host_functions(&options.entry, &options.host_functions)?;
         let object_output = options.object_output.clone();
         let compiled = if let Some(object_output) = object_output.as_ref() {
             if let Some(parent) = object_output.parent() {
@@ -175,6 +265,7 @@ impl Compiler {
             module_name,
             llvm_ir: compiled.llvm_ir,
             bundle,
+            warnings,
         })
     }
 
@@ -182,7 +273,8 @@ impl Compiler {
         let module_name = options
             .module_name
             .unwrap_or_else(|| default_module_name(&options.entry));
-        let program = self.build_program(&options.entry)?;
+        let (program, warnings) =
+            self.build_program_with_host_functions(&options.entry, &options.host_functions)?;
         let story_json =
             serde_json::to_string_pretty(&program).map_err(Error::StandaloneStorySerialize)?;
         let output_path = options
@@ -286,6 +378,7 @@ impl Compiler {
             entry: options.entry,
             output_path,
             module_name,
+            warnings,
         })
     }
 
@@ -447,12 +540,23 @@ fn run() -> Result<(), Box<dyn StdError>> {
             }
             StoryEvent::Selection(selection) => {
                 for (index, choice) in selection.choices.iter().enumerate() {
-                    println!("{}. {}", index + 1, choice.text);
+                    if choice.enabled {
+                        println!("{}. {}", index + 1, choice.text);
+                    } else {
+                        println!("{}. {} (unavailable)", index + 1, choice.text);
+                    }
                 }
 
-                let choice = prompt_choice(selection.choices.len())?;
+                let enabled: Vec<bool> = selection.choices.iter().map(|choice| choice.enabled).collect();
+                let choice = prompt_choice(&enabled)?;
                 event = machine.choose(choice)?;
             }
+            StoryEvent::Directive { name, args } => {
+                let args: Vec<String> = args.iter().map(ToString::to_string).collect();
+                println!("! {}({})", name, args.join(", "));
+                event = machine.advance()?;
+            }
+            StoryEvent::PartEntered { .. } => event = machine.advance()?,
             StoryEvent::Finished => return Ok(()),
         }
     }
@@ -475,7 +579,8 @@ fn prompt_continue() -> io::Result<()> {
     Ok(())
 }
 
-fn prompt_choice(choice_count: usize) -> io::Result<usize> {
+fn prompt_choice(enabled: &[bool]) -> io::Result<usize> {
+    let choice_count = enabled.len();
     let stdin = io::stdin();
     let mut line = String::new();
 
@@ -493,7 +598,11 @@ fn prompt_choice(choice_count: usize) -> io::Result<usize> {
         };
 
         if (1..=choice_count).contains(&index) {
-            return Ok(index - 1);
+            if enabled[index - 1] {
+                return Ok(index - 1);
+            }
+            eprintln!("That choice is unavailable.");
+            continue;
         }
 
         eprintln!("Enter a number between 1 and {choice_count}.");
@@ -578,7 +687,7 @@ mod tests {
     use serde_json::Value;
 
     use super::{CompileOptions, Compiler, ExecutableOptions};
-    use crate::{CompiledBundle, Error};
+    use crate::{CompiledBundle, DataType, Error, HostFunctionDecl, StoryMachine, StoryValue};
 
     #[test]
     fn compiles_entry_with_static_imports() {
@@ -652,6 +761,7 @@ mod tests {
             object_output: Some(object_path.clone()),
             module_name: Some("object_story".to_string()),
             bundle_output: None,
+            host_functions: Vec::new(),
         })
         .expect("emit object file");
 
@@ -698,6 +808,7 @@ mod tests {
             output: Some(executable_path.clone()),
             module_name: Some("standalone_story".to_string()),
             release: false,
+            host_functions: Vec::new(),
         })
         .expect("build standalone executable");
 
@@ -864,11 +975,114 @@ mod tests {
         };
 
         assert!(diagnostics.iter().any(|diagnostic| matches!(
-            diagnostic.kind,
+            diagnostic.error.kind,
             ErrorKind::Compile(CompileErrorKind::UninitializedVariable)
         )));
     }
 
+    #[test]
+    fn builds_programs_that_call_declared_host_functions() {
+        let root = temp_case_dir("host_functions");
+        fs::create_dir_all(&root).expect("create temp dir");
+
+        let entry = root.join("entry.fab");
+
+        fs::write(
+            &entry,
+            r#"
+            Story { start: "intro" }
+
+            # intro
+            * "You find a sword." {
+                next: () => {
+                    context.items = give_item("sword");
+                }
+            }
+            "#,
+        )
+        .expect("write entry");
+
+        let error = Compiler
+            .build_program(&entry)
+            .expect_err("undeclared host function should fail analysis");
+        assert!(matches!(error, Error::SemanticDiagnostics { .. }));
+
+        let declarations = [HostFunctionDecl::new(
+            "give_item",
+            vec![DataType::String],
+            DataType::Number,
+        )];
+        let (program, _) = Compiler
+            .build_program_with_host_functions(&entry, &declarations)
+            .expect("declared host function should type-check");
+
+        let mut machine = StoryMachine::new(program).expect("build story machine");
+        machine.register_function("give_item", |args| {
+            assert_eq!(args, [StoryValue::String("sword".to_string())]);
+            Ok(StoryValue::Number(1.0))
+        });
+        machine.start().expect("start story");
+        machine.advance().expect("call host function");
+
+        assert_eq!(
+            machine.context_value("items"),
+            Some(StoryValue::Number(1.0))
+        );
+    }
+
+    #[test]
+    fn compiles_bundles_that_call_declared_host_functions() {
+        let root = temp_case_dir("host_function_bundle");
+        fs::create_dir_all(&root).expect("create temp dir");
+
+        let entry = root.join("entry.fab");
+        let bundle_dir = root.join("bundle");
+
+        fs::write(
+            &entry,
+            r#"
+            Story { start: "intro" }
+
+            # intro
+            * "You find a sword." {
+                next: () => {
+                    context.items = give_item("sword");
+                }
+            }
+            "#,
+        )
+        .expect("write entry");
+
+        let mut options = CompileOptions::new(&entry);
+        options.module_name = Some("host_function_bundle".to_string());
+        options.bundle_output = Some(bundle_dir.clone());
+        Compiler::compile_with_options(options.clone())
+            .expect_err("undeclared host function should fail analysis");
+
+        options.host_functions = vec![HostFunctionDecl::new(
+            "give_item",
+            vec![DataType::String],
+            DataType::Number,
+        )];
+        Compiler::compile_with_options(options).expect("compile bundle");
+
+        let bundle = CompiledBundle::load(&bundle_dir).expect("load bundle");
+        let mut machine = bundle
+            .native_story_machine()
+            .expect("build native story machine");
+        machine.register_function("give_item", |args| {
+            assert_eq!(args, [StoryValue::String("sword".to_string())]);
+            Ok(StoryValue::Number(1.0))
+        });
+        machine.start().expect("start story");
+        machine.advance().expect("call host function");
+
+        assert_eq!(
+            machine.context_value("items"),
+            Some(StoryValue::Number(1.0))
+        );
+    }
+
     #[test]
     fn writes_compiled_bundle_manifest() {
         let root = temp_case_dir("bundle_manifest");
@@ -901,6 +1115,7 @@ mod tests {
             object_output: None,
             module_name: Some("bundle_story".to_string()),
             bundle_output: Some(bundle_dir.clone()),
+            host_functions: Vec::new(),
         })
         .expect("compile bundle");
 
@@ -951,6 +1166,7 @@ mod tests {
             object_output: None,
             module_name: Some("bundle_playback".to_string()),
             bundle_output: Some(bundle_dir.clone()),
+            host_functions: Vec::new(),
         })
         .expect("compile bundle");
 
@@ -1008,6 +1224,7 @@ mod tests {
             object_output: None,
             module_name: Some("bundle_fallback".to_string()),
             bundle_output: Some(bundle_dir.clone()),
+            host_functions: Vec::new(),
         })
         .expect("compile bundle");
 
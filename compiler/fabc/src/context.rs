@@ -0,0 +1,35 @@
+use std::collections::BTreeMap;
+
+use fabc_rt::Value;
+
+use crate::error::{Error, Result};
+
+/// Parses a JSON object, such as `{"trust": 2, "flags": {"met": true}}`, into context values.
+pub fn parse_context(source: &str) -> Result<BTreeMap<String, Value>> {
+    match serde_json::from_str(source) {
+        Ok(serde_json::Value::Object(values)) => Ok(values
+            .into_iter()
+            .map(|(key, value)| (key, json_value(value)))
+            .collect()),
+        Ok(_) => Err(Error::InvalidContext("expected a JSON object".to_string())),
+        Err(error) => Err(Error::InvalidContext(error.to_string())),
+    }
+}
+
+pub(crate) fn json_value(value: serde_json::Value) -> Value {
+    match value {
+        serde_json::Value::Null => Value::None,
+        serde_json::Value::Bool(value) => Value::Boolean(value),
+        serde_json::Value::Number(value) => Value::Number(value.as_f64().unwrap_or(f64::NAN)),
+        serde_json::Value::String(value) => Value::String(value),
+        serde_json::Value::Array(values) => {
+            Value::list(values.into_iter().map(json_value).collect())
+        }
+        serde_json::Value::Object(values) => Value::object(
+            values
+                .into_iter()
+                .map(|(key, value)| (key, json_value(value)))
+                .collect(),
+        ),
+    }
+}
@@ -1,5 +1,6 @@
 use std::{io, path::PathBuf, result::Result as StdResult};
 
+use fabc_error::Diagnostic;
 use fabc_llvm::Error as LlvmError;
 use fabc_rt::RuntimeError as StoryRuntimeError;
 use serde_json::Error as JsonError;
@@ -12,13 +13,13 @@ pub enum Error {
         #[source]
         source: io::Error,
     },
-    #[error("parser reported diagnostics for `{path}`: {diagnostics:?}")]
+    #[error("parser reported diagnostics for `{path}`:\n{}", Diagnostic::render_all(.diagnostics, false))]
     ParseDiagnostics {
         path: PathBuf,
-        diagnostics: Vec<fabc_error::Error>,
+        diagnostics: Vec<Diagnostic>,
     },
-    #[error("semantic analysis reported diagnostics: {diagnostics:?}")]
-    SemanticDiagnostics { diagnostics: Vec<fabc_error::Error> },
+    #[error("semantic analysis reported diagnostics:\n{}", Diagnostic::render_all(.diagnostics, false))]
+    SemanticDiagnostics { diagnostics: Vec<Diagnostic> },
     #[error("`{path}` does not contain a story")]
     MissingStory { path: PathBuf },
     #[error("`{path}` contains multiple story blocks: {count}")]
@@ -59,12 +60,38 @@ pub enum Error {
         status: i32,
         stderr: String,
     },
+    #[error("{}:{line}: {message}", .path.display())]
+    InvalidPlaytest {
+        path: PathBuf,
+        line: usize,
+        message: String,
+    },
+    #[error("invalid story context: {0}")]
+    InvalidContext(String),
+    #[error("invalid host function manifest `{path}`: {source}")]
+    InvalidHostFunctions {
+        path: PathBuf,
+        #[source]
+        source: JsonError,
+    },
     #[error(transparent)]
     Backend(LlvmError),
 }
 
 pub type Result<T> = StdResult<T, Error>;
 
+impl Error {
+    /// Source diagnostics carried by this error, if it failed on the story text itself.
+    pub fn diagnostics(&self) -> Option<&[Diagnostic]> {
+        match self {
+            Error::ParseDiagnostics { diagnostics, .. }
+            | Error::SemanticDiagnostics { diagnostics } => Some(diagnostics),
+            Error::Backend(error) => error.diagnostics(),
+            _ => None,
+        }
+    }
+}
+
 impl From<LlvmError> for Error {
     fn from(error: LlvmError) -> Self {
         match error {
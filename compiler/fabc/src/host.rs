@@ -0,0 +1,136 @@
+use std::{collections::BTreeMap, fs, path::Path};
+
+use fabc_analyzer::{types::DataType, HostFunctionDecl};
+use fabc_rt::{RuntimeError, StoryMachine};
+use serde::Deserialize;
+
+use crate::context::json_value;
+use crate::error::{Error, Result};
+
+/// Host functions declared in a JSON manifest, so tools without the embedding application can
+/// check and play stories that call them:
+///
+/// ```json
+/// { "reputation": { "parameters": ["string"], "returns": "number", "result": 3 } }
+/// ```
+///
+/// Types are `number`, `boolean`, `string`, `none` or `unknown`. A function with no parameters
+/// may leave them out, and one without `returns` returns `none`. When a tool plays the story,
+/// each function returns its `result`, and calling one that has none is a runtime error.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HostFunctions {
+    pub declarations: Vec<HostFunctionDecl>,
+    results: BTreeMap<String, serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ManifestEntry {
+    #[serde(default)]
+    parameters: Vec<ManifestType>,
+    #[serde(default)]
+    returns: ManifestType,
+    result: Option<serde_json::Value>,
+}
+
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ManifestType {
+    Number,
+    Boolean,
+    String,
+    #[default]
+    None,
+    Unknown,
+}
+
+impl From<ManifestType> for DataType {
+    fn from(r#type: ManifestType) -> Self {
+        match r#type {
+            ManifestType::Number => DataType::Number,
+            ManifestType::Boolean => DataType::Boolean,
+            ManifestType::String => DataType::String,
+            ManifestType::None => DataType::None,
+            ManifestType::Unknown => DataType::Unknown,
+        }
+    }
+}
+
+impl HostFunctions {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path).map_err(|source| Error::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        Self::parse(&source).map_err(|source| Error::InvalidHostFunctions {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    pub fn parse(source: &str) -> serde_json::Result<Self> {
+        let entries: BTreeMap<String, ManifestEntry> = serde_json::from_str(source)?;
+
+        let mut functions = Self::default();
+        for (name, entry) in entries {
+            functions.declarations.push(HostFunctionDecl::new(
+                name.clone(),
+                entry.parameters.into_iter().map(DataType::from).collect(),
+                entry.returns.into(),
+            ));
+            if let Some(result) = entry.result {
+                functions.results.insert(name, result);
+            }
+        }
+        Ok(functions)
+    }
+
+    /// Registers a stand-in for every declared function that returns its `result`.
+    pub fn register(&self, machine: &mut StoryMachine) {
+        for declaration in &self.declarations {
+            let name = declaration.name.clone();
+            let result = self.results.get(&name).cloned();
+            machine.register_function(name.clone(), move |_| match &result {
+                Some(result) => Ok(json_value(result.clone())),
+                None => Err(RuntimeError::Host(format!(
+                    "host function `{name}` has no `result` in the manifest"
+                ))),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fabc_analyzer::{types::DataType, HostFunctionDecl};
+
+    use super::HostFunctions;
+
+    #[test]
+    fn parses_declarations_and_results() {
+        let functions = HostFunctions::parse(
+            r#"{
+                "reputation": { "parameters": ["string"], "returns": "number", "result": 3 },
+                "save_game": {}
+            }"#,
+        )
+        .expect("parse manifest");
+
+        assert_eq!(
+            functions.declarations,
+            [
+                HostFunctionDecl::new("reputation", vec![DataType::String], DataType::Number),
+                HostFunctionDecl::new("save_game", Vec::new(), DataType::None),
+            ]
+        );
+        assert_eq!(functions.results.len(), 1);
+    }
+
+    #[test]
+    fn rejects_unknown_types_and_fields() {
+        assert!(HostFunctions::parse(r#"{ "f": { "returns": "float" } }"#).is_err());
+        assert!(HostFunctions::parse(r#"{ "f": { "params": [] } }"#).is_err());
+    }
+}
@@ -1,11 +1,22 @@
 pub mod bundle;
 mod compiler;
+mod context;
 pub mod error;
+mod host;
+pub mod playtest;
 
 pub use bundle::{CompiledBundle, CompiledBundleManifest, COMPILED_BUNDLE_FORMAT_VERSION};
 pub use compiler::{
     CompileArtifact, CompileBundleArtifact, CompileOptions, Compiler, ExecutableArtifact,
     ExecutableOptions,
 };
+pub use context::parse_context;
 pub use error::{Error, Result};
-pub use fabc_rt::{RuntimeError as StoryRuntimeError, StoryEvent, StoryMachine};
+pub use fabc_analyzer::{types::DataType, HostFunctionDecl, StoryEdge, StoryGraph};
+pub use fabc_error::{Diagnostic, Severity};
+pub use fabc_rt::{
+    explore, simulate, ChoiceStrategy, ExploreOptions, ExploreReport, PathChoice,
+    RuntimeError as StoryRuntimeError, SimulateOptions, SimulationReport, StoryEvent, StoryMachine,
+    StorySnapshot, Value as StoryValue,
+};
+pub use host::HostFunctions;
@@ -0,0 +1,613 @@
+//! Scripted playthroughs (`.fabtest` files) that drive a [`StoryMachine`] and check what it emits.
+//!
+//! A test file reads like a transcript of the story:
+//!
+//! ```text
+//! // Comments start with `//`.
+//! story "./story.fab"
+//! seed 7
+//!
+//! test "takes the ceiling"
+//! [Jose] "What's up"
+//! - "The ceiling."
+//! * "You both look up."
+//! context mood == "curious"
+//! ending dialogue_2
+//! ```
+//!
+//! `story` defaults to the `.fab` file next to the test with the same name, and `seed` to none.
+//! Each `test` starts a fresh playthrough. `[speaker] "text"` and `* "text"` must match the next
+//! dialogue or narration line exactly. `- "text"` (or `- 2` for the second choice) picks a
+//! choice, and `ending [part]` expects the story to have finished, optionally in `part`. Both
+//! skip over lines the test does not spell out. `context path == value` compares a context value,
+//! reached through dotted keys, with a JSON value or a `@part` reference.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    result::Result as StdResult,
+};
+
+use fabc_llvm::ir::StoryProgram;
+use fabc_rt::{RuntimeError, StoryEvent, StoryMachine, Value};
+
+use crate::context::json_value;
+use crate::error::{Error, Result};
+use crate::host::HostFunctions;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Playtest {
+    /// The story under test, resolved against the test file's directory.
+    pub story: PathBuf,
+    pub seed: Option<u64>,
+    pub cases: Vec<PlaytestCase>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlaytestCase {
+    pub name: String,
+    pub line: usize,
+    pub steps: Vec<PlaytestStep>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlaytestStep {
+    pub line: usize,
+    pub expectation: Expectation,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expectation {
+    Dialogue { speaker: String, text: String },
+    Narration { text: String },
+    Choose(ChoiceRef),
+    Context { path: Vec<String>, value: Value },
+    Ending { part: Option<String> },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChoiceRef {
+    Text(String),
+    /// Zero-based position among all offered choices.
+    Index(usize),
+}
+
+/// Outcome of one [`PlaytestCase`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaseReport {
+    pub name: String,
+    pub failure: Option<PlaytestFailure>,
+}
+
+/// The first step of a case that did not hold, with what was expected and what happened.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlaytestFailure {
+    pub line: usize,
+    pub expected: String,
+    pub found: String,
+}
+
+impl Playtest {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path).map_err(|source| Error::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        Self::parse(path, &source)
+    }
+
+    /// Parses the text of the test file at `path`; the path only resolves the story.
+    pub fn parse(path: &Path, source: &str) -> Result<Self> {
+        let invalid = |line: usize, message: String| Error::InvalidPlaytest {
+            path: path.to_path_buf(),
+            line,
+            message,
+        };
+
+        let mut story = None;
+        let mut seed = None;
+        let mut cases = Vec::<PlaytestCase>::new();
+
+        for (index, text) in source.lines().enumerate() {
+            let line = index + 1;
+            let text = text.trim();
+            if text.is_empty() || text.starts_with("//") {
+                continue;
+            }
+
+            let (keyword, rest) = text
+                .split_once(char::is_whitespace)
+                .map_or((text, ""), |(keyword, rest)| (keyword, rest.trim()));
+
+            let expectation = match keyword {
+                "story" if cases.is_empty() => {
+                    story = Some(string(rest).map_err(|message| invalid(line, message))?);
+                    continue;
+                }
+                "seed" if cases.is_empty() => {
+                    seed = Some(rest.parse().map_err(|_| {
+                        invalid(
+                            line,
+                            format!("expected a whole number seed, found `{rest}`"),
+                        )
+                    })?);
+                    continue;
+                }
+                "test" => {
+                    cases.push(PlaytestCase {
+                        name: string(rest).map_err(|message| invalid(line, message))?,
+                        line,
+                        steps: Vec::new(),
+                    });
+                    continue;
+                }
+                "context" => context(rest),
+                "ending" => Ok(Expectation::Ending {
+                    part: (!rest.is_empty()).then(|| rest.to_string()),
+                }),
+                "*" => string(rest).map(|text| Expectation::Narration { text }),
+                "-" => match rest.parse::<usize>() {
+                    Ok(0) => Err("choices are numbered from 1".to_string()),
+                    Ok(number) => Ok(Expectation::Choose(ChoiceRef::Index(number - 1))),
+                    Err(_) => string(rest).map(|text| Expectation::Choose(ChoiceRef::Text(text))),
+                },
+                _ => match text.strip_prefix('[').and_then(|text| text.split_once(']')) {
+                    Some((speaker, rest)) => {
+                        string(rest.trim()).map(|text| Expectation::Dialogue {
+                            speaker: speaker.trim().to_string(),
+                            text,
+                        })
+                    }
+                    None => Err(format!("unknown step `{text}`")),
+                },
+            }
+            .map_err(|message| invalid(line, message))?;
+
+            let Some(case) = cases.last_mut() else {
+                return Err(invalid(
+                    line,
+                    "steps must follow a `test \"name\"` line".to_string(),
+                ));
+            };
+            case.steps.push(PlaytestStep { line, expectation });
+        }
+
+        let story = match story {
+            Some(story) => path.parent().unwrap_or_else(|| Path::new("")).join(story),
+            None => path.with_extension("fab"),
+        };
+
+        Ok(Self { story, seed, cases })
+    }
+
+    /// Plays every case against `program`, each from a fresh machine.
+    pub fn run(&self, program: &StoryProgram) -> Vec<CaseReport> {
+        self.run_with_host_functions(program, &HostFunctions::default())
+    }
+
+    /// Like [`Playtest::run`], with `host_functions` registered on every machine.
+    pub fn run_with_host_functions(
+        &self,
+        program: &StoryProgram,
+        host_functions: &HostFunctions,
+    ) -> Vec<CaseReport> {
+        self.cases
+            .iter()
+            .map(|case| CaseReport {
+                name: case.name.clone(),
+                failure: self.run_case(program, host_functions, case).err(),
+            })
+            .collect()
+    }
+
+    fn run_case(
+        &self,
+        program: &StoryProgram,
+        host_functions: &HostFunctions,
+        case: &PlaytestCase,
+    ) -> StdResult<(), PlaytestFailure> {
+        let failure = |line: usize, expected: String, found: String| PlaytestFailure {
+            line,
+            expected,
+            found,
+        };
+        let runtime = |line: usize, expected: &str| {
+            let expected = expected.to_string();
+            move |error: RuntimeError| failure(line, expected, format!("runtime error: {error}"))
+        };
+
+        let mut machine =
+            StoryMachine::new(program.clone()).map_err(runtime(case.line, "story to start"))?;
+        host_functions.register(&mut machine);
+        if let Some(seed) = self.seed {
+            machine.set_seed(seed);
+        }
+        let mut event = machine
+            .start()
+            .map_err(runtime(case.line, "story to start"))?;
+        let mut last_part = machine.current_part().map(ToOwned::to_owned);
+
+        for step in &case.steps {
+            let line = step.line;
+            let expected = describe_expectation(&step.expectation);
+
+            // Directives and part entries are cues for the host, so cases read as if they were
+            // not there.
+            while matches!(
+                event,
+                StoryEvent::Directive { .. } | StoryEvent::PartEntered { .. }
+            ) {
+                event = machine.advance().map_err(runtime(line, &expected))?;
+            }
+
+            if matches!(
+                step.expectation,
+                Expectation::Choose(_) | Expectation::Ending { .. }
+            ) {
+                while matches!(
+                    event,
+                    StoryEvent::Narration(_)
+                        | StoryEvent::Dialogue(_)
+                        | StoryEvent::Directive { .. }
+                        | StoryEvent::PartEntered { .. }
+                ) {
+                    event = machine.advance().map_err(runtime(line, &expected))?;
+                    if let Some(part) = machine.current_part() {
+                        last_part = Some(part.to_string());
+                    }
+                }
+            }
+
+            let next = match (&step.expectation, &event) {
+                (Expectation::Dialogue { speaker, text }, StoryEvent::Dialogue(view))
+                    if view.speaker == *speaker && view.text == *text =>
+                {
+                    machine.advance()
+                }
+                (Expectation::Narration { text }, StoryEvent::Narration(view))
+                    if view.text == *text =>
+                {
+                    machine.advance()
+                }
+                (Expectation::Choose(choice), StoryEvent::Selection(selection)) => {
+                    let index = match choice {
+                        ChoiceRef::Text(text) => selection
+                            .choices
+                            .iter()
+                            .position(|choice| choice.enabled && choice.text == *text),
+                        ChoiceRef::Index(index) => Some(*index).filter(|index| {
+                            selection
+                                .choices
+                                .get(*index)
+                                .is_some_and(|choice| choice.enabled)
+                        }),
+                    };
+                    let Some(index) = index else {
+                        return Err(failure(line, expected, describe_event(&event)));
+                    };
+                    machine.choose(index)
+                }
+                (Expectation::Context { path, value }, _) => {
+                    let found = match context_value(&machine, path) {
+                        Some(found) if found == *value => continue,
+                        Some(found) => {
+                            format!("context {} == {}", path.join("."), describe_value(&found))
+                        }
+                        None => format!("context {} is not set", path.join(".")),
+                    };
+                    return Err(failure(line, expected, found));
+                }
+                (Expectation::Ending { part }, StoryEvent::Finished)
+                    if part.is_none() || *part == last_part =>
+                {
+                    continue;
+                }
+                (Expectation::Ending { .. }, StoryEvent::Finished) => {
+                    return Err(failure(
+                        line,
+                        expected,
+                        format!("ending {}", last_part.unwrap_or_default()),
+                    ));
+                }
+                _ => return Err(failure(line, expected, describe_event(&event))),
+            };
+
+            event = next.map_err(runtime(line, &expected))?;
+            if let Some(part) = machine.current_part() {
+                last_part = Some(part.to_string());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn context(rest: &str) -> StdResult<Expectation, String> {
+    let Some((path, value)) = rest.split_once("==") else {
+        return Err("expected `context <path> == <value>`".to_string());
+    };
+    let path = path.trim();
+    if path.is_empty() {
+        return Err("expected a context key before `==`".to_string());
+    }
+
+    let value = value.trim();
+    let value = match value.strip_prefix('@') {
+        Some(part) => Value::StoryRef(part.to_string()),
+        None => serde_json::from_str::<serde_json::Value>(value)
+            .map(json_value)
+            .map_err(|_| format!("expected a JSON value or `@part`, found `{value}`"))?,
+    };
+
+    Ok(Expectation::Context {
+        path: path.split('.').map(|key| key.trim().to_string()).collect(),
+        value,
+    })
+}
+
+/// A double-quoted string with JSON escapes.
+fn string(text: &str) -> StdResult<String, String> {
+    serde_json::from_str(text).map_err(|_| format!("expected a quoted string, found `{text}`"))
+}
+
+fn context_value(machine: &StoryMachine, path: &[String]) -> Option<Value> {
+    let (first, rest) = path.split_first()?;
+    rest.iter()
+        .try_fold(machine.context_value(first)?, |value, key| {
+            value.get_member(&Value::String(key.clone())).ok()
+        })
+}
+
+fn describe_expectation(expectation: &Expectation) -> String {
+    match expectation {
+        Expectation::Dialogue { speaker, text } => format!("[{speaker}] {}", quoted(text)),
+        Expectation::Narration { text } => format!("* {}", quoted(text)),
+        Expectation::Choose(ChoiceRef::Text(text)) => format!("- {}", quoted(text)),
+        Expectation::Choose(ChoiceRef::Index(index)) => format!("- {}", index + 1),
+        Expectation::Context { path, value } => {
+            format!("context {} == {}", path.join("."), describe_value(value))
+        }
+        Expectation::Ending { part: Some(part) } => format!("ending {part}"),
+        Expectation::Ending { part: None } => "ending".to_string(),
+    }
+}
+
+fn describe_event(event: &StoryEvent) -> String {
+    match event {
+        StoryEvent::Dialogue(view) => format!("[{}] {}", view.speaker, quoted(&view.text)),
+        StoryEvent::Narration(view) => format!("* {}", quoted(&view.text)),
+        StoryEvent::Selection(selection) => {
+            let choices = selection
+                .choices
+                .iter()
+                .map(|choice| {
+                    if choice.enabled {
+                        quoted(&choice.text)
+                    } else {
+                        format!("{} (unavailable)", quoted(&choice.text))
+                    }
+                })
+                .collect::<Vec<_>>();
+            format!("choices {}", choices.join(", "))
+        }
+        StoryEvent::PartEntered { id, .. } => format!("# {id}"),
+        StoryEvent::Directive { name, args } => {
+            let args = args.iter().map(describe_value).collect::<Vec<_>>();
+            format!("! {name}({})", args.join(", "))
+        }
+        StoryEvent::Finished => "end of story".to_string(),
+    }
+}
+
+fn describe_value(value: &Value) -> String {
+    match value {
+        Value::String(text) => quoted(text),
+        Value::StoryRef(part) => format!("@{part}"),
+        Value::Object(object) => {
+            let entries = object
+                .borrow()
+                .iter()
+                .map(|(key, value)| format!("{}: {}", quoted(key), describe_value(value)))
+                .collect::<Vec<_>>();
+            format!("{{{}}}", entries.join(", "))
+        }
+        Value::List(list) => {
+            let elements = list.borrow().iter().map(describe_value).collect::<Vec<_>>();
+            format!("[{}]", elements.join(", "))
+        }
+        Value::None => "null".to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn quoted(text: &str) -> String {
+    serde_json::Value::from(text).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use fabc_llvm::compile::lower_source;
+    use fabc_reg_test::{COMPLEX_STORY, SIMPLE_STORY};
+
+    use super::{ChoiceRef, Expectation, Playtest, PlaytestFailure};
+    use crate::Error;
+
+    fn playtest(source: &str) -> Playtest {
+        Playtest::parse(Path::new("tests/story.fabtest"), source).expect("parse playtest")
+    }
+
+    #[test]
+    fn parses_header_cases_and_steps() {
+        let playtest = playtest(
+            r#"
+            // Walkthrough.
+            story "../stories/complex.fab"
+            seed 7
+
+            test "greets"
+            [Hero] "Hello there!"
+            - 2
+            - "Who are you?"
+            context stats.total == 30
+            context target == @part_2
+            ending part_2
+            "#,
+        );
+
+        assert_eq!(playtest.story, Path::new("tests/../stories/complex.fab"));
+        assert_eq!(playtest.seed, Some(7));
+        assert_eq!(playtest.cases.len(), 1);
+        let steps = &playtest.cases[0].steps;
+        assert_eq!(steps.len(), 6);
+        assert_eq!(steps[0].line, 7);
+        assert_eq!(
+            steps[1].expectation,
+            Expectation::Choose(ChoiceRef::Index(1))
+        );
+        assert!(matches!(
+            &steps[3].expectation,
+            Expectation::Context { path, .. } if path == &["stats", "total"]
+        ));
+        assert_eq!(
+            steps[5].expectation,
+            Expectation::Ending {
+                part: Some("part_2".to_string())
+            }
+        );
+    }
+
+    #[test]
+    fn choices_skip_lines_the_test_leaves_out() {
+        let program = lower_source(SIMPLE_STORY.replace("dialogue_2", "dialogue_1").as_str())
+            .expect("lower story");
+        let reports = playtest(
+            r#"
+            test "loops back"
+            - "The ceiling."
+            [Jose] "What's up"
+            - 2
+            ending
+            "#,
+        )
+        .run(&program);
+
+        let failure = reports[0].failure.as_ref().expect("story never ends");
+        assert_eq!(failure.line, 6);
+        assert_eq!(failure.expected, "ending");
+        assert_eq!(failure.found, r#"choices "The ceiling.", "Nothing much.""#);
+    }
+
+    #[test]
+    fn defaults_story_to_sibling_fab_file() {
+        assert_eq!(
+            playtest("test \"empty\"").story,
+            Path::new("tests/story.fab")
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_steps_with_line_numbers() {
+        let error = Playtest::parse(Path::new("story.fabtest"), "test \"a\"\nwander off\n")
+            .expect_err("unknown step");
+
+        assert!(matches!(error, Error::InvalidPlaytest { line: 2, .. }));
+        assert_eq!(
+            error.to_string(),
+            "story.fabtest:2: unknown step `wander off`"
+        );
+    }
+
+    #[test]
+    fn passing_playthrough_reports_no_failures() {
+        let program = lower_source(COMPLEX_STORY).expect("lower story");
+        let reports = playtest(
+            r#"
+            test "hero meets villain"
+            [Hero] "Hello there!"
+            context total == 30
+            [Villain] "I've been expecting you."
+            ending part_2
+            "#,
+        )
+        .run(&program);
+
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].failure, None);
+    }
+
+    #[test]
+    fn mismatches_report_expected_and_found() {
+        let program = lower_source(COMPLEX_STORY).expect("lower story");
+        let reports = playtest(
+            r#"
+            test "wrong line"
+            [Hero] "Hi!"
+
+            test "wrong total"
+            [Hero] "Hello there!"
+            context total == 31
+
+            test "wrong ending"
+            ending part_3
+            "#,
+        )
+        .run(&program);
+
+        let failures = reports
+            .into_iter()
+            .map(|report| report.failure.expect("case should fail"))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            failures,
+            [
+                PlaytestFailure {
+                    line: 3,
+                    expected: r#"[Hero] "Hi!""#.to_string(),
+                    found: r#"[Hero] "Hello there!""#.to_string(),
+                },
+                PlaytestFailure {
+                    line: 7,
+                    expected: "context total == 31".to_string(),
+                    found: "context total == 30".to_string(),
+                },
+                PlaytestFailure {
+                    line: 10,
+                    expected: "ending part_3".to_string(),
+                    found: "ending part_2".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn runtime_errors_fail_the_step_that_hit_them() {
+        let program = lower_source(
+            r#"
+            Story { start: "intro" }
+
+            # intro
+            - "Somewhere" { next: () => { goto context.missing; } }
+            "#,
+        )
+        .expect("lower story");
+        let reports = playtest(
+            r#"
+            test "somewhere"
+            - "Somewhere"
+            "#,
+        )
+        .run(&program);
+
+        let failure = reports[0].failure.as_ref().expect("case should fail");
+        assert_eq!(failure.line, 3);
+        assert!(
+            failure.found.starts_with("runtime error:"),
+            "found={}",
+            failure.found
+        );
+    }
+}
@@ -0,0 +1,83 @@
+use fabc_ir::{BuiltinType, BUILTINS};
+
+use crate::types::{DataType, Field, ModuleSymbolType};
+
+/// Signature of a function the embedding application registers on the story machine, declared
+/// up front so scripts calling it type-check.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HostFunctionDecl {
+    pub name: String,
+    pub parameters: Vec<DataType>,
+    pub return_type: DataType,
+}
+
+impl HostFunctionDecl {
+    pub fn new(name: impl Into<String>, parameters: Vec<DataType>, return_type: DataType) -> Self {
+        Self {
+            name: name.into(),
+            parameters,
+            return_type,
+        }
+    }
+
+    pub(crate) fn symbol_type(&self) -> ModuleSymbolType {
+        function_type(
+            self.parameters.iter().cloned().map(ModuleSymbolType::Data),
+            ModuleSymbolType::Data(self.return_type.clone()),
+        )
+    }
+}
+
+/// Type of a builtin namespace such as `str` or a global builtin such as `random`, used when
+/// no user binding shadows the name.
+pub fn builtin_type(name: &str) -> Option<ModuleSymbolType> {
+    if let Some(builtin) = BUILTINS
+        .iter()
+        .find(|builtin| builtin.namespace.is_none() && builtin.name == name)
+    {
+        return Some(signature_type(builtin.parameters, builtin.return_type));
+    }
+
+    let fields: Vec<Field> = BUILTINS
+        .iter()
+        .filter(|builtin| builtin.namespace == Some(name))
+        .map(|builtin| Field {
+            name: builtin.name.to_string(),
+            r#type: Box::new(signature_type(builtin.parameters, builtin.return_type)),
+        })
+        .collect();
+
+    (!fields.is_empty()).then_some(ModuleSymbolType::Data(DataType::Record { fields }))
+}
+
+fn signature_type(parameters: &[BuiltinType], return_type: BuiltinType) -> ModuleSymbolType {
+    function_type(
+        parameters.iter().map(|parameter| data_type(*parameter)),
+        data_type(return_type),
+    )
+}
+
+fn function_type(
+    parameters: impl IntoIterator<Item = ModuleSymbolType>,
+    return_type: ModuleSymbolType,
+) -> ModuleSymbolType {
+    let parameters: Vec<_> = parameters.into_iter().collect();
+    ModuleSymbolType::Function {
+        return_type: Box::new(return_type),
+        arity: parameters.len(),
+        parameters,
+    }
+}
+
+fn data_type(builtin: BuiltinType) -> ModuleSymbolType {
+    ModuleSymbolType::Data(match builtin {
+        BuiltinType::Number => DataType::Number,
+        BuiltinType::String => DataType::String,
+        BuiltinType::Boolean => DataType::Boolean,
+        BuiltinType::None => DataType::None,
+        BuiltinType::Unknown => DataType::Unknown,
+        BuiltinType::StringList => DataType::List {
+            element: Box::new(ModuleSymbolType::Data(DataType::String)),
+        },
+    })
+}
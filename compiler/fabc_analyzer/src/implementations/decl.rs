@@ -1,5 +1,8 @@
 use fabc_error::{kind::CompileErrorKind, Error};
-use fabc_parser::ast::decl::{object::ObjectDecl, quote::QuoteDecl};
+use fabc_parser::ast::decl::{
+    object::ObjectDecl,
+    quote::{QuoteDecl, TextSegment},
+};
 
 use crate::{
     types::{DataType, Field, ModuleSymbolType, SymbolAnnotation},
@@ -8,6 +11,32 @@ use crate::{
 
 impl Analyzable for QuoteDecl {
     fn analyze(&self, analyzer: &mut Analyzer) -> AnalysisResult {
+        for segment in &self.segments {
+            let TextSegment::Expr(expr) = segment else {
+                continue;
+            };
+
+            match expr.analyze(analyzer).mod_sym_type {
+                Some(
+                    sym_type
+                    @ (ModuleSymbolType::Function { .. } | ModuleSymbolType::Module { .. }),
+                ) => {
+                    analyzer.push_error(Error::new(
+                        CompileErrorKind::ExpectedType {
+                            expected: "displayable value".to_string(),
+                            found: sym_type.to_string(),
+                        },
+                        expr.info().span.clone(),
+                    ));
+                }
+                Some(_) => {}
+                None => analyzer.push_error(Error::new(
+                    CompileErrorKind::TypeInference,
+                    expr.info().span.clone(),
+                )),
+            }
+        }
+
         if let Some(properties) = &self.properties {
             properties.analyze(analyzer);
         }
@@ -58,9 +87,38 @@ impl Analyzable for ObjectDecl {
 mod tests {
     use std::collections::BTreeMap;
 
+    use fabc_error::kind::ErrorKind;
+
     use super::*;
     use crate::test_utils::{info, number_expr, string_expr};
 
+    #[test]
+    fn quote_interpolations_are_analyzed() {
+        let quote = fabc_parser::Parser::parse_ast_str::<QuoteDecl>(
+            "\"{1 + 2} apples, {str.upper} and {missing}\"",
+        )
+        .expect("parse failed");
+
+        let analyzer = Analyzer::analyze_ast(&quote).expect("analyze failed");
+
+        let kinds: Vec<_> = analyzer
+            .errors
+            .iter()
+            .map(|error| error.kind.clone())
+            .filter(|kind| !matches!(kind, ErrorKind::Compile(CompileErrorKind::TypeInference)))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ErrorKind::Compile(CompileErrorKind::ExpectedType {
+                    expected: "displayable value".to_string(),
+                    found: "fn(String) -> String".to_string(),
+                }),
+                ErrorKind::Compile(CompileErrorKind::UninitializedVariable),
+            ]
+        );
+    }
+
     #[test]
     fn object_decl_turns_into_record_type() {
         let mut map = BTreeMap::new();
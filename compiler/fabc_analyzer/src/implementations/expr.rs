@@ -2,18 +2,38 @@ use fabc_error::{
     kind::{CompileErrorKind, InternalErrorKind},
     Error,
 };
+use fabc_ir::is_builtin_namespace;
 use fabc_parser::ast::{
     expr::{literal::Literal, primitive::Primitive, Expr, Primary},
     stmt::{block::BlockStmt, Stmt as ParserStmt},
 };
 
 use crate::{
+    builtin_type,
     types::{
         BindingDetails, BindingKind, DataType, ModuleSymbolType, StorySymbolType, SymbolAnnotation,
     },
     AnalysisResult, Analyzable, Analyzer,
 };
 
+/// The builtin namespace an assignment target writes into, as in `str = ...` or
+/// `str.len = ...`, unless a user binding or host function shadows it.
+fn assigned_builtin_namespace(target: &Expr, analyzer: &mut Analyzer) -> Option<String> {
+    match target {
+        Expr::Primary {
+            value: Primary::Primitive(Primitive::Identifier { name, .. }),
+            ..
+        } => {
+            let shadowed = analyzer.mut_mod_sym_table().lookup_symbol(name).is_some()
+                || analyzer.host_function_type(name).is_some();
+            (is_builtin_namespace(name) && !shadowed).then(|| name.clone())
+        }
+        Expr::MemberAccess { left, .. } => assigned_builtin_namespace(left, analyzer),
+        Expr::Grouping { expression, .. } => assigned_builtin_namespace(expression, analyzer),
+        _ => None,
+    }
+}
+
 fn analyze_block_in_current_scope(block: &BlockStmt, analyzer: &mut Analyzer) -> AnalysisResult {
     let mut return_type: Option<ModuleSymbolType> = None;
 
@@ -156,6 +176,14 @@ impl Analyzable for Expr {
                 }
             }
             Expr::Assignment { info, name, value } => {
+                if let Some(namespace) = assigned_builtin_namespace(name, analyzer) {
+                    analyzer.push_error(Error::new(
+                        CompileErrorKind::ReadOnlyNamespace { namespace },
+                        name.info().span.clone(),
+                    ));
+                    return AnalysisResult::default();
+                }
+
                 let name_sym_type = {
                     let Some(sym_type) = name.analyze(analyzer).mod_sym_type else {
                         analyzer.push_error(Error::new(
@@ -349,7 +377,10 @@ impl Analyzable for Expr {
 
                     if supports_dynamic_members(&left_type) {
                         ModuleSymbolType::Data(DataType::Unknown)
-                    } else if let ModuleSymbolType::Data(DataType::Record { .. }) = left_type {
+                    } else if let ModuleSymbolType::Data(
+                        DataType::Record { .. } | DataType::List { .. },
+                    ) = left_type
+                    {
                         left_type
                     } else {
                         analyzer.push_error(Error::new(
@@ -380,8 +411,14 @@ impl Analyzable for Expr {
                 }
 
                 for member in members.iter() {
-                    let member_name = if let Some(member_name) = static_member_name(member) {
-                        member_name
+                    if is_unknown_mod_type(&current_type) {
+                        break;
+                    }
+
+                    let (member_name, index_type) = if let Some(member_name) =
+                        static_member_name(member)
+                    {
+                        (member_name, None)
                     } else {
                         let Some(member_name_type) = member.analyze(analyzer).mod_sym_type else {
                             analyzer.push_error(Error::new(
@@ -390,31 +427,59 @@ impl Analyzable for Expr {
                             ));
                             return AnalysisResult::default();
                         };
-                        member_name_type.to_string()
+                        (member_name_type.to_string(), Some(member_name_type))
                     };
 
-                    if let ModuleSymbolType::Data(DataType::Record { fields }) = &current_type {
-                        if let Some(field) = fields.iter().find(|f| f.name == member_name) {
-                            current_type = (*field.r#type).clone();
-                        } else {
+                    current_type = match &current_type {
+                        ModuleSymbolType::Data(DataType::Record { fields }) => {
+                            if let Some(field) = fields.iter().find(|f| f.name == member_name) {
+                                (*field.r#type).clone()
+                            } else {
+                                analyzer.push_error(Error::new(
+                                    CompileErrorKind::InvalidMemberAccess {
+                                        member: member_name,
+                                    },
+                                    info.span.clone(),
+                                ));
+                                return AnalysisResult::default();
+                            }
+                        }
+                        ModuleSymbolType::Data(DataType::List { element }) => match index_type {
+                            Some(
+                                ModuleSymbolType::Data(DataType::Number)
+                                | ModuleSymbolType::Data(DataType::Unknown),
+                            ) => (**element).clone(),
+                            Some(index_type) => {
+                                analyzer.push_error(Error::new(
+                                    CompileErrorKind::ExpectedType {
+                                        expected: "Number".to_string(),
+                                        found: format!("{index_type}"),
+                                    },
+                                    member.info().span.clone(),
+                                ));
+                                return AnalysisResult::default();
+                            }
+                            None => {
+                                analyzer.push_error(Error::new(
+                                    CompileErrorKind::InvalidMemberAccess {
+                                        member: member_name,
+                                    },
+                                    info.span.clone(),
+                                ));
+                                return AnalysisResult::default();
+                            }
+                        },
+                        _ => {
                             analyzer.push_error(Error::new(
-                                CompileErrorKind::InvalidMemberAccess {
-                                    member: member_name,
+                                CompileErrorKind::ExpectedType {
+                                    expected: "Record".to_string(),
+                                    found: format!("{current_type}"),
                                 },
                                 info.span.clone(),
                             ));
                             return AnalysisResult::default();
                         }
-                    } else {
-                        analyzer.push_error(Error::new(
-                            CompileErrorKind::ExpectedType {
-                                expected: "Record".to_string(),
-                                found: format!("{current_type}"),
-                            },
-                            info.span.clone(),
-                        ));
-                        return AnalysisResult::default();
-                    }
+                    };
                 }
 
                 analyzer.annotate_mod_symbol(
@@ -463,6 +528,45 @@ impl Analyzable for Literal {
 impl Analyzable for Primitive {
     fn analyze(&self, analyzer: &mut Analyzer) -> AnalysisResult {
         match self {
+            Primitive::List { info, elements } => {
+                let mut element_type: Option<ModuleSymbolType> = None;
+                for element in elements {
+                    let Some(analyzed_type) = element.analyze(analyzer).mod_sym_type else {
+                        analyzer.push_error(Error::new(
+                            CompileErrorKind::TypeInference,
+                            info.span.clone(),
+                        ));
+                        return AnalysisResult::default();
+                    };
+
+                    // Mixed lists are allowed; they just lose their element type.
+                    element_type = Some(match element_type {
+                        None => analyzed_type,
+                        Some(current) if current == analyzed_type => current,
+                        Some(_) => ModuleSymbolType::Data(DataType::Unknown),
+                    });
+                }
+
+                let list_type = ModuleSymbolType::Data(DataType::List {
+                    element: Box::new(
+                        element_type.unwrap_or(ModuleSymbolType::Data(DataType::Unknown)),
+                    ),
+                });
+
+                analyzer.annotate_mod_symbol(
+                    self.info().id,
+                    SymbolAnnotation {
+                        name: None,
+                        r#type: list_type.clone(),
+                        binding: None,
+                    },
+                );
+
+                AnalysisResult {
+                    mod_sym_type: Some(list_type),
+                    ..Default::default()
+                }
+            }
             Primitive::Object { info, value } => {
                 let Some(obj_type) = value.analyze(analyzer).mod_sym_type else {
                     analyzer.push_error(Error::new(
@@ -546,6 +650,25 @@ impl Analyzable for Primitive {
                 let current_level = mod_table.current_level();
                 let ident_sym = {
                     let Some(ident_sym) = mod_table.lookup_symbol(name) else {
+                        let global_sym_type = analyzer
+                            .host_function_type(name)
+                            .or_else(|| builtin_type(name));
+                        if let Some(builtin_sym_type) = global_sym_type {
+                            analyzer.annotate_mod_symbol(
+                                self.info().id,
+                                SymbolAnnotation {
+                                    name: Some(name.clone()),
+                                    r#type: builtin_sym_type.clone(),
+                                    binding: None,
+                                },
+                            );
+
+                            return AnalysisResult {
+                                mod_sym_type: Some(builtin_sym_type),
+                                ..Default::default()
+                            };
+                        }
+
                         analyzer.push_error(Error::new(
                             CompileErrorKind::UninitializedVariable,
                             info.span.clone(),
@@ -662,17 +785,19 @@ impl Analyzable for Primitive {
                 }
 
                 let previous_error_count = analyzer.errors.len();
-                let body_sym_type =
-                    match analyze_block_in_current_scope(body, analyzer).mod_sym_type {
-                        Some(sym_type) => sym_type,
-                        None if analyzer.errors.len() == previous_error_count => {
-                            ModuleSymbolType::Data(DataType::None)
-                        }
-                        None => {
-                            analyzer.mut_mod_sym_table().exit_scope();
-                            return AnalysisResult::default();
-                        }
-                    };
+                let enclosing_loop_depth = analyzer.replace_loop_depth(0);
+                let analyzed_body = analyze_block_in_current_scope(body, analyzer);
+                analyzer.replace_loop_depth(enclosing_loop_depth);
+                let body_sym_type = match analyzed_body.mod_sym_type {
+                    Some(sym_type) => sym_type,
+                    None if analyzer.errors.len() == previous_error_count => {
+                        ModuleSymbolType::Data(DataType::None)
+                    }
+                    None => {
+                        analyzer.mut_mod_sym_table().exit_scope();
+                        return AnalysisResult::default();
+                    }
+                };
 
                 analyzer.mut_mod_sym_table().exit_scope();
 
@@ -718,6 +843,7 @@ mod tests {
     use super::*;
     use crate::test_utils::{identifier_expr, info, number_expr, string_expr};
     use crate::types::BindingKind;
+    use crate::HostFunctionDecl;
     use fabc_error::kind::{CompileErrorKind, ErrorKind};
     use fabc_parser::ast::{
         decl::object::ObjectDecl,
@@ -725,6 +851,152 @@ mod tests {
         stmt::{block::BlockStmt, expr::ExprStmt, r#let::LetStmt, r#return::ReturnStmt, Stmt},
     };
 
+    #[test]
+    fn list_indexing_yields_element_type_and_requires_numbers() {
+        let block = fabc_parser::Parser::parse_ast_str::<BlockStmt>(
+            r#"{
+                let bag = [1, 2];
+                let next = bag[0] + 1;
+                bag["first"];
+                bag.size;
+            }"#,
+        )
+        .expect("parse failed");
+
+        let analyzer = Analyzer::analyze_ast(&block).expect("analyze failed");
+
+        let kinds: Vec<_> = analyzer
+            .errors
+            .iter()
+            .map(|error| error.kind.clone())
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ErrorKind::Compile(CompileErrorKind::ExpectedType {
+                    expected: "Number".to_string(),
+                    found: "String".to_string(),
+                }),
+                ErrorKind::Compile(CompileErrorKind::InvalidMemberAccess {
+                    member: "size".to_string(),
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn builtin_namespaces_resolve_unless_shadowed() {
+        let block = fabc_parser::Parser::parse_ast_str::<BlockStmt>(
+            r#"{
+                let length = str.len("hello") + 1;
+                let shout = str.upper(5);
+                num.round(1.5);
+                let keys = obj.keys({ a: 1 })[0];
+                let keys_again = str.upper(keys);
+                let str = { len: 2 };
+                str.len;
+            }"#,
+        )
+        .expect("parse failed");
+
+        let analyzer = Analyzer::analyze_ast(&block).expect("analyze failed");
+
+        let kinds: Vec<_> = analyzer
+            .errors
+            .iter()
+            .map(|error| error.kind.clone())
+            .filter(|kind| !matches!(kind, ErrorKind::Compile(CompileErrorKind::TypeInference)))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ErrorKind::Compile(CompileErrorKind::ExpectedType {
+                    expected: "String".to_string(),
+                    found: "Number".to_string(),
+                }),
+                ErrorKind::Compile(CompileErrorKind::InvalidMemberAccess {
+                    member: "round".to_string(),
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn assignments_to_builtin_namespaces_are_rejected() {
+        let block = fabc_parser::Parser::parse_ast_str::<BlockStmt>(
+            r#"{
+                str.len = 5;
+                num = 1;
+                let list = [1];
+                list = [2];
+            }"#,
+        )
+        .expect("parse failed");
+
+        let analyzer = Analyzer::analyze_ast(&block).expect("analyze failed");
+
+        let kinds: Vec<_> = analyzer
+            .errors
+            .iter()
+            .map(|error| error.kind.clone())
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ErrorKind::Compile(CompileErrorKind::ReadOnlyNamespace {
+                    namespace: "str".to_string(),
+                }),
+                ErrorKind::Compile(CompileErrorKind::ReadOnlyNamespace {
+                    namespace: "num".to_string(),
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn declared_host_functions_type_check_calls() {
+        let block = fabc_parser::Parser::parse_ast_str::<BlockStmt>(
+            r#"{
+                let count = give_item("sword") + 1;
+                give_item(5);
+                give_item();
+                play_sound("bell");
+            }"#,
+        )
+        .expect("parse failed");
+        let mut analyzer = Analyzer {
+            host_functions: vec![HostFunctionDecl::new(
+                "give_item",
+                vec![DataType::String],
+                DataType::Number,
+            )],
+            ..Analyzer::default()
+        };
+
+        block.analyze(&mut analyzer);
+
+        let kinds: Vec<_> = analyzer
+            .errors
+            .iter()
+            .map(|error| error.kind.clone())
+            .filter(|kind| !matches!(kind, ErrorKind::Compile(CompileErrorKind::TypeInference)))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ErrorKind::Compile(CompileErrorKind::ExpectedType {
+                    expected: "String".to_string(),
+                    found: "Number".to_string(),
+                }),
+                ErrorKind::Compile(CompileErrorKind::ArityMismatch {
+                    expected: 1,
+                    found: 0,
+                }),
+                ErrorKind::Compile(CompileErrorKind::UninitializedVariable),
+            ]
+        );
+    }
+
     #[test]
     fn binary_mismatch_reports_error() {
         let expr = Expr::Binary {
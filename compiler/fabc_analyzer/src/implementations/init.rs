@@ -1,24 +1,31 @@
 use std::collections::HashSet;
 
-use fabc_error::{kind::InternalErrorKind, Error};
-use fabc_parser::ast::init::{
-    module::ModuleInit,
-    story::{
-        metadata::Metadata,
-        part::{
-            element::{
-                dialogue::DialogueElement, narration::NarrationElement,
-                selection::SelectionElement, Element,
+use fabc_error::{
+    kind::{CompileErrorKind, InternalErrorKind},
+    Error,
+};
+use fabc_parser::ast::{
+    decl::quote::QuoteDecl,
+    expr::{literal::Literal, Expr, Primary},
+    init::{
+        module::ModuleInit,
+        story::{
+            metadata::Metadata,
+            part::{
+                element::{
+                    dialogue::DialogueElement, directive::DirectiveElement,
+                    narration::NarrationElement, selection::SelectionElement, Element,
+                },
+                Part,
             },
-            Part,
+            StoryInit,
         },
-        StoryInit,
+        Init,
     },
-    Init,
 };
 
 use crate::{
-    reachability::{extract_start_part, report_unreachable_parts, StoryReachability},
+    reachability::{extract_start_part, finish_story_reachability, StoryReachability},
     types::{ModuleSymbolType, StorySymbolType, Symbol},
     AnalysisResult, Analyzable, Analyzer,
 };
@@ -111,7 +118,9 @@ impl Analyzable for StoryInit {
             part.analyze(analyzer);
         });
 
-        report_unreachable_parts(self, analyzer);
+        if let Some(graph) = finish_story_reachability(self, analyzer) {
+            analyzer.set_story_graph(graph);
+        }
 
         AnalysisResult::default()
     }
@@ -133,6 +142,10 @@ impl Analyzable for Part {
 
         analyzer.set_current_story_part(Some(self.ident.clone()));
 
+        if let Some(properties) = &self.properties {
+            properties.analyze(analyzer);
+        }
+
         self.elements.iter().for_each(|element| {
             element.analyze(analyzer);
         });
@@ -157,6 +170,9 @@ impl Analyzable for Element {
             Element::Narration(narration) => {
                 narration.analyze(analyzer);
             }
+            Element::Directive(directive) => {
+                directive.analyze(analyzer);
+            }
         }
 
         AnalysisResult::default()
@@ -192,13 +208,40 @@ impl Analyzable for DialogueElement {
 impl Analyzable for SelectionElement {
     fn analyze(&self, analyzer: &mut Analyzer) -> AnalysisResult {
         self.choices.iter().for_each(|choice| {
+            analyzer.set_current_story_choice(Some(choice.text.clone()));
             choice.analyze(analyzer);
+            analyzer.set_current_story_choice(None);
         });
 
+        if !self.choices.iter().any(choice_always_remains) {
+            analyzer.push_warning(Error::new(
+                CompileErrorKind::ExhaustibleSelection,
+                self.info.span.clone(),
+            ));
+        }
+
         AnalysisResult::default()
     }
 }
 
+/// Whether `choice` is offered (or, as a `fallback`, taken) every time its selection is
+/// reached: it has neither a `when`/`if` guard nor `once: true`.
+fn choice_always_remains(choice: &QuoteDecl) -> bool {
+    let Some(properties) = &choice.properties else {
+        return true;
+    };
+
+    let guarded = properties.map.contains_key("when") || properties.map.contains_key("if");
+    let once = matches!(
+        properties.map.get("once"),
+        Some(Expr::Primary {
+            value: Primary::Literal(Literal::Boolean { value: true, .. }),
+            ..
+        })
+    );
+    !guarded && !once
+}
+
 impl Analyzable for NarrationElement {
     fn analyze(&self, analyzer: &mut Analyzer) -> AnalysisResult {
         self.quote.analyze(analyzer);
@@ -207,14 +250,30 @@ impl Analyzable for NarrationElement {
     }
 }
 
+impl Analyzable for DirectiveElement {
+    fn analyze(&self, analyzer: &mut Analyzer) -> AnalysisResult {
+        self.arguments.iter().for_each(|argument| {
+            argument.analyze(analyzer);
+        });
+
+        AnalysisResult::default()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::BTreeMap;
 
+    use fabc_error::kind::ErrorKind;
+    use fabc_parser::Parser;
+
     use super::*;
     use crate::test_utils::{info, string_expr};
     use fabc_parser::ast::{
-        decl::{object::ObjectDecl, quote::QuoteDecl},
+        decl::{
+            object::ObjectDecl,
+            quote::{QuoteDecl, TextSegment},
+        },
         init::story::{
             metadata::Metadata,
             part::{
@@ -226,6 +285,36 @@ mod tests {
         init::Init,
     };
 
+    #[test]
+    fn selection_that_can_run_out_of_choices_is_warned_about() {
+        let exhaustible = |source: &str| {
+            let story = Parser::parse_ast_str::<StoryInit>(source).expect("parse story");
+            let analyzer = Analyzer::analyze_ast(&story).expect("analyze failed");
+            analyzer.warnings.iter().any(|warning| {
+                warning.kind == ErrorKind::Compile(CompileErrorKind::ExhaustibleSelection)
+            })
+        };
+
+        assert!(exhaustible(
+            r#"
+            Story { start: "intro" }
+
+            # intro
+            - "Ask" { once: true }
+            - "Open" { when: () => context.has_key }
+            "#
+        ));
+        assert!(!exhaustible(
+            r#"
+            Story { start: "intro" }
+
+            # intro
+            - "Ask" { once: true }
+            - "Leave" { fallback: true }
+            "#
+        ));
+    }
+
     #[test]
     fn module_init_alias_is_annotated() {
         let init = ModuleInit {
@@ -255,12 +344,14 @@ mod tests {
         let part = Part {
             info: info(80),
             ident: "intro".to_string(),
+            properties: None,
             elements: vec![Element::Dialogue(DialogueElement {
                 info: info(81),
                 speaker: "guide".to_string(),
                 quotes: vec![QuoteDecl {
                     info: info(82),
                     text: "hi".to_string(),
+                    segments: vec![TextSegment::Text("hi".to_string())],
                     properties: None,
                 }],
             })],
@@ -299,11 +390,13 @@ mod tests {
             parts: vec![Part {
                 info: info(94),
                 ident: "p1".to_string(),
+                properties: None,
                 elements: vec![Element::Narration(NarrationElement {
                     info: info(95),
                     quote: QuoteDecl {
                         info: info(96),
                         text: "hello".to_string(),
+                        segments: vec![TextSegment::Text("hello".to_string())],
                         properties: None,
                     },
                 })],
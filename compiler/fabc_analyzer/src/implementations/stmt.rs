@@ -7,9 +7,13 @@ use fabc_parser::ast::stmt::{
     block::BlockStmt,
     expr::ExprStmt,
     goto::GotoStmt,
+    r#break::BreakStmt,
+    r#continue::ContinueStmt,
+    r#for::ForStmt,
     r#if::{ElseClause, IfStmt},
     r#let::LetStmt,
     r#return::ReturnStmt,
+    r#while::WhileStmt,
     Stmt,
 };
 
@@ -28,6 +32,10 @@ impl Analyzable for Stmt {
             Stmt::If(if_stmt) => if_stmt.analyze(analyzer),
             Stmt::Let(let_stmt) => let_stmt.analyze(analyzer),
             Stmt::Return(return_stmt) => return_stmt.analyze(analyzer),
+            Stmt::While(while_stmt) => while_stmt.analyze(analyzer),
+            Stmt::For(for_stmt) => for_stmt.analyze(analyzer),
+            Stmt::Break(break_stmt) => break_stmt.analyze(analyzer),
+            Stmt::Continue(continue_stmt) => continue_stmt.analyze(analyzer),
         }
     }
 }
@@ -146,6 +154,72 @@ impl Analyzable for IfStmt {
     }
 }
 
+impl Analyzable for WhileStmt {
+    fn analyze(&self, analyzer: &mut Analyzer) -> AnalysisResult {
+        self.condition.analyze(analyzer);
+
+        analyzer.enter_loop();
+        self.body.analyze(analyzer);
+        analyzer.exit_loop();
+
+        AnalysisResult::default()
+    }
+}
+
+impl Analyzable for ForStmt {
+    fn analyze(&self, analyzer: &mut Analyzer) -> AnalysisResult {
+        analyzer.mut_mod_sym_table().enter_scope();
+
+        if let Some(initializer) = &self.initializer {
+            initializer.analyze(analyzer);
+        }
+        if let Some(condition) = &self.condition {
+            condition.analyze(analyzer);
+        }
+        if let Some(increment) = &self.increment {
+            increment.analyze(analyzer);
+        }
+
+        analyzer.enter_loop();
+        self.body.analyze(analyzer);
+        analyzer.exit_loop();
+
+        analyzer.mut_mod_sym_table().exit_scope();
+
+        AnalysisResult::default()
+    }
+}
+
+impl Analyzable for BreakStmt {
+    fn analyze(&self, analyzer: &mut Analyzer) -> AnalysisResult {
+        if !analyzer.in_loop() {
+            analyzer.push_error(Error::new(
+                CompileErrorKind::LoopControlOutsideLoop {
+                    keyword: "break".to_string(),
+                },
+                self.info.span.clone(),
+            ));
+        }
+
+        AnalysisResult::default()
+    }
+}
+
+impl Analyzable for ContinueStmt {
+    fn analyze(&self, analyzer: &mut Analyzer) -> AnalysisResult {
+        if !analyzer.in_loop() {
+            analyzer.push_error(Error::new(
+                CompileErrorKind::LoopControlOutsideLoop {
+                    keyword: "continue".to_string(),
+                },
+                self.info.span.clone(),
+            ));
+        }
+
+        AnalysisResult::default()
+    }
+}
+
 impl Analyzable for LetStmt {
     fn analyze(&self, analyzer: &mut Analyzer) -> AnalysisResult {
         let Some(var_type) = self.initializer.analyze(analyzer).mod_sym_type else {
@@ -269,6 +343,37 @@ mod tests {
         );
     }
 
+    #[test]
+    fn loop_control_is_only_allowed_inside_loop_bodies() {
+        let block = fabc_parser::Parser::parse_ast_str::<BlockStmt>(
+            r#"{
+                let total = 0;
+                while (total < 3) {
+                    total = total + 1;
+                    if (total == 2) { continue; }
+                    let escape = () => { break; };
+                }
+                for (let i = 0; i < 3; i = i + 1) { break; }
+                continue;
+            }"#,
+        )
+        .expect("parse failed");
+
+        let analyzer = Analyzer::analyze_ast(&block).expect("analyze failed");
+
+        let keywords: Vec<_> = analyzer
+            .errors
+            .iter()
+            .filter_map(|error| match &error.kind {
+                ErrorKind::Compile(CompileErrorKind::LoopControlOutsideLoop { keyword }) => {
+                    Some(keyword.as_str())
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(keywords, vec!["break", "continue"]);
+    }
+
     #[test]
     fn goto_requires_part_symbol() {
         let mut analyzer = Analyzer::default();
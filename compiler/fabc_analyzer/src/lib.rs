@@ -5,12 +5,15 @@ use fabc_parser::ast::init::Init;
 #[cfg(test)]
 use fabc_parser::Parsable;
 
+pub use crate::builtins::{builtin_type, HostFunctionDecl};
+pub use crate::reachability::{StoryEdge, StoryGraph};
 use crate::{
     reachability::StoryReachability,
     symbol_table::SymbolTable,
     types::{ModuleSymbolType, StorySymbolType, SymbolAnnotation},
 };
 
+mod builtins;
 pub mod implementations;
 mod reachability;
 pub mod symbol_table;
@@ -31,6 +34,8 @@ pub trait Analyzable {
 pub struct AnalyzerResult {
     pub story_sym_annotations: HashMap<usize, SymbolAnnotation<StorySymbolType>>,
     pub mod_sym_annotations: HashMap<usize, SymbolAnnotation<ModuleSymbolType>>,
+    /// Part graph of the analyzed story, if the inits contained one.
+    pub story_graph: Option<StoryGraph>,
     pub errors: Vec<Error>,
     pub warnings: Vec<Error>,
 }
@@ -42,13 +47,28 @@ pub struct Analyzer {
     story_sym_annotations: HashMap<usize, SymbolAnnotation<StorySymbolType>>,
     mod_sym_annotations: HashMap<usize, SymbolAnnotation<ModuleSymbolType>>,
     story_reachability: Option<StoryReachability>,
+    story_graph: Option<StoryGraph>,
+    host_functions: Vec<HostFunctionDecl>,
+    loop_depth: usize,
     errors: Vec<Error>,
     warnings: Vec<Error>,
 }
 
 impl Analyzer {
     pub fn analyze(inits: &[Init]) -> AnalyzerResult {
-        let mut analyzer = Self::default();
+        Self::analyze_with_host_functions(inits, &[])
+    }
+
+    /// Like [`Analyzer::analyze`], also accepting calls to the given host functions wherever
+    /// no story binding shadows them.
+    pub fn analyze_with_host_functions(
+        inits: &[Init],
+        host_functions: &[HostFunctionDecl],
+    ) -> AnalyzerResult {
+        let mut analyzer = Self {
+            host_functions: host_functions.to_vec(),
+            ..Self::default()
+        };
 
         for init in inits {
             init.analyze(&mut analyzer);
@@ -57,6 +77,7 @@ impl Analyzer {
         AnalyzerResult {
             story_sym_annotations: analyzer.story_sym_annotations,
             mod_sym_annotations: analyzer.mod_sym_annotations,
+            story_graph: analyzer.story_graph,
             errors: analyzer.errors,
             warnings: analyzer.warnings,
         }
@@ -96,6 +117,13 @@ impl Analyzer {
         self.mod_sym_annotations.insert(node_id, symbol);
     }
 
+    pub(crate) fn host_function_type(&self, name: &str) -> Option<ModuleSymbolType> {
+        self.host_functions
+            .iter()
+            .find(|function| function.name == name)
+            .map(HostFunctionDecl::symbol_type)
+    }
+
     pub(crate) fn begin_story_reachability(&mut self, reachability: StoryReachability) {
         self.story_reachability = Some(reachability);
     }
@@ -110,6 +138,12 @@ impl Analyzer {
         }
     }
 
+    pub(crate) fn set_current_story_choice(&mut self, choice: Option<String>) {
+        if let Some(reachability) = self.story_reachability.as_mut() {
+            reachability.set_current_choice(choice);
+        }
+    }
+
     pub(crate) fn record_story_target_reference(&mut self, target: Option<String>) {
         if let Some(reachability) = self.story_reachability.as_mut() {
             reachability.record_target(target);
@@ -120,6 +154,26 @@ impl Analyzer {
         self.story_reachability.take()
     }
 
+    pub(crate) fn set_story_graph(&mut self, graph: StoryGraph) {
+        self.story_graph = Some(graph);
+    }
+
+    pub(crate) fn enter_loop(&mut self) {
+        self.loop_depth += 1;
+    }
+
+    pub(crate) fn exit_loop(&mut self) {
+        self.loop_depth = self.loop_depth.saturating_sub(1);
+    }
+
+    pub(crate) fn in_loop(&self) -> bool {
+        self.loop_depth > 0
+    }
+
+    pub(crate) fn replace_loop_depth(&mut self, depth: usize) -> usize {
+        std::mem::replace(&mut self.loop_depth, depth)
+    }
+
     pub(crate) fn push_error(&mut self, error: Error) {
         self.errors.push(error);
     }
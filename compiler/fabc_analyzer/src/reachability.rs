@@ -1,19 +1,59 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 use fabc_error::{kind::CompileErrorKind, Error};
 use fabc_parser::ast::{
     expr::{literal::Literal, primitive::Primitive, Expr, Primary},
-    init::story::StoryInit,
+    init::story::{part::element::Element, StoryInit},
 };
 
 use crate::Analyzer;
 
+/// A transition from one part to another, found in a `goto` with a static target.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct StoryEdge {
+    pub from: String,
+    pub to: String,
+    /// Text of the choice whose closure performs the `goto`, if there is one.
+    pub label: Option<String>,
+}
+
+/// The part graph of a linked story. Imported parts are named `namespace.part`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StoryGraph {
+    pub start: Option<String>,
+    /// Part names in declaration order.
+    pub parts: Vec<String>,
+    /// Edges in the order their `goto`s appear in the source.
+    pub edges: Vec<StoryEdge>,
+    /// Parts the start part cannot reach. Left empty when any `goto` has a dynamic target,
+    /// since those could lead anywhere.
+    pub unreachable: BTreeSet<String>,
+    /// Parts without a way out that close on narration, dialogue or a directive, where the
+    /// story ends as written.
+    pub endings: BTreeSet<String>,
+    /// Parts without a way out that are empty or close on a selection, so the story stops
+    /// as soon as the reader picks a choice.
+    pub dead_ends: BTreeSet<String>,
+    /// Parts containing a `goto` whose target is only known at runtime.
+    pub dynamic: BTreeSet<String>,
+}
+
+impl StoryGraph {
+    /// The module namespace a part was imported under, or `None` for the entry's own parts.
+    pub fn namespace(part: &str) -> Option<&str> {
+        part.rsplit_once('.').map(|(namespace, _)| namespace)
+    }
+}
+
 #[derive(Default)]
 pub(crate) struct StoryReachability {
     start_part: Option<String>,
     declared_parts: HashSet<String>,
     current_part: Option<String>,
+    current_choice: Option<String>,
     edges: HashMap<String, HashSet<String>>,
+    labelled_edges: Vec<StoryEdge>,
+    dynamic_parts: BTreeSet<String>,
     has_dynamic_target: bool,
 }
 
@@ -23,7 +63,10 @@ impl StoryReachability {
             start_part,
             declared_parts,
             current_part: None,
+            current_choice: None,
             edges: HashMap::new(),
+            labelled_edges: Vec::new(),
+            dynamic_parts: BTreeSet::new(),
             has_dynamic_target: false,
         }
     }
@@ -32,6 +75,10 @@ impl StoryReachability {
         self.current_part = part;
     }
 
+    pub(crate) fn set_current_choice(&mut self, choice: Option<String>) {
+        self.current_choice = choice;
+    }
+
     pub(crate) fn record_target(&mut self, target: Option<String>) {
         let Some(current_part) = self.current_part.clone() else {
             return;
@@ -39,9 +86,18 @@ impl StoryReachability {
 
         match target {
             Some(target) => {
+                let edge = StoryEdge {
+                    from: current_part.clone(),
+                    to: target.clone(),
+                    label: self.current_choice.clone(),
+                };
+                if !self.labelled_edges.contains(&edge) {
+                    self.labelled_edges.push(edge);
+                }
                 self.edges.entry(current_part).or_default().insert(target);
             }
             None => {
+                self.dynamic_parts.insert(current_part);
                 self.has_dynamic_target = true;
             }
         }
@@ -60,18 +116,53 @@ pub(crate) fn record_story_target(expr: &Expr, analyzer: &mut Analyzer) {
     analyzer.record_story_target_reference(target);
 }
 
-pub(crate) fn report_unreachable_parts(story: &StoryInit, analyzer: &mut Analyzer) {
-    let Some(reachability) = analyzer.take_story_reachability() else {
-        return;
+/// Warns about unreachable parts and returns the story's part graph.
+pub(crate) fn finish_story_reachability(
+    story: &StoryInit,
+    analyzer: &mut Analyzer,
+) -> Option<StoryGraph> {
+    let reachability = analyzer.take_story_reachability()?;
+
+    let parts = story
+        .parts
+        .iter()
+        .map(|part| part.ident.clone())
+        .collect::<Vec<_>>();
+    let edges = reachability
+        .labelled_edges
+        .into_iter()
+        .filter(|edge| reachability.declared_parts.contains(edge.to.as_str()))
+        .collect::<Vec<_>>();
+    let (mut endings, mut dead_ends) = (BTreeSet::new(), BTreeSet::new());
+    for part in &story.parts {
+        if reachability.dynamic_parts.contains(part.ident.as_str())
+            || edges.iter().any(|edge| edge.from == part.ident)
+        {
+            continue;
+        }
+
+        match part.elements.last() {
+            None | Some(Element::Selection(_)) => dead_ends.insert(part.ident.clone()),
+            Some(_) => endings.insert(part.ident.clone()),
+        };
+    }
+    let mut graph = StoryGraph {
+        start: reachability.start_part.clone(),
+        parts,
+        edges,
+        unreachable: BTreeSet::new(),
+        endings,
+        dead_ends,
+        dynamic: reachability.dynamic_parts,
     };
 
     let Some(start_part) = reachability.start_part else {
-        return;
+        return Some(graph);
     };
 
     if !reachability.declared_parts.contains(start_part.as_str()) || reachability.has_dynamic_target
     {
-        return;
+        return Some(graph);
     }
 
     let reachable_parts = collect_reachable_parts(
@@ -82,6 +173,7 @@ pub(crate) fn report_unreachable_parts(story: &StoryInit, analyzer: &mut Analyze
 
     for part in &story.parts {
         if !reachable_parts.contains(part.ident.as_str()) {
+            graph.unreachable.insert(part.ident.clone());
             analyzer.push_warning(Error::new(
                 CompileErrorKind::UnreachablePart {
                     part: part.ident.clone(),
@@ -90,6 +182,8 @@ pub(crate) fn report_unreachable_parts(story: &StoryInit, analyzer: &mut Analyze
             ));
         }
     }
+
+    Some(graph)
 }
 
 pub(crate) fn extract_start_part(story: &StoryInit) -> Option<String> {
@@ -172,7 +266,65 @@ mod tests {
     use fabc_error::kind::{CompileErrorKind, ErrorKind};
     use fabc_parser::{ast::init::story::StoryInit, Parser};
 
-    use crate::Analyzer;
+    use crate::{Analyzer, StoryEdge};
+
+    #[test]
+    fn story_init_builds_labelled_part_graph() {
+        let story = Parser::parse_ast_str::<StoryInit>(
+            r#"
+            Story { start: "intro" }
+
+            # intro
+            [Guide]
+            > "Where to?" {
+                next: () => { goto hall; }
+            }
+                - "The cellar." {
+                    next: () => { goto cellar; }
+                }
+
+            # hall
+            * "A long hall."
+
+            # cellar
+            * "It is dark." {
+                next: () => { goto context.exit; }
+            }
+
+            # attic
+            * "Dusty."
+
+            # vault
+            - "Knock."
+            "#,
+        )
+        .expect("parse story");
+
+        let analyzer = Analyzer::analyze_ast(&story).expect("analyze failed");
+        let graph = analyzer.story_graph.expect("story graph");
+
+        assert_eq!(graph.start.as_deref(), Some("intro"));
+        assert_eq!(graph.parts, ["intro", "hall", "cellar", "attic", "vault"]);
+        assert_eq!(
+            graph.edges,
+            [
+                StoryEdge {
+                    from: "intro".to_string(),
+                    to: "hall".to_string(),
+                    label: None,
+                },
+                StoryEdge {
+                    from: "intro".to_string(),
+                    to: "cellar".to_string(),
+                    label: Some("The cellar.".to_string()),
+                },
+            ]
+        );
+        assert_eq!(graph.endings.iter().collect::<Vec<_>>(), ["attic", "hall"]);
+        assert_eq!(graph.dead_ends.iter().collect::<Vec<_>>(), ["vault"]);
+        assert_eq!(graph.dynamic.iter().collect::<Vec<_>>(), ["cellar"]);
+        assert!(graph.unreachable.is_empty());
+    }
 
     #[test]
     fn story_init_reports_unreachable_parts() {
@@ -202,6 +354,13 @@ mod tests {
             &warning.kind,
             ErrorKind::Compile(CompileErrorKind::UnreachablePart { part }) if part == "dangling"
         )));
+        assert_eq!(
+            analyzer
+                .story_graph
+                .as_ref()
+                .map(|graph| &graph.unreachable),
+            Some(&["dangling".to_string()].into())
+        );
 
         assert!(!analyzer.errors.iter().any(|error| matches!(
             &error.kind,
@@ -15,6 +15,7 @@ pub enum DataType {
     None,
     Context,
     Record { fields: Vec<Field> },
+    List { element: Box<ModuleSymbolType> },
 }
 
 impl Display for DataType {
@@ -33,6 +34,7 @@ impl Display for DataType {
                     .collect();
                 write!(f, "Record {{ {} }}", field_strs.join(", "))
             }
+            DataType::List { element } => write!(f, "List[{element}]"),
         }
     }
 }
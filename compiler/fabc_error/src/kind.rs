@@ -13,9 +13,15 @@ pub enum CompileErrorKind {
     InvalidGotoTarget,
     TypeInference,
     UnclosedDelimiter,
+    EmptyInterpolation,
     UninitializedVariable,
     UnreachablePart { part: String },
+    ExhaustibleSelection,
+    ReadOnlyNamespace { namespace: String },
     NotCallable,
+    LoopControlOutsideLoop { keyword: String },
+    InvalidEscape { escape: String },
+    UnterminatedString,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -94,9 +100,15 @@ impl CompileErrorKind {
             CompileErrorKind::InvalidGotoTarget => "Invalid goto target",
             CompileErrorKind::TypeInference => "Type Inference",
             CompileErrorKind::UnclosedDelimiter => "Unclosed delimiter",
+            CompileErrorKind::EmptyInterpolation => "Empty interpolation",
             CompileErrorKind::UninitializedVariable => "Uninitialized variable",
             CompileErrorKind::UnreachablePart { .. } => "Unreachable part",
+            CompileErrorKind::ExhaustibleSelection => "Exhaustible selection",
+            CompileErrorKind::ReadOnlyNamespace { .. } => "Read-only namespace",
             CompileErrorKind::NotCallable => "Not callable",
+            CompileErrorKind::LoopControlOutsideLoop { .. } => "Loop control outside loop",
+            CompileErrorKind::InvalidEscape { .. } => "Invalid escape",
+            CompileErrorKind::UnterminatedString => "Unterminated string",
         }
     }
 
@@ -112,6 +124,9 @@ impl CompileErrorKind {
                 format!("Expected {} arguments, found {}", expected, found)
             }
             CompileErrorKind::NotCallable => "Attempted to call a non-callable entity".to_string(),
+            CompileErrorKind::LoopControlOutsideLoop { keyword } => {
+                format!("'{}' can only be used inside a loop body", keyword)
+            }
             CompileErrorKind::ExpectedType { expected, found } => {
                 format!("Expected type '{}', found '{}'", expected, found)
             }
@@ -121,6 +136,14 @@ impl CompileErrorKind {
             CompileErrorKind::UnreachablePart { part } => {
                 format!("Part '{}' is unreachable from the story start", part)
             }
+            CompileErrorKind::ExhaustibleSelection => {
+                "Every choice in this selection can disappear; add one without `once` or a \
+                 `when`/`if` guard, such as a `fallback`"
+                    .to_string()
+            }
+            CompileErrorKind::ReadOnlyNamespace { namespace } => {
+                format!("Builtin namespace '{}' cannot be assigned to", namespace)
+            }
             CompileErrorKind::TypeInference => "Unable to infer type".to_string(),
             CompileErrorKind::ExpectedSymbol { expected, found } => {
                 format!("Expected '{}', found '{}'", expected, found)
@@ -144,6 +167,16 @@ impl CompileErrorKind {
                 format!("Invalid operator '{}'", operator)
             }
             CompileErrorKind::UnclosedDelimiter => "Unclosed delimiter found".to_string(),
+            CompileErrorKind::EmptyInterpolation => {
+                "Interpolation braces need an expression; write '{{' for a literal brace"
+                    .to_string()
+            }
+            CompileErrorKind::InvalidEscape { escape } => {
+                format!("Invalid escape sequence '{}' in string literal", escape)
+            }
+            CompileErrorKind::UnterminatedString => {
+                "String literal is missing its closing quote".to_string()
+            }
         }
     }
 }
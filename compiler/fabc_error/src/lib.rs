@@ -1,6 +1,10 @@
 use anstyle::{Ansi256Color, AnsiColor, Color, Style};
 use fabc_lexer::tokens::Token;
-use std::fmt::Write;
+use std::{
+    fmt::Write,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use crate::kind::ErrorKind;
 
@@ -23,7 +27,7 @@ impl LineCol {
         Self::new(token.line, token.column)
     }
     pub fn from_token_end(token: &Token<'_>) -> Self {
-        Self::new(token.line, token.column + token.length - 1)
+        Self::new(token.line, token.column + token.length.saturating_sub(1))
     }
 }
 
@@ -57,7 +61,7 @@ impl From<&Token<'_>> for Span {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Error {
     pub kind: ErrorKind,
     pub span: Span,
@@ -68,6 +72,9 @@ impl Error {
     const ERROR_COLOR: Style = Style::new()
         .fg_color(Some(Color::Ansi256(Ansi256Color(160))))
         .bold();
+    const WARNING_COLOR: Style = Style::new()
+        .fg_color(Some(Color::Ansi256(Ansi256Color(214))))
+        .bold();
     const LINE_NUMBER_COLOR: Style = Style::new()
         .fg_color(Some(Color::Ansi(AnsiColor::BrightBlack)))
         .bold();
@@ -84,120 +91,150 @@ impl Error {
     }
 
     pub fn format(&self, source: &str) -> String {
+        self.format_with(source, &FormatOptions::default())
+    }
+
+    pub fn format_with(&self, source: &str, options: &FormatOptions<'_>) -> String {
+        let palette = Palette {
+            color: options.color,
+        };
         let start_line = self.span.start().line();
         let end_line = self.span.end().line();
         let (first_line, last_line) = Self::span_lines(source, start_line, end_line);
         let message = self.kind.message();
+        let (label, label_color) = match options.severity {
+            Severity::Error => ("error", Self::ERROR_COLOR),
+            Severity::Warning => ("warning", Self::WARNING_COLOR),
+        };
 
         let mut formatted_error = String::new();
 
         let _ = writeln!(
             formatted_error,
-            "{}error: {}{}",
-            Self::ERROR_COLOR,
+            "{}{label}: {}{}",
+            palette.style(label_color),
             self.kind.name(),
-            anstyle::Reset
+            palette.reset()
         );
 
+        if let Some(path) = options.path {
+            let _ = writeln!(
+                formatted_error,
+                "{}{:>offset$} {}:{}:{}{}",
+                palette.style(Self::LINE_NUMBER_COLOR),
+                "-->",
+                path.display(),
+                start_line,
+                self.span.start().col(),
+                palette.reset(),
+                offset = Self::LINE_OFFSET + 1
+            );
+        }
+
         if start_line == end_line {
             Self::format_line(
                 &mut formatted_error,
+                &palette,
                 start_line,
                 first_line,
                 self.span.start().col(),
                 self.span.end().col(),
             );
-            Self::format_annotation(&mut formatted_error, &message);
+            Self::format_annotation(&mut formatted_error, &palette, &message);
         } else {
             Self::format_line(
                 &mut formatted_error,
+                &palette,
                 start_line,
                 first_line,
                 self.span.start().col(),
-                first_line.len() + 1,
+                first_line.chars().count() + 1,
             );
             formatted_error.push('\n');
 
-            Self::format_line_continuation(&mut formatted_error);
+            Self::format_line_continuation(&mut formatted_error, &palette);
 
             let last_line_leading_whitespace_len =
                 last_line.chars().take_while(|c| c.is_whitespace()).count();
 
             Self::format_line(
                 &mut formatted_error,
+                &palette,
                 end_line,
                 last_line,
                 last_line_leading_whitespace_len + 1,
                 self.span.end().col(),
             );
 
-            Self::format_annotation(&mut formatted_error, &message);
+            Self::format_annotation(&mut formatted_error, &palette, &message);
         };
 
         formatted_error
     }
-    fn format_annotation(string_buf: &mut String, content: &str) {
+    fn format_annotation(string_buf: &mut String, palette: &Palette, content: &str) {
         let _ = write!(
             string_buf,
             " {}{}{}",
-            Self::INFO_COLOR,
+            palette.style(Self::INFO_COLOR),
             content,
-            anstyle::Reset
+            palette.reset()
         );
     }
     fn format_line(
         string_buf: &mut String,
+        palette: &Palette,
         line_number: usize,
         content: &str,
         annotation_start: usize,
         annotation_end: usize,
     ) {
-        let annotation_length = annotation_end - annotation_start;
-        let anotation_offset = annotation_start - 1 + annotation_length;
+        // Single-column spans still get one caret.
+        let annotation_length = annotation_end.saturating_sub(annotation_start).max(1);
+        let anotation_offset = annotation_start.saturating_sub(1) + annotation_length;
 
-        Self::format_line_header(string_buf, None);
+        Self::format_line_header(string_buf, palette, None);
         string_buf.push('\n');
 
-        Self::format_line_header(string_buf, Some(line_number));
+        Self::format_line_header(string_buf, palette, Some(line_number));
 
         let _ = writeln!(
             string_buf,
             "{}{}{}",
-            Self::CODE_COLOR,
+            palette.style(Self::CODE_COLOR),
             content,
-            anstyle::Reset
+            palette.reset()
         );
 
-        Self::format_line_header(string_buf, None);
+        Self::format_line_header(string_buf, palette, None);
 
         let _ = write!(
             string_buf,
             "{}{:>offset$}{}",
-            Self::INFO_COLOR,
+            palette.style(Self::INFO_COLOR),
             "^".repeat(annotation_length),
-            anstyle::Reset,
+            palette.reset(),
             offset = anotation_offset
         );
     }
-    fn format_line_header(string_buf: &mut String, line_number: Option<usize>) {
+    fn format_line_header(string_buf: &mut String, palette: &Palette, line_number: Option<usize>) {
         let _ = write!(
             string_buf,
             "{}{:>offset$} |{}",
-            Self::LINE_NUMBER_COLOR,
+            palette.style(Self::LINE_NUMBER_COLOR),
             line_number
                 .map(|num| num.to_string())
                 .unwrap_or_else(|| " ".to_string()),
-            anstyle::Reset,
+            palette.reset(),
             offset = Self::LINE_OFFSET
         );
     }
-    fn format_line_continuation(string_buf: &mut String) {
+    fn format_line_continuation(string_buf: &mut String, palette: &Palette) {
         let _ = writeln!(
             string_buf,
             "{}{:>offset$}{}",
-            Self::LINE_NUMBER_COLOR,
+            palette.style(Self::LINE_NUMBER_COLOR),
             "...",
-            anstyle::Reset,
+            palette.reset(),
             offset = Self::LINE_OFFSET + 2
         );
     }
@@ -223,6 +260,95 @@ impl Error {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FormatOptions<'a> {
+    pub severity: Severity,
+    pub path: Option<&'a Path>,
+    pub color: bool,
+}
+
+impl Default for FormatOptions<'_> {
+    fn default() -> Self {
+        Self {
+            severity: Severity::Error,
+            path: None,
+            color: true,
+        }
+    }
+}
+
+struct Palette {
+    color: bool,
+}
+
+impl Palette {
+    fn style(&self, style: Style) -> String {
+        if self.color {
+            style.to_string()
+        } else {
+            String::new()
+        }
+    }
+
+    fn reset(&self) -> String {
+        if self.color {
+            anstyle::Reset.to_string()
+        } else {
+            String::new()
+        }
+    }
+}
+
+/// An error or warning together with the file it was reported against.
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub path: Option<PathBuf>,
+    pub source: Arc<str>,
+    pub error: Error,
+}
+
+impl Diagnostic {
+    pub fn new(
+        severity: Severity,
+        path: Option<PathBuf>,
+        source: impl Into<Arc<str>>,
+        error: Error,
+    ) -> Self {
+        Self {
+            severity,
+            path,
+            source: source.into(),
+            error,
+        }
+    }
+
+    pub fn render(&self, color: bool) -> String {
+        self.error.format_with(
+            &self.source,
+            &FormatOptions {
+                severity: self.severity,
+                path: self.path.as_deref(),
+                color,
+            },
+        )
+    }
+
+    pub fn render_all(diagnostics: &[Diagnostic], color: bool) -> String {
+        diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.render(color))
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -286,4 +412,30 @@ mod tests {
             "^^\u{1b}[0m \u{1b}[1m\u{1b}[94mExpected 'i32', found 'string'\u{1b}[0m")
         )
     }
+
+    #[test]
+    fn renders_plain_warning_with_path() {
+        let source = "# intro\n# orphan\n";
+        let kind = ErrorKind::Compile(CompileErrorKind::UnreachablePart {
+            part: "orphan".to_string(),
+        });
+        let error = Error::new(kind, Span::new(LineCol::new(2, 1), LineCol::new(2, 8)));
+        let diagnostic = Diagnostic::new(
+            Severity::Warning,
+            Some(PathBuf::from("story.fab")),
+            source,
+            error,
+        );
+
+        assert_eq!(
+            diagnostic.render(false),
+            concat!(
+                "warning: Unreachable part\n",
+                "   --> story.fab:2:1\n",
+                "      |\n",
+                "    2 |# orphan\n",
+                "      |^^^^^^^ Part 'orphan' is unreachable from the story start"
+            )
+        );
+    }
 }
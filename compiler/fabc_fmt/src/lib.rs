@@ -0,0 +1,250 @@
+//! Pretty-prints `.fab` source in its canonical layout, keeping `//` comments.
+
+use fabc_error::{Error, LineCol};
+use fabc_lexer::{tokens::TokenKind, Lexer};
+use fabc_parser::Parser;
+
+use crate::printer::{LineEnds, Printer, RawStrings};
+
+mod printer;
+
+/// Reformats `source`, or returns the syntax errors that stop it from being parsed.
+///
+/// String literals are reprinted exactly as written, so escapes, raw and triple-quoted strings
+/// survive a round trip.
+pub fn format_source(source: &str) -> Result<String, Vec<Error>> {
+    let (tokens, comments) = Lexer::tokenize_with_comments(source);
+    let parsed = Parser::parse(&tokens);
+    if !parsed.errors.is_empty() {
+        return Err(parsed.errors);
+    }
+
+    let strings = tokens
+        .iter()
+        .filter(|token| matches!(token.kind, TokenKind::String(_)))
+        .map(|token| {
+            let raw = &source[token.offset..];
+            let end = raw
+                .char_indices()
+                .nth(token.length)
+                .map_or(raw.len(), |(index, _)| index);
+            ((token.line, token.column), &raw[..end])
+        })
+        .collect::<RawStrings>();
+
+    let line_ends = tokens
+        .iter()
+        .filter(|token| token.kind != TokenKind::EoF)
+        .map(|token| {
+            let end = LineCol::from_token_end(token);
+            (end.line(), end.col())
+        })
+        .collect::<LineEnds>();
+
+    let mut printer = Printer::new(&strings, &line_ends, &comments);
+    printer.inits(&parsed.result);
+    Ok(printer.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use fabc_parser::Parser;
+    use fabc_reg_test::{COMPLEX_STORY, SIMPLE_STORY};
+
+    use crate::format_source;
+
+    #[test]
+    fn formats_story_in_canonical_layout() {
+        let source = r#"module   "./shared.fab"  as shared ;
+Story {start:"intro",title : "Demo"}
+#   intro
+[Narrator]
+  >  "Hello." {mood:"calm"}
+*"The room is dark."
+- "Look around." { next: () => { goto shared.hall; } }
+        - "Leave."   {next:()=>@outro}
+# outro
+*   "Bye."
+"#;
+
+        let formatted = format_source(source).expect("story should format");
+
+        assert_eq!(
+            formatted,
+            r#"module "./shared.fab" as shared;
+
+Story { start: "intro", title: "Demo" }
+
+# intro
+[Narrator]
+> "Hello." { mood: "calm" }
+* "The room is dark."
+    - "Look around." {
+        next: () => {
+            goto shared.hall;
+        }
+    }
+    - "Leave." { next: () => @outro }
+
+# outro
+* "Bye."
+"#
+        );
+    }
+
+    #[test]
+    fn formats_statements_and_expressions() {
+        let source = r#"Story {start: "a"}
+# a
+* "x" { next: () => { let i=0; while(i<3){i=i+1;} for(let j=0;j<2;j=j+1){ if (j==1) {break;} else if(!context.done){continue;}else{ return [1,2][0]; } } goto @b; } }
+# b
+"#;
+
+        let formatted = format_source(source).expect("story should format");
+
+        assert_eq!(
+            formatted,
+            r#"Story { start: "a" }
+
+# a
+* "x" {
+    next: () => {
+        let i = 0;
+        while (i < 3) {
+            i = i + 1;
+        }
+        for (let j = 0; j < 2; j = j + 1) {
+            if (j == 1) {
+                break;
+            } else if (!context.done) {
+                continue;
+            } else {
+                return [1, 2][0];
+            }
+        }
+        goto @b;
+    }
+}
+
+# b
+"#
+        );
+    }
+
+    #[test]
+    fn formats_part_properties() {
+        let source = r#"Story {start: "a"}
+#   a=>{background:"bg_alley",on_enter:()=>{context.seen=true;}}
+* "Rain."
+# b => { mood: "calm" }
+"#;
+
+        let formatted = format_source(source).expect("story should format");
+
+        assert_eq!(
+            formatted,
+            r#"Story { start: "a" }
+
+# a => {
+    background: "bg_alley",
+    on_enter: () => {
+        context.seen = true;
+    }
+}
+* "Rain."
+
+# b => { mood: "calm" }
+"#
+        );
+    }
+
+    #[test]
+    fn formats_directives() {
+        let source = r#"Story {start: "a"}
+# a
+!   play_music( "rain" ,0.5 )
+! clear_screen ()
+* "It pours."
+"#;
+
+        let formatted = format_source(source).expect("story should format");
+
+        assert_eq!(
+            formatted,
+            r#"Story { start: "a" }
+
+# a
+! play_music("rain", 0.5)
+! clear_screen()
+* "It pours."
+"#
+        );
+    }
+
+    #[test]
+    fn preserves_comments_and_string_spelling() {
+        let source = r#"// Opening scene.
+Story { start: "intro" } // trailing meta
+
+# intro // the start
+
+// Greeting.
+[Guide]
+> "Say \"hi\"" {
+    // Where to go next.
+    next: () => {
+        goto @intro; // loop
+    }
+}
+> r"C:\raw"
+"#;
+
+        let formatted = format_source(source).expect("story should format");
+
+        assert_eq!(formatted, source);
+    }
+
+    #[test]
+    fn keeps_trailing_comments_after_expanded_one_line_objects() {
+        let source = r#"Story { start: "a" }
+# a
+* "x" { next: () => { goto @b; } } // after the choice
+# b
+"#;
+
+        let formatted = format_source(source).expect("story should format");
+
+        assert_eq!(
+            formatted,
+            r#"Story { start: "a" }
+
+# a
+* "x" {
+    next: () => {
+        goto @b;
+    }
+} // after the choice
+
+# b
+"#
+        );
+    }
+
+    #[test]
+    fn formatting_is_idempotent() {
+        let commented = "Story { start: \"intro\" } // trailing meta\n# intro\n* \"x\" { next: () => { goto @intro; } } // loop\n";
+        for source in [SIMPLE_STORY, COMPLEX_STORY, commented] {
+            let formatted = format_source(source).expect("story should format");
+
+            assert!(Parser::parse_str(&formatted).errors.is_empty());
+            assert_eq!(format_source(&formatted).unwrap(), formatted);
+        }
+    }
+
+    #[test]
+    fn rejects_source_with_syntax_errors() {
+        let errors = format_source("# intro\n> \"unterminated {").unwrap_err();
+
+        assert!(!errors.is_empty());
+    }
+}
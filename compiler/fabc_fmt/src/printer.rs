@@ -0,0 +1,671 @@
+use std::collections::BTreeMap;
+
+use fabc_error::{LineCol, Span};
+use fabc_lexer::tokens::Comment;
+use fabc_parser::ast::{
+    decl::{object::ObjectDecl, quote::QuoteDecl},
+    expr::{literal::Literal, primitive::Primitive, BinaryOperator, Expr, Primary, UnaryOperator},
+    init::{
+        module::ModuleInit,
+        story::{part::element::Element, part::Part, StoryInit},
+        Init,
+    },
+    stmt::{
+        block::BlockStmt,
+        r#if::{ElseClause, IfStmt},
+        Stmt,
+    },
+    NodeInfo,
+};
+
+const INDENT: &str = "    ";
+
+/// Objects stay on one line only while that line fits in this many characters.
+const MAX_WIDTH: usize = 100;
+
+/// Raw source text of each string token, keyed by the line and column it starts at, so
+/// literals are reprinted with the quoting and escapes they were written with.
+pub(crate) type RawStrings<'src> = BTreeMap<(usize, usize), &'src str>;
+
+/// Column where the last token on each source line ends, measured the way spans measure it.
+pub(crate) type LineEnds = BTreeMap<usize, usize>;
+
+pub(crate) struct Printer<'a, 'src> {
+    out: String,
+    indent: usize,
+    strings: &'a RawStrings<'src>,
+    line_ends: &'a LineEnds,
+    comments: &'a [Comment<'src>],
+    next_comment: usize,
+    /// Furthest source line printed so far, used to keep blank lines the source had.
+    last_line: usize,
+}
+
+impl<'a, 'src> Printer<'a, 'src> {
+    pub(crate) fn new(
+        strings: &'a RawStrings<'src>,
+        line_ends: &'a LineEnds,
+        comments: &'a [Comment<'src>],
+    ) -> Self {
+        Self {
+            out: String::new(),
+            indent: 0,
+            strings,
+            line_ends,
+            comments,
+            next_comment: 0,
+            last_line: 0,
+        }
+    }
+
+    pub(crate) fn finish(mut self) -> String {
+        self.comments_before(usize::MAX);
+        let mut out = self.out.trim_end().to_string();
+        out.push('\n');
+        out
+    }
+
+    pub(crate) fn inits(&mut self, inits: &[Init]) {
+        for init in inits {
+            match init {
+                Init::Module(module) => self.module(module),
+                Init::Story(story) => {
+                    self.blank_line();
+                    self.story(story);
+                }
+            }
+        }
+    }
+
+    fn module(&mut self, module: &ModuleInit) {
+        self.begin_line(&module.info);
+        self.write("module ");
+        let start = module.info.span.start();
+        let path = self
+            .strings
+            .range((start.line(), start.col())..)
+            .next()
+            .map(|(_, raw)| raw.to_string())
+            .unwrap_or_else(|| quoted(&module.path));
+        self.write(&path);
+        if let Some(alias) = &module.alias {
+            self.write(" as ");
+            self.write(alias);
+        }
+        self.write(";");
+        self.end_line(module.info.span.end());
+    }
+
+    fn story(&mut self, story: &StoryInit) {
+        if let Some(metadata) = &story.metadata {
+            self.begin_line(&metadata.info);
+            self.write("Story ");
+            self.object(&metadata.object);
+            self.end_line(metadata.info.span.end());
+        }
+
+        for part in &story.parts {
+            self.blank_line();
+            self.part(part);
+        }
+    }
+
+    fn part(&mut self, part: &Part) {
+        self.begin_line(&part.info);
+        self.write("# ");
+        self.write(&part.ident);
+        match &part.properties {
+            Some(properties) => {
+                self.write(" => ");
+                self.object(properties);
+                self.end_line(properties.info.span.end());
+            }
+            None => self.end_header(part.info.span.start().line()),
+        }
+
+        for element in &part.elements {
+            self.element(element);
+        }
+    }
+
+    /// Dialogue lines and narration sit at the part's level; choices are indented under them.
+    fn element(&mut self, element: &Element) {
+        match element {
+            Element::Narration(narration) => self.quote_line("* ", &narration.quote),
+            Element::Dialogue(dialogue) => {
+                self.begin_line(&dialogue.info);
+                self.write("[");
+                self.write(&dialogue.speaker);
+                self.write("]");
+                self.end_header(dialogue.info.span.start().line());
+
+                for quote in &dialogue.quotes {
+                    self.quote_line("> ", quote);
+                }
+            }
+            Element::Selection(selection) => {
+                self.indent += 1;
+                for choice in &selection.choices {
+                    self.quote_line("- ", choice);
+                }
+                self.indent -= 1;
+            }
+            Element::Directive(directive) => {
+                self.begin_line(&directive.info);
+                self.write("! ");
+                self.write(&directive.name);
+                self.write("(");
+                self.exprs(&directive.arguments);
+                self.write(")");
+                self.end_line(directive.info.span.end());
+            }
+        }
+    }
+
+    fn quote_line(&mut self, marker: &str, quote: &QuoteDecl) {
+        self.begin_line(&quote.info);
+        self.write(marker);
+        let start = quote.info.span.start();
+        self.string(start, &quote.text);
+        if let Some(properties) = &quote.properties {
+            self.write(" ");
+            self.object(properties);
+        }
+        self.end_line(quote.info.span.end());
+    }
+
+    fn object(&mut self, object: &ObjectDecl) {
+        let mut entries = object.map.iter().collect::<Vec<_>>();
+        // The map is keyed by name, so recover the order the properties were written in.
+        entries.sort_by_key(|(_, value)| position(expr_start(value)));
+
+        if entries.is_empty() {
+            self.write("{}");
+            return;
+        }
+
+        let span = &object.info.span;
+        if !self.has_comments_within(span) && entries.iter().all(|(_, value)| is_flat(value)) {
+            let flat = self.flat(|printer| {
+                printer.write("{ ");
+                for (index, (key, value)) in entries.iter().enumerate() {
+                    if index > 0 {
+                        printer.write(", ");
+                    }
+                    printer.write(key);
+                    printer.write(": ");
+                    printer.expr(value);
+                }
+                printer.write(" }");
+            });
+
+            if self.column() + flat.chars().count() <= MAX_WIDTH {
+                self.write(&flat);
+                self.seen(span.end().line());
+                return;
+            }
+        }
+
+        self.open("{", span.start());
+        for (index, (key, value)) in entries.iter().enumerate() {
+            let line = expr_start(value).line();
+            self.comments_before(line);
+            self.keep_gap(line);
+            self.write(key);
+            self.write(": ");
+            self.expr(value);
+            if index + 1 < entries.len() {
+                self.write(",");
+            }
+            self.end_line(value.info().span.end());
+        }
+        self.close("}", span.end().line());
+    }
+
+    fn block(&mut self, block: &BlockStmt) {
+        let span = &block.info.span;
+        if block.statements.is_empty() && !self.has_comments_within(span) {
+            self.write("{}");
+            self.seen(span.end().line());
+            return;
+        }
+
+        self.open("{", span.start());
+        for statement in &block.statements {
+            self.statement(statement);
+        }
+        self.close("}", span.end().line());
+    }
+
+    fn statement(&mut self, statement: &Stmt) {
+        let info = statement.info();
+        self.begin_line(info);
+
+        match statement {
+            Stmt::Expr(stmt) => {
+                self.expr(&stmt.expr);
+                self.write(";");
+            }
+            Stmt::Block(block) => self.block(block),
+            Stmt::Let(stmt) => {
+                self.write("let ");
+                self.write(&stmt.name);
+                self.write(" = ");
+                self.expr(&stmt.initializer);
+                self.write(";");
+            }
+            Stmt::Goto(stmt) => {
+                self.write("goto ");
+                self.expr(&stmt.target);
+                self.write(";");
+            }
+            Stmt::If(stmt) => self.if_stmt(stmt),
+            Stmt::Return(stmt) => match &stmt.value {
+                Some(value) => {
+                    self.write("return ");
+                    self.expr(value);
+                    self.write(";");
+                }
+                None => self.write("return;"),
+            },
+            Stmt::While(stmt) => {
+                self.write("while (");
+                self.expr(&stmt.condition);
+                self.write(") ");
+                self.block(&stmt.body);
+            }
+            Stmt::For(stmt) => {
+                self.write("for (");
+                match stmt.initializer.as_deref() {
+                    Some(Stmt::Let(initializer)) => {
+                        self.write("let ");
+                        self.write(&initializer.name);
+                        self.write(" = ");
+                        self.expr(&initializer.initializer);
+                    }
+                    Some(Stmt::Expr(initializer)) => self.expr(&initializer.expr),
+                    _ => {}
+                }
+                self.write(";");
+                if let Some(condition) = &stmt.condition {
+                    self.write(" ");
+                    self.expr(condition);
+                }
+                self.write(";");
+                if let Some(increment) = &stmt.increment {
+                    self.write(" ");
+                    self.expr(increment);
+                }
+                self.write(") ");
+                self.block(&stmt.body);
+            }
+            Stmt::Break(_) => self.write("break;"),
+            Stmt::Continue(_) => self.write("continue;"),
+        }
+
+        self.end_line(info.span.end());
+    }
+
+    fn if_stmt(&mut self, stmt: &IfStmt) {
+        self.write("if (");
+        self.expr(&stmt.condition);
+        self.write(") ");
+        self.block(&stmt.then_branch);
+
+        match &stmt.else_branch {
+            Some(ElseClause::If(stmt)) => {
+                self.write(" else ");
+                self.if_stmt(stmt);
+            }
+            Some(ElseClause::Block(block)) => {
+                self.write(" else ");
+                self.block(block);
+            }
+            None => {}
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Binary {
+                left,
+                operator,
+                right,
+                ..
+            } => {
+                self.expr(left);
+                self.write(" ");
+                self.write(binary_operator(operator));
+                self.write(" ");
+                self.expr(right);
+            }
+            Expr::Unary {
+                operator, right, ..
+            } => {
+                self.write(match operator {
+                    UnaryOperator::Not => "!",
+                    UnaryOperator::Negate => "-",
+                });
+                self.expr(right);
+            }
+            Expr::Assignment { name, value, .. } => {
+                self.expr(name);
+                self.write(" = ");
+                self.expr(value);
+            }
+            Expr::MemberAccess { left, members, .. } => {
+                self.expr(left);
+                for member in members {
+                    // The parser wraps `[index]` members in a grouping.
+                    match member {
+                        Expr::Grouping { expression, .. } => {
+                            self.write("[");
+                            self.expr(expression);
+                            self.write("]");
+                        }
+                        member => {
+                            self.write(".");
+                            self.expr(member);
+                        }
+                    }
+                }
+            }
+            Expr::Call {
+                callee, arguments, ..
+            } => {
+                self.expr(callee);
+                self.write("(");
+                self.exprs(arguments);
+                self.write(")");
+            }
+            Expr::Primary {
+                value: Primary::Literal(literal),
+                ..
+            } => self.literal(literal),
+            Expr::Primary {
+                value: Primary::Primitive(primitive),
+                ..
+            } => self.primitive(primitive),
+            Expr::Grouping { expression, .. } => {
+                self.write("(");
+                self.expr(expression);
+                self.write(")");
+            }
+        }
+    }
+
+    fn exprs(&mut self, exprs: &[Expr]) {
+        for (index, expr) in exprs.iter().enumerate() {
+            if index > 0 {
+                self.write(", ");
+            }
+            self.expr(expr);
+        }
+    }
+
+    fn literal(&mut self, literal: &Literal) {
+        match literal {
+            Literal::Boolean { value, .. } => self.write(if *value { "true" } else { "false" }),
+            Literal::String { info, value } => self.string(info.span.start(), value),
+            Literal::Number { value, .. } => self.write(&value.to_string()),
+            Literal::None { .. } => self.write("none"),
+        }
+    }
+
+    fn primitive(&mut self, primitive: &Primitive) {
+        match primitive {
+            Primitive::Identifier { name, .. } => self.write(name),
+            Primitive::StoryIdentifier { name, .. } => {
+                self.write("@");
+                self.write(name);
+            }
+            Primitive::Context { .. } => self.write("context"),
+            Primitive::Grouping { expr, .. } => {
+                self.write("(");
+                self.expr(expr);
+                self.write(")");
+            }
+            Primitive::Object { value, .. } => self.object(value),
+            Primitive::List { elements, .. } => {
+                self.write("[");
+                self.exprs(elements);
+                self.write("]");
+            }
+            Primitive::Closure { params, body, .. } => {
+                self.write("(");
+                for (index, param) in params.iter().enumerate() {
+                    if index > 0 {
+                        self.write(", ");
+                    }
+                    self.primitive(param);
+                }
+                self.write(") => ");
+                match expression_body(body) {
+                    Some(value) => self.expr(value),
+                    None => self.block(body),
+                }
+            }
+        }
+    }
+
+    fn string(&mut self, start: &LineCol, value: &str) {
+        match self.strings.get(&(start.line(), start.col())) {
+            Some(raw) => {
+                let raw = *raw;
+                self.write(raw);
+                self.seen(start.line() + raw.matches('\n').count());
+            }
+            None => self.write(&quoted(value)),
+        }
+    }
+
+    /// Renders with a scratch printer that has no comments to place, for one-line layouts.
+    fn flat(&self, print: impl FnOnce(&mut Printer<'a, 'src>)) -> String {
+        let mut printer = Printer::new(self.strings, self.line_ends, &[]);
+        print(&mut printer);
+        printer.out
+    }
+
+    fn open(&mut self, delimiter: &str, at: &LineCol) {
+        self.write(delimiter);
+        self.seen(at.line());
+        self.trailing_comment(at.line(), at);
+        self.out.push('\n');
+        self.indent += 1;
+    }
+
+    fn close(&mut self, delimiter: &str, line: usize) {
+        self.comments_before(line);
+        self.indent -= 1;
+        self.write(delimiter);
+        self.seen(line);
+    }
+
+    fn begin_line(&mut self, info: &NodeInfo) {
+        let line = info.span.start().line();
+        self.comments_before(line);
+        self.keep_gap(line);
+    }
+
+    /// Ends the output line after a node that ends at `end`.
+    fn end_line(&mut self, end: &LineCol) {
+        self.seen(end.line());
+        self.trailing_comment(self.last_line, end);
+        self.out.push('\n');
+    }
+
+    /// Ends a header such as `# part` or `[Speaker]`, which has its source line to itself.
+    fn end_header(&mut self, line: usize) {
+        self.end_line(&LineCol::new(line, usize::MAX));
+    }
+
+    /// Prints the comments written on lines before `line`, each on a line of its own.
+    fn comments_before(&mut self, line: usize) {
+        while let Some(comment) = self
+            .comments
+            .get(self.next_comment)
+            .filter(|comment| comment.line < line)
+        {
+            self.keep_gap(comment.line);
+            self.write("//");
+            self.write(comment.text.trim_end());
+            self.out.push('\n');
+            self.seen(comment.line);
+            self.next_comment += 1;
+        }
+    }
+
+    /// Appends the comment that ends source line `line`, if the next one does and no token
+    /// after `end` shares that line. A comment trails the last token on its line, so a
+    /// one-line object or block keeps it after its closing brace when it is expanded.
+    fn trailing_comment(&mut self, line: usize, end: &LineCol) {
+        let trails_end = end.line() != line
+            || self
+                .line_ends
+                .get(&line)
+                .is_none_or(|last| *last <= end.col());
+        if let Some(comment) = self
+            .comments
+            .get(self.next_comment)
+            .filter(|comment| comment.line == line && trails_end)
+        {
+            self.out.push_str(" //");
+            self.out.push_str(comment.text.trim_end());
+            self.next_comment += 1;
+        }
+    }
+
+    /// Whether a comment sits inside `span`; one trailing its closing delimiter does not count.
+    fn has_comments_within(&self, span: &Span) -> bool {
+        let (start, end) = (span.start(), span.end());
+        self.comments[self.next_comment..].iter().any(|comment| {
+            (start.line()..=end.line()).contains(&comment.line)
+                && !(comment.line == end.line() && comment.column > end.col())
+        })
+    }
+
+    /// Keeps a single blank line before `line` where the source had at least one.
+    fn keep_gap(&mut self, line: usize) {
+        if self.last_line > 0 && line > self.last_line + 1 {
+            self.blank_line();
+        }
+    }
+
+    fn blank_line(&mut self) {
+        if !self.out.is_empty() && !self.out.ends_with("\n\n") && !self.out.ends_with("{\n") {
+            self.out.push('\n');
+        }
+    }
+
+    fn seen(&mut self, line: usize) {
+        self.last_line = self.last_line.max(line);
+    }
+
+    fn column(&self) -> usize {
+        let line = self.out.rsplit('\n').next().unwrap_or_default();
+        line.chars().count()
+    }
+
+    fn write(&mut self, text: &str) {
+        if self.out.is_empty() || self.out.ends_with('\n') {
+            for _ in 0..self.indent {
+                self.out.push_str(INDENT);
+            }
+        }
+        self.out.push_str(text);
+    }
+}
+
+/// Whether `expr` can be printed on one line: no statement blocks and no multi-line strings.
+fn is_flat(expr: &Expr) -> bool {
+    match expr {
+        Expr::Binary { left, right, .. } => is_flat(left) && is_flat(right),
+        Expr::Unary { right, .. } => is_flat(right),
+        Expr::Assignment { name, value, .. } => is_flat(name) && is_flat(value),
+        Expr::MemberAccess { left, members, .. } => is_flat(left) && members.iter().all(is_flat),
+        Expr::Call {
+            callee, arguments, ..
+        } => is_flat(callee) && arguments.iter().all(is_flat),
+        Expr::Grouping { expression, .. } => is_flat(expression),
+        Expr::Primary { value, .. } => match value {
+            Primary::Literal(Literal::String { value, .. }) => !value.contains('\n'),
+            Primary::Literal(_) => true,
+            Primary::Primitive(Primitive::Grouping { expr, .. }) => is_flat(expr),
+            Primary::Primitive(Primitive::Object { value, .. }) => value.map.values().all(is_flat),
+            Primary::Primitive(Primitive::List { elements, .. }) => elements.iter().all(is_flat),
+            Primary::Primitive(Primitive::Closure { body, .. }) => {
+                expression_body(body).is_some_and(is_flat)
+            }
+            Primary::Primitive(_) => true,
+        },
+    }
+}
+
+/// The value of an `() => expr` closure, which the parser desugars into a block holding one
+/// `return` that shares the expression's span.
+fn expression_body(body: &BlockStmt) -> Option<&Expr> {
+    match body.statements.as_slice() {
+        [Stmt::Return(stmt)] => stmt
+            .value
+            .as_ref()
+            .filter(|value| value.info().span == body.info.span),
+        _ => None,
+    }
+}
+
+/// Where `expr` starts. Primaries only record their last token, so look at the node inside.
+fn expr_start(expr: &Expr) -> &LineCol {
+    match expr {
+        Expr::Primary {
+            value: Primary::Primitive(primitive),
+            ..
+        } => primitive.info().span.start(),
+        Expr::Primary {
+            value: Primary::Literal(literal),
+            ..
+        } => literal.info().span.start(),
+        Expr::Binary { left, .. } | Expr::Assignment { name: left, .. } => expr_start(left),
+        Expr::MemberAccess { left, .. } => expr_start(left),
+        Expr::Call { callee, .. } => expr_start(callee),
+        Expr::Unary { info, .. } | Expr::Grouping { info, .. } => info.span.start(),
+    }
+}
+
+fn position(line_col: &LineCol) -> (usize, usize) {
+    (line_col.line(), line_col.col())
+}
+
+fn binary_operator(operator: &BinaryOperator) -> &'static str {
+    match operator {
+        BinaryOperator::EqualEqual => "==",
+        BinaryOperator::NotEqual => "!=",
+        BinaryOperator::Greater => ">",
+        BinaryOperator::GreaterEqual => ">=",
+        BinaryOperator::Less => "<",
+        BinaryOperator::LessEqual => "<=",
+        BinaryOperator::Add => "+",
+        BinaryOperator::Subtraction => "-",
+        BinaryOperator::Multiply => "*",
+        BinaryOperator::Divide => "/",
+        BinaryOperator::And => "and",
+        BinaryOperator::Or => "or",
+    }
+}
+
+/// Quotes a decoded string for the rare node without source text, escaping what the lexer
+/// would otherwise decode.
+fn quoted(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for ch in value.chars() {
+        match ch {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            ch => quoted.push(ch),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
@@ -0,0 +1,66 @@
+/// Parameter or result type of a builtin, as far as the analyzer checks it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuiltinType {
+    Number,
+    String,
+    Boolean,
+    None,
+    /// Any value; checked by the builtin when it runs.
+    Unknown,
+    /// A list of strings, such as the result of `obj.keys`.
+    StringList,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BuiltinSignature {
+    /// `str`, `num`, `list` or `obj`; `None` for globals such as `random`.
+    pub namespace: Option<&'static str>,
+    pub name: &'static str,
+    pub parameters: &'static [BuiltinType],
+    pub return_type: BuiltinType,
+}
+
+impl BuiltinSignature {
+    const fn new(
+        namespace: Option<&'static str>,
+        name: &'static str,
+        parameters: &'static [BuiltinType],
+        return_type: BuiltinType,
+    ) -> Self {
+        Self {
+            namespace,
+            name,
+            parameters,
+            return_type,
+        }
+    }
+}
+
+/// Every builtin scripts can call, in the order of `fabc_rt::Builtin`. The analyzer types
+/// calls from this table and the runtime defines exactly these globals and namespaces.
+pub const BUILTINS: [BuiltinSignature; 13] = {
+    use BuiltinType::*;
+
+    [
+        BuiltinSignature::new(Some("str"), "len", &[String], Number),
+        BuiltinSignature::new(Some("str"), "upper", &[String], String),
+        BuiltinSignature::new(Some("num"), "floor", &[Number], Number),
+        BuiltinSignature::new(Some("num"), "clamp", &[Number, Number, Number], Number),
+        BuiltinSignature::new(Some("num"), "to_string", &[Number], String),
+        BuiltinSignature::new(Some("list"), "push", &[Unknown, Unknown], None),
+        BuiltinSignature::new(Some("obj"), "has", &[Unknown, String], Boolean),
+        BuiltinSignature::new(Some("obj"), "keys", &[Unknown], StringList),
+        BuiltinSignature::new(Option::None, "random", &[], Number),
+        BuiltinSignature::new(Option::None, "random_int", &[Number, Number], Number),
+        BuiltinSignature::new(Option::None, "pick", &[Unknown], Unknown),
+        BuiltinSignature::new(Option::None, "visits", &[Unknown], Number),
+        BuiltinSignature::new(Option::None, "seen", &[], Boolean),
+    ]
+};
+
+/// Whether `name` is a builtin namespace. Scripts cannot assign to one or to its members.
+pub fn is_builtin_namespace(name: &str) -> bool {
+    BUILTINS
+        .iter()
+        .any(|builtin| builtin.namespace == Some(name))
+}
@@ -45,6 +45,7 @@ pub enum Expr {
     StoryReference(String),
     Context,
     Object(BTreeMap<String, Expr>),
+    List(Vec<Expr>),
     Closure(FunctionId),
     Call {
         callee: Box<Expr>,
@@ -1,10 +1,12 @@
+mod builtins;
 mod expr;
 mod stmt;
 mod story;
 
+pub use builtins::{is_builtin_namespace, BuiltinSignature, BuiltinType, BUILTINS};
 pub use expr::{BinaryOperator, Expr, Literal, MemberSegment, UnaryOperator};
 pub use stmt::{Block, Stmt};
 pub use story::{
-    DialogueSpec, FunctionId, FunctionSpec, PartSpec, QuoteSpec, SelectionSpec, StepSpec,
-    StoryProgram,
+    ChoiceGuard, DialogueSpec, DirectiveSpec, FunctionId, FunctionSpec, PartSpec, QuoteSpec,
+    SelectionSpec, StepSpec, StoryProgram, TextSegment,
 };
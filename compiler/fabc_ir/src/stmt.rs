@@ -20,4 +20,16 @@ pub enum Stmt {
         else_branch: Option<Box<Stmt>>,
     },
     Return(Option<Expr>),
+    While {
+        condition: Expr,
+        body: Block,
+    },
+    For {
+        initializer: Option<Box<Stmt>>,
+        condition: Option<Expr>,
+        increment: Option<Expr>,
+        body: Block,
+    },
+    Break,
+    Continue,
 }
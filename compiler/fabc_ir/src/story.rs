@@ -25,6 +25,16 @@ impl StoryProgram {
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct PartSpec {
     pub id: String,
+    /// Static `# id => { ... }` properties, without the hooks.
+    #[serde(default)]
+    pub properties: BTreeMap<String, Expr>,
+    /// Runs when the cursor moves into the part, before its first step.
+    #[serde(default)]
+    pub on_enter: Option<FunctionId>,
+    /// Runs when the cursor leaves the part, by `goto` or by running past its last step. A
+    /// `goto` to the part itself leaves and re-enters it, running `on_exit` then `on_enter`.
+    #[serde(default)]
+    pub on_exit: Option<FunctionId>,
     pub steps: Vec<StepSpec>,
 }
 
@@ -33,6 +43,7 @@ pub enum StepSpec {
     Narration(QuoteSpec),
     Dialogue(DialogueSpec),
     Selection(SelectionSpec),
+    Directive(DirectiveSpec),
 }
 
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -46,12 +57,46 @@ pub struct SelectionSpec {
     pub choices: Vec<QuoteSpec>,
 }
 
+/// An engine cue such as `! play_music("rain")`, handed to the host in story order.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct DirectiveSpec {
+    pub node_id: usize,
+    pub name: String,
+    pub args: Vec<Expr>,
+}
+
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct QuoteSpec {
     pub node_id: usize,
     pub text: String,
+    /// Interpolated form of `text`; empty when the text has no `{expr}` segments.
+    #[serde(default)]
+    pub segments: Vec<TextSegment>,
     pub properties: BTreeMap<String, Expr>,
     pub next_action: Option<FunctionId>,
+    /// Only meaningful on selection choices.
+    #[serde(default)]
+    pub guard: Option<ChoiceGuard>,
+    /// `once: true`: the choice disappears after it was picked.
+    #[serde(default)]
+    pub once: bool,
+    /// `fallback: true`: never listed, taken automatically when no other choice can be.
+    #[serde(default)]
+    pub fallback: bool,
+}
+
+/// A `when`/`if` condition on a choice. Failing choices are hidden unless `show_disabled` keeps
+/// them listed as unavailable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ChoiceGuard {
+    pub condition: FunctionId,
+    pub show_disabled: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum TextSegment {
+    Text(String),
+    Interpolation(FunctionId),
 }
 
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
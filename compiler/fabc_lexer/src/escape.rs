@@ -0,0 +1,200 @@
+use std::borrow::Cow;
+
+/// A malformed escape sequence, located by its byte offset in the unescaped input.
+#[derive(Debug, PartialEq)]
+pub(crate) struct InvalidEscape {
+    pub(crate) offset: usize,
+    pub(crate) escape: String,
+}
+
+/// Decodes `\"`, `\\`, `\n`, `\t` and `\u{..}`, borrowing the input when it has no escapes.
+pub(crate) fn unescape(raw: &str) -> Result<Cow<'_, str>, InvalidEscape> {
+    if !raw.contains('\\') {
+        return Ok(Cow::Borrowed(raw));
+    }
+
+    let mut decoded = String::with_capacity(raw.len());
+    let mut chars = raw.char_indices().peekable();
+
+    while let Some((offset, ch)) = chars.next() {
+        if ch != '\\' {
+            decoded.push(ch);
+            continue;
+        }
+
+        let invalid = |escape: &str| InvalidEscape {
+            offset,
+            escape: escape.to_string(),
+        };
+
+        match chars.next() {
+            Some((_, '"')) => decoded.push('"'),
+            Some((_, '\\')) => decoded.push('\\'),
+            Some((_, 'n')) => decoded.push('\n'),
+            Some((_, 't')) => decoded.push('\t'),
+            Some((_, 'u')) => {
+                let rest = &raw[offset + 2..];
+                let Some(end) = rest.strip_prefix('{').and_then(|rest| rest.find('}')) else {
+                    return Err(invalid("\\u"));
+                };
+                let digits = &rest[1..end + 1];
+                let escape = &raw[offset..offset + 2 + end + 2];
+                let scalar = (1..=6)
+                    .contains(&digits.len())
+                    .then(|| u32::from_str_radix(digits, 16).ok())
+                    .flatten()
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| invalid(escape))?;
+
+                decoded.push(scalar);
+                for _ in 0..digits.len() + 2 {
+                    chars.next();
+                }
+            }
+            Some((_, other)) => return Err(invalid(&format!("\\{other}"))),
+            None => return Err(invalid("\\")),
+        }
+    }
+
+    Ok(Cow::Owned(decoded))
+}
+
+/// For every byte of `unescape(text)`, the offset in `text` of the character or escape it came
+/// from, followed by `text.len()`. `text` must already have unescaped cleanly.
+pub(crate) fn decoded_offsets(text: &str) -> Vec<usize> {
+    let mut offsets = Vec::with_capacity(text.len() + 1);
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((offset, ch)) = chars.next() {
+        let decoded = if ch != '\\' {
+            ch
+        } else {
+            match chars.next() {
+                Some((_, 'n')) => '\n',
+                Some((_, 't')) => '\t',
+                Some((_, 'u')) => {
+                    let rest = &text[offset + 2..];
+                    let end = rest.find('}').unwrap_or(rest.len());
+                    let scalar = u32::from_str_radix(&rest[1..end], 16)
+                        .ok()
+                        .and_then(char::from_u32)
+                        .unwrap_or(char::REPLACEMENT_CHARACTER);
+                    while chars
+                        .next_if(|(index, _)| *index <= offset + 2 + end)
+                        .is_some()
+                    {}
+                    scalar
+                }
+                Some((_, other)) => other,
+                None => break,
+            }
+        };
+        offsets.extend(std::iter::repeat_n(offset, decoded.len_utf8()));
+    }
+
+    offsets.push(text.len());
+    offsets
+}
+
+/// Drops the line breaks that follow an opening and precede a closing `"""`, then removes the
+/// indentation shared by every line, including the closing delimiter's.
+pub(crate) fn strip_indent(raw: &str) -> String {
+    strip_indent_mapped(raw).0
+}
+
+/// [`strip_indent`], also returning the offset in `raw` of every byte it kept, followed by the
+/// offset just past the last one.
+pub(crate) fn strip_indent_mapped(raw: &str) -> (String, Vec<usize>) {
+    let mut lines: Vec<(usize, &str)> = raw
+        .split('\n')
+        .scan(0, |start, line| {
+            let offset = *start;
+            *start += line.len() + 1;
+            Some((offset, line))
+        })
+        .collect();
+    if lines.len() > 1 && lines[0].1.trim().is_empty() {
+        lines.remove(0);
+    }
+    let closing = match lines.last() {
+        Some((_, last)) if lines.len() > 1 && last.trim().is_empty() => lines.pop(),
+        _ => None,
+    };
+
+    let indent_of = |line: &str| line.chars().take_while(|c| matches!(c, ' ' | '\t')).count();
+    let indent = lines
+        .iter()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .chain(closing.iter())
+        .map(|(_, line)| indent_of(line))
+        .min()
+        .unwrap_or(0);
+
+    let mut stripped = String::with_capacity(raw.len());
+    let mut offsets = Vec::with_capacity(raw.len() + 1);
+    for (index, (start, line)) in lines.iter().enumerate() {
+        if index > 0 {
+            // The line break that ended the previous kept line.
+            stripped.push('\n');
+            offsets.push(start - 1);
+        }
+        let trimmed = line.strip_suffix('\r').unwrap_or(line);
+        // Indentation is ASCII whitespace, so its character count is also its byte length.
+        let cut = indent.min(indent_of(trimmed));
+        stripped.push_str(&trimmed[cut..]);
+        offsets.extend(start + cut..start + trimmed.len());
+    }
+    offsets.push(lines.last().map_or(0, |(start, line)| {
+        start + line.strip_suffix('\r').unwrap_or(line).len()
+    }));
+
+    (stripped, offsets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_escapes() {
+        assert_eq!(
+            unescape(r#"say \"hi\"\n\tback\\slash \u{1F600}"#).unwrap(),
+            "say \"hi\"\n\tback\\slash \u{1F600}"
+        );
+        assert!(matches!(unescape("plain"), Ok(Cow::Borrowed("plain"))));
+    }
+
+    #[test]
+    fn locates_invalid_escapes() {
+        assert_eq!(
+            unescape(r"ok \q"),
+            Err(InvalidEscape {
+                offset: 3,
+                escape: r"\q".to_string()
+            })
+        );
+        assert_eq!(
+            unescape(r"\u{110000}"),
+            Err(InvalidEscape {
+                offset: 0,
+                escape: r"\u{110000}".to_string()
+            })
+        );
+        assert_eq!(
+            unescape(r"\u12"),
+            Err(InvalidEscape {
+                offset: 0,
+                escape: r"\u".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn strips_shared_indentation() {
+        let raw = "\n        The rain had not stopped.\n          It never would.\n\n        ";
+        assert_eq!(
+            strip_indent(raw),
+            "The rain had not stopped.\n  It never would.\n"
+        );
+    }
+}
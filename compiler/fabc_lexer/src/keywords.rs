@@ -11,6 +11,8 @@ pub enum KeywordKind {
     Else,
     Return,
     Goto,
+    Break,
+    Continue,
 
     // Definitions
     Story,
@@ -43,6 +45,8 @@ impl Display for KeywordKind {
             KeywordKind::Else => write!(f, "else"),
             KeywordKind::Return => write!(f, "return"),
             KeywordKind::Goto => write!(f, "goto"),
+            KeywordKind::Break => write!(f, "break"),
+            KeywordKind::Continue => write!(f, "continue"),
             KeywordKind::Story => write!(f, "Story"),
             KeywordKind::Module => write!(f, "module"),
             KeywordKind::As => write!(f, "as"),
@@ -67,6 +71,8 @@ impl KeywordKind {
             "else" => Some(KeywordKind::Else),
             "return" => Some(KeywordKind::Return),
             "goto" => Some(KeywordKind::Goto),
+            "break" => Some(KeywordKind::Break),
+            "continue" => Some(KeywordKind::Continue),
             "Story" => Some(KeywordKind::Story),
             "module" => Some(KeywordKind::Module),
             "as" => Some(KeywordKind::As),
@@ -1,55 +1,70 @@
+use std::borrow::Cow;
+
 use crate::{
+    escape::{strip_indent, unescape},
     keywords::KeywordKind,
-    tokens::{ErrorKind, Token, TokenKind},
+    tokens::{Comment, ErrorKind, StringLiteral, StringStyle, Token, TokenKind},
 };
 
+mod escape;
 pub mod keywords;
 pub mod tokens;
 
 pub struct Lexer<'src> {
     tokens: Vec<Token<'src>>,
+    comments: Vec<Comment<'src>>,
     source: &'src str,
     start: usize,
     current: usize,
     line: usize,
+    line_start: usize,
     col: usize,
     col_start: usize,
 }
 
 impl<'src> Lexer<'src> {
     pub fn tokenize(input: &'src str) -> Vec<Token<'src>> {
+        Self::tokenize_with_comments(input).0
+    }
+
+    /// Like [`Lexer::tokenize`], also returning the `//` comments it skipped, in source order.
+    pub fn tokenize_with_comments(input: &'src str) -> (Vec<Token<'src>>, Vec<Comment<'src>>) {
         let mut lexer = Self {
             tokens: Vec::new(),
+            comments: Vec::new(),
             source: input,
             start: 0,
             current: 0,
             line: 1,
+            line_start: 1,
             col: 1,
             col_start: 1,
         };
 
         lexer.scan_tokens();
 
-        lexer.tokens
+        (lexer.tokens, lexer.comments)
     }
 
     pub fn push_token(&mut self, kind: TokenKind<'src>) {
-        let column = match kind {
-            TokenKind::EoF => self.current,
-            _ => self.col_start,
-        };
-
-        let length = match kind {
-            TokenKind::EoF => 0,
-            _ => self.col - self.col_start,
+        let token = match kind {
+            TokenKind::EoF => Token {
+                kind,
+                line: self.line,
+                column: self.col - 1,
+                length: 0,
+                offset: self.current,
+            },
+            _ => Token {
+                kind,
+                line: self.line_start,
+                column: self.col_start,
+                length: self.source[self.start..self.current].chars().count(),
+                offset: self.start,
+            },
         };
 
-        self.tokens.push(Token {
-            kind,
-            line: self.line,
-            column,
-            length,
-        });
+        self.tokens.push(token);
     }
 
     fn scan_tokens(&mut self) {
@@ -119,6 +134,12 @@ impl<'src> Lexer<'src> {
                     while self.peek() != '\n' && !self.is_at_end() {
                         self.advance();
                     }
+                    self.comments.push(Comment {
+                        text: self.source[self.start + 2..self.current].trim_end_matches('\r'),
+                        line: self.line_start,
+                        column: self.col_start,
+                        offset: self.start,
+                    });
                 } else {
                     self.push_token(TokenKind::Slash)
                 }
@@ -128,15 +149,13 @@ impl<'src> Lexer<'src> {
                     self.advance();
                 }
             }
-            '\n' => {
-                self.line += 1;
-                self.col = 1;
-            }
+            '\n' => {}
 
             // Literals.
             '"' => self.string(),
+            'r' if self.is_raw_string_start() => self.raw_string(),
             '0'..='9' => self.number(),
-            'a'..='z' | 'A'..='Z' | '_' => self.identifier(),
+            c if Self::is_identifier_start(c) => self.identifier(),
             _ => self.push_token(TokenKind::Error(ErrorKind::UnrecognizedCharacter)),
         }
     }
@@ -147,11 +166,20 @@ impl<'src> Lexer<'src> {
 
     fn reset_start(&mut self) {
         self.start = self.current;
+        self.line_start = self.line;
         self.col_start = self.col;
     }
 
+    fn is_identifier_start(c: char) -> bool {
+        c.is_alphabetic() || c == '_'
+    }
+
+    fn is_identifier_continue(c: char) -> bool {
+        c.is_alphanumeric() || c == '_'
+    }
+
     fn identifier(&mut self) {
-        while self.peek().is_ascii_alphanumeric() || self.peek() == '_' {
+        while Self::is_identifier_continue(self.peek()) {
             self.advance();
         }
 
@@ -188,20 +216,144 @@ impl<'src> Lexer<'src> {
     }
 
     fn string(&mut self) {
-        while self.peek() != '"' && !self.is_at_end() {
-            if self.peek() == '\n' {
-                self.line += 1;
+        let delimiter = if self.source[self.current..].starts_with("\"\"") {
+            self.advance();
+            self.advance();
+            "\"\"\""
+        } else {
+            "\""
+        };
+
+        // Inside `{...}` a `"` opens a nested literal wherever an operand can start, so
+        // `{str.upper("x")}` stays one quote. After an operand it ends the quote as usual and
+        // leaves the unclosed `{` for the parser to report.
+        let mut depth = 0usize;
+        let mut after_operand = false;
+        while !self.is_at_end() {
+            let opens_literal = depth > 0 && !after_operand && self.peek() == '"';
+            if !opens_literal && self.source[self.current..].starts_with(delimiter) {
+                break;
             }
+
+            let ch = self.advance();
+            match ch {
+                '\\' => {
+                    self.advance();
+                }
+                '"' if opens_literal => {
+                    self.skip_nested_string();
+                    after_operand = true;
+                    continue;
+                }
+                '{' if depth == 0 && self.peek() == '{' => {
+                    self.advance();
+                }
+                '{' => depth += 1,
+                '}' if depth > 0 => depth -= 1,
+                _ => {}
+            }
+            if !ch.is_whitespace() {
+                after_operand = depth > 0
+                    && (Self::is_identifier_continue(ch) || matches!(ch, ')' | ']' | '}'));
+            }
+        }
+
+        if self.is_at_end() {
+            self.push_token(TokenKind::Error(ErrorKind::UnterminatedString));
+            return;
+        }
+        for _ in 0..delimiter.len() {
+            self.advance();
+        }
+
+        let content_start = self.start + delimiter.len();
+        let raw = &self.source[content_start..self.current - delimiter.len()];
+        let value = match unescape(raw) {
+            // Escapes are validated against the source so errors keep their real position.
+            Ok(_) if delimiter.len() == 3 => Cow::Owned(
+                unescape(&strip_indent(raw))
+                    .unwrap_or_default()
+                    .into_owned(),
+            ),
+            Ok(value) => value,
+            Err(error) => {
+                self.push_escape_error(content_start + error.offset, error.escape);
+                return;
+            }
+        };
+        self.push_token(TokenKind::String(StringLiteral {
+            value,
+            raw,
+            style: if delimiter.len() == 3 {
+                StringStyle::Triple
+            } else {
+                StringStyle::Quoted
+            },
+        }));
+    }
+
+    /// Moves past a string literal nested in an interpolation, whose opening `"` is consumed.
+    fn skip_nested_string(&mut self) {
+        while !self.is_at_end() && self.peek() != '"' {
+            if self.advance() == '\\' {
+                self.advance();
+            }
+        }
+        self.advance();
+    }
+
+    /// Reports a bad escape at its own position inside the literal instead of at the quote.
+    fn push_escape_error(&mut self, offset: usize, escape: String) {
+        let preceding = &self.source[self.start..offset];
+        let column = match preceding.rfind('\n') {
+            Some(newline) => preceding[newline + 1..].chars().count() + 1,
+            None => self.col_start + preceding.chars().count(),
+        };
+
+        self.tokens.push(Token {
+            line: self.line_start + preceding.matches('\n').count(),
+            column,
+            length: escape.chars().count(),
+            offset,
+            kind: TokenKind::Error(ErrorKind::InvalidEscape(escape)),
+        });
+    }
+
+    fn is_raw_string_start(&self) -> bool {
+        self.source[self.current..]
+            .trim_start_matches('#')
+            .starts_with('"')
+    }
+
+    /// `r"..."` or `r#"..."#`, taken verbatim; each extra `#` allows the body to contain one more
+    /// `"#` run without ending the string.
+    fn raw_string(&mut self) {
+        let mut hashes = 0;
+        while self.r#match('#') {
+            hashes += 1;
+        }
+        self.advance();
+
+        let closing = format!("\"{}", "#".repeat(hashes));
+        let content_start = self.current;
+        while !self.is_at_end() && !self.source[self.current..].starts_with(&closing) {
             self.advance();
         }
 
         if self.is_at_end() {
             self.push_token(TokenKind::Error(ErrorKind::UnterminatedString));
-        } else {
+            return;
+        }
+
+        let value = &self.source[content_start..self.current];
+        for _ in 0..closing.len() {
             self.advance();
-            let value = &self.source[self.start + 1..self.current - 1];
-            self.push_token(TokenKind::String(value));
         }
+        self.push_token(TokenKind::String(StringLiteral {
+            value: Cow::Borrowed(value),
+            raw: value,
+            style: StringStyle::Raw { hashes },
+        }));
     }
 
     fn r#match(&mut self, expected: char) -> bool {
@@ -213,19 +365,11 @@ impl<'src> Lexer<'src> {
     }
 
     fn peek(&self) -> char {
-        if self.is_at_end() {
-            '\0'
-        } else {
-            self.source.as_bytes()[self.current] as char
-        }
+        self.source[self.current..].chars().next().unwrap_or('\0')
     }
 
     fn peek_next(&self) -> char {
-        if self.is_at_end() {
-            '\0'
-        } else {
-            self.source.as_bytes()[self.current + 1] as char
-        }
+        self.source[self.current..].chars().nth(1).unwrap_or('\0')
     }
 
     fn advance(&mut self) -> char {
@@ -233,8 +377,13 @@ impl<'src> Lexer<'src> {
             return '\0';
         }
         let ch = self.peek();
-        self.current += 1;
-        self.col += 1;
+        self.current += ch.len_utf8();
+        if ch == '\n' {
+            self.line += 1;
+            self.col = 1;
+        } else {
+            self.col += 1;
+        }
         ch
     }
 
@@ -257,150 +406,175 @@ mod tests {
                 line: 1,
                 column: 1,
                 length: 1,
+                offset: 0,
             },
             Token {
                 kind: TokenKind::RightParen,
                 line: 1,
                 column: 3,
                 length: 1,
+                offset: 2,
             },
             Token {
                 kind: TokenKind::LeftBrace,
                 line: 1,
                 column: 5,
                 length: 1,
+                offset: 4,
             },
             Token {
                 kind: TokenKind::RightBrace,
                 line: 1,
                 column: 7,
                 length: 1,
+                offset: 6,
             },
             Token {
                 kind: TokenKind::LeftBracket,
                 line: 1,
                 column: 9,
                 length: 1,
+                offset: 8,
             },
             Token {
                 kind: TokenKind::RightBracket,
                 line: 1,
                 column: 11,
                 length: 1,
+                offset: 10,
             },
             Token {
                 kind: TokenKind::Comma,
                 line: 1,
                 column: 13,
                 length: 1,
+                offset: 12,
             },
             Token {
                 kind: TokenKind::Dot,
                 line: 1,
                 column: 15,
                 length: 1,
+                offset: 14,
             },
             Token {
                 kind: TokenKind::Minus,
                 line: 1,
                 column: 17,
                 length: 1,
+                offset: 16,
             },
             Token {
                 kind: TokenKind::Plus,
                 line: 1,
                 column: 19,
                 length: 1,
+                offset: 18,
             },
             Token {
                 kind: TokenKind::Asterisk,
                 line: 1,
                 column: 21,
                 length: 1,
+                offset: 20,
             },
             Token {
                 kind: TokenKind::Colon,
                 line: 1,
                 column: 23,
                 length: 1,
+                offset: 22,
             },
             Token {
                 kind: TokenKind::Semicolon,
                 line: 1,
                 column: 25,
                 length: 1,
+                offset: 24,
             },
             Token {
                 kind: TokenKind::Bang,
                 line: 1,
                 column: 27,
                 length: 1,
+                offset: 26,
             },
             Token {
                 kind: TokenKind::BangEqual,
                 line: 1,
                 column: 29,
                 length: 2,
+                offset: 28,
             },
             Token {
                 kind: TokenKind::Equal,
                 line: 1,
                 column: 32,
                 length: 1,
+                offset: 31,
             },
             Token {
                 kind: TokenKind::EqualEqual,
                 line: 1,
                 column: 34,
                 length: 2,
+                offset: 33,
             },
             Token {
                 kind: TokenKind::Less,
                 line: 1,
                 column: 37,
                 length: 1,
+                offset: 36,
             },
             Token {
                 kind: TokenKind::LessEqual,
                 line: 1,
                 column: 39,
                 length: 2,
+                offset: 38,
             },
             Token {
                 kind: TokenKind::Greater,
                 line: 1,
                 column: 42,
                 length: 1,
+                offset: 41,
             },
             Token {
                 kind: TokenKind::GreaterEqual,
                 line: 1,
                 column: 44,
                 length: 2,
+                offset: 43,
             },
             Token {
                 kind: TokenKind::Slash,
                 line: 1,
                 column: 47,
                 length: 1,
+                offset: 46,
             },
             Token {
                 kind: TokenKind::ArrowRight,
                 line: 1,
                 column: 49,
                 length: 2,
+                offset: 48,
             },
             Token {
                 kind: TokenKind::Commat,
                 line: 1,
                 column: 52,
                 length: 1,
+                offset: 51,
             },
             Token {
                 kind: TokenKind::EoF,
                 line: 1,
                 column: 52,
                 length: 0,
+                offset: 52,
             },
         ];
         assert_eq!(*tokens, expected_tokens);
@@ -416,96 +590,112 @@ mod tests {
                 line: 1,
                 column: 1,
                 length: 3,
+                offset: 0,
             },
             Token {
                 kind: TokenKind::Keyword(KeywordKind::Fn),
                 line: 1,
                 column: 5,
                 length: 2,
+                offset: 4,
             },
             Token {
                 kind: TokenKind::Keyword(KeywordKind::If),
                 line: 1,
                 column: 8,
                 length: 2,
+                offset: 7,
             },
             Token {
                 kind: TokenKind::Keyword(KeywordKind::Else),
                 line: 1,
                 column: 11,
                 length: 4,
+                offset: 10,
             },
             Token {
                 kind: TokenKind::Keyword(KeywordKind::Return),
                 line: 1,
                 column: 16,
                 length: 6,
+                offset: 15,
             },
             Token {
                 kind: TokenKind::Keyword(KeywordKind::Goto),
                 line: 1,
                 column: 23,
                 length: 4,
+                offset: 22,
             },
             Token {
                 kind: TokenKind::Keyword(KeywordKind::True),
                 line: 1,
                 column: 28,
                 length: 4,
+                offset: 27,
             },
             Token {
                 kind: TokenKind::Keyword(KeywordKind::False),
                 line: 1,
                 column: 33,
                 length: 5,
+                offset: 32,
             },
             Token {
                 kind: TokenKind::Keyword(KeywordKind::None),
                 line: 1,
                 column: 39,
                 length: 4,
+                offset: 38,
             },
             Token {
                 kind: TokenKind::Keyword(KeywordKind::While),
                 line: 1,
                 column: 44,
                 length: 5,
+                offset: 43,
             },
             Token {
                 kind: TokenKind::Keyword(KeywordKind::For),
                 line: 1,
                 column: 50,
                 length: 3,
+                offset: 49,
             },
             Token {
                 kind: TokenKind::Keyword(KeywordKind::And),
                 line: 1,
                 column: 54,
                 length: 3,
+                offset: 53,
             },
             Token {
                 kind: TokenKind::Keyword(KeywordKind::Or),
                 line: 1,
                 column: 58,
                 length: 2,
+                offset: 57,
             },
             Token {
                 kind: TokenKind::Keyword(KeywordKind::Context),
                 line: 1,
                 column: 61,
                 length: 7,
+                offset: 60,
             },
             Token {
                 kind: TokenKind::Identifier("myVar"),
                 line: 1,
                 column: 69,
                 length: 5,
+                offset: 68,
             },
             Token {
                 kind: TokenKind::EoF,
                 line: 1,
                 column: 73,
                 length: 0,
+                offset: 73,
             },
         ];
         assert_eq!(*tokens, expected_tokens);
@@ -517,28 +707,36 @@ mod tests {
         let tokens = Lexer::tokenize(source);
         let expected_tokens = vec![
             Token {
-                kind: TokenKind::String("hello"),
+                kind: TokenKind::String(StringLiteral {
+                    value: "hello".into(),
+                    raw: "hello",
+                    style: StringStyle::Quoted,
+                }),
                 line: 1,
                 column: 1,
                 length: 7,
+                offset: 0,
             },
             Token {
                 kind: TokenKind::Number(123.0),
                 line: 1,
                 column: 9,
                 length: 3,
+                offset: 8,
             },
             Token {
                 kind: TokenKind::Number(45.67),
                 line: 1,
                 column: 13,
                 length: 5,
+                offset: 12,
             },
             Token {
                 kind: TokenKind::EoF,
                 line: 1,
                 column: 17,
                 length: 0,
+                offset: 17,
             },
         ];
         assert_eq!(*tokens, expected_tokens);
@@ -554,23 +752,213 @@ mod tests {
                 line: 1,
                 column: 1,
                 length: 1,
+                offset: 0,
             },
             Token {
                 kind: TokenKind::Error(ErrorKind::UnterminatedString),
                 line: 1,
                 column: 3,
                 length: 6,
+                offset: 2,
             },
             Token {
                 kind: TokenKind::EoF,
                 line: 1,
                 column: 8,
                 length: 0,
+                offset: 8,
             },
         ];
         assert_eq!(*tokens, expected_tokens);
     }
 
+    #[test]
+    fn tokenizes_unicode_text_and_identifiers() {
+        let source = "[Zoë] \"Café — 你好\" 名前";
+        let tokens = Lexer::tokenize(source);
+        let expected_tokens = vec![
+            Token {
+                kind: TokenKind::LeftBracket,
+                line: 1,
+                column: 1,
+                length: 1,
+                offset: 0,
+            },
+            Token {
+                kind: TokenKind::Identifier("Zoë"),
+                line: 1,
+                column: 2,
+                length: 3,
+                offset: 1,
+            },
+            Token {
+                kind: TokenKind::RightBracket,
+                line: 1,
+                column: 5,
+                length: 1,
+                offset: 5,
+            },
+            Token {
+                kind: TokenKind::String(StringLiteral {
+                    value: "Café — 你好".into(),
+                    raw: "Café — 你好",
+                    style: StringStyle::Quoted,
+                }),
+                line: 1,
+                column: 7,
+                length: 11,
+                offset: 7,
+            },
+            Token {
+                kind: TokenKind::Identifier("名前"),
+                line: 1,
+                column: 19,
+                length: 2,
+                offset: 26,
+            },
+            Token {
+                kind: TokenKind::EoF,
+                line: 1,
+                column: 20,
+                length: 0,
+                offset: 32,
+            },
+        ];
+        assert_eq!(*tokens, expected_tokens);
+    }
+
+    #[test]
+    fn decodes_string_escapes_and_raw_strings() {
+        let source = r##""say \"hi\"\n" r"C:\path" r#"a "quoted" word"#"##;
+        let kinds: Vec<_> = Lexer::tokenize(source)
+            .into_iter()
+            .map(|token| token.kind)
+            .collect();
+
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::String(StringLiteral {
+                    value: "say \"hi\"\n".into(),
+                    raw: r#"say \"hi\"\n"#,
+                    style: StringStyle::Quoted,
+                }),
+                TokenKind::String(StringLiteral {
+                    value: r"C:\path".into(),
+                    raw: r"C:\path",
+                    style: StringStyle::Raw { hashes: 0 },
+                }),
+                TokenKind::String(StringLiteral {
+                    value: r#"a "quoted" word"#.into(),
+                    raw: r#"a "quoted" word"#,
+                    style: StringStyle::Raw { hashes: 1 },
+                }),
+                TokenKind::EoF,
+            ]
+        );
+    }
+
+    #[test]
+    fn strips_indentation_from_triple_quoted_strings() {
+        let source = "* \"\"\"\n    The rain had not stopped.\n      \\\"Again,\\\" she said.\n    \"\"\" {}";
+        let tokens = Lexer::tokenize(source);
+
+        let TokenKind::String(literal) = &tokens[1].kind else {
+            panic!("Expected a string, found {:?}", tokens[1].kind);
+        };
+        assert_eq!(
+            literal.value,
+            "The rain had not stopped.\n  \"Again,\" she said."
+        );
+        assert_eq!(literal.style, StringStyle::Triple);
+        assert_eq!((tokens[1].line, tokens[1].column), (1, 3));
+        assert_eq!((tokens[2].line, tokens[2].column), (4, 9));
+    }
+
+    #[test]
+    fn maps_decoded_string_bytes_back_to_the_source() {
+        let source = "\"a\\\"b\" \"\"\"\n    x\n      \\ty\n    \"\"\" r#\"{z}\"#";
+        let tokens = Lexer::tokenize(source);
+        let literals: Vec<_> = tokens
+            .iter()
+            .filter_map(|token| match &token.kind {
+                TokenKind::String(literal) => Some(literal),
+                _ => None,
+            })
+            .collect();
+
+        // `a \" b`: the escaped quote decodes from the backslash two bytes in.
+        assert_eq!(literals[0].source_offsets(), vec![0, 1, 3, 4]);
+        // `x \n ␣␣ \t y`: indentation is skipped and the tab decodes from its backslash.
+        assert_eq!(literals[1].value, "x\n  \ty");
+        assert_eq!(literals[1].source_offsets(), vec![5, 6, 11, 12, 13, 15, 16]);
+        assert_eq!(literals[2].source_offsets(), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn keeps_string_literals_nested_in_interpolations_inside_the_quote() {
+        let source = r#"* "Shout {str.upper("hey")}!" "You have {gold coins""#;
+        let tokens = Lexer::tokenize(source);
+        let values: Vec<_> = tokens
+            .iter()
+            .filter_map(|token| match &token.kind {
+                TokenKind::String(literal) => Some(literal.value.as_ref()),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(
+            values,
+            [r#"Shout {str.upper("hey")}!"#, "You have {gold coins"]
+        );
+    }
+
+    #[test]
+    fn reports_invalid_escapes_inside_the_literal() {
+        let source = "let a = \"fine\";\nlet b = \"oops \\q\";";
+        let tokens = Lexer::tokenize(source);
+        let error = tokens
+            .iter()
+            .find(|token| matches!(token.kind, TokenKind::Error(_)))
+            .expect("error token");
+
+        assert_eq!(
+            error,
+            &Token {
+                kind: TokenKind::Error(ErrorKind::InvalidEscape(r"\q".to_string())),
+                line: 2,
+                column: 15,
+                length: 2,
+                offset: 30,
+            }
+        );
+    }
+
+    #[test]
+    fn keeps_comments_out_of_the_token_stream() {
+        let source = "// héading\r\nlet a = \"// not a comment\"; // trailing\n";
+        let (tokens, comments) = Lexer::tokenize_with_comments(source);
+
+        assert_eq!(tokens, Lexer::tokenize(source));
+        assert_eq!(
+            comments,
+            vec![
+                Comment {
+                    text: " héading",
+                    line: 1,
+                    column: 1,
+                    offset: 0,
+                },
+                Comment {
+                    text: " trailing",
+                    line: 2,
+                    column: 29,
+                    offset: 41,
+                },
+            ]
+        );
+    }
+
     #[test]
     fn tokenizes_simple_story() {
         let tokens = Lexer::tokenize(fabc_reg_test::SIMPLE_STORY);
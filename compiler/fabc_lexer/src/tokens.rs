@@ -1,12 +1,19 @@
-use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::{
+    borrow::Cow,
+    fmt::{Display, Formatter, Result as FmtResult},
+};
 
-use crate::keywords::KeywordKind;
+use crate::{
+    escape::{decoded_offsets, strip_indent_mapped},
+    keywords::KeywordKind,
+};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum ErrorKind {
     UnrecognizedCharacter,
     UnterminatedString,
     InvalidNumber,
+    InvalidEscape(String),
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -42,7 +49,7 @@ pub enum TokenKind<'src> {
 
     // Literals
     Identifier(&'src str),
-    String(&'src str),
+    String(StringLiteral<'src>),
     Number(f64),
     Keyword(KeywordKind),
 
@@ -88,10 +95,84 @@ impl<'src> Display for TokenKind<'src> {
     }
 }
 
+/// How a string literal was delimited, which decides how its body is decoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StringStyle {
+    /// `"..."`, with escapes.
+    Quoted,
+    /// `"""..."""`, with escapes and shared indentation removed.
+    Triple,
+    /// `r"..."` or `r#"..."#`, taken verbatim.
+    Raw { hashes: usize },
+}
+
+impl StringStyle {
+    /// Byte length of the opening delimiter, including a raw string's `r` and `#`s.
+    pub fn opening_len(self) -> usize {
+        match self {
+            StringStyle::Quoted => 1,
+            StringStyle::Triple => 3,
+            StringStyle::Raw { hashes } => 2 + hashes,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StringLiteral<'src> {
+    /// Decoded contents, borrowed from the source when nothing needed decoding.
+    pub value: Cow<'src, str>,
+    /// The source between the delimiters, before any decoding.
+    pub raw: &'src str,
+    pub style: StringStyle,
+}
+
+impl StringLiteral<'_> {
+    /// For every byte of [`Self::value`], the offset in [`Self::raw`] it was decoded from,
+    /// followed by one entry for the end of the body. Lets callers point into the source at
+    /// a position found in the decoded text.
+    pub fn source_offsets(&self) -> Vec<usize> {
+        match self.style {
+            StringStyle::Raw { .. } => (0..=self.raw.len()).collect(),
+            StringStyle::Quoted => decoded_offsets(self.raw),
+            StringStyle::Triple => {
+                let (stripped, offsets) = strip_indent_mapped(self.raw);
+                decoded_offsets(&stripped)
+                    .into_iter()
+                    .map(|offset| offsets[offset])
+                    .collect()
+            }
+        }
+    }
+}
+
+impl Display for StringLiteral<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}", self.value)
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub struct Token<'src> {
     pub kind: TokenKind<'src>,
+    /// Line the token starts on.
     pub line: usize,
+    /// 1-based column, counted in characters rather than bytes.
     pub column: usize,
+    /// Length in characters, including any line breaks inside the token.
     pub length: usize,
+    /// Byte offset of the token's first character in the source.
+    pub offset: usize,
+}
+
+/// A `//` line comment. Comments never reach the token stream; tools that reprint source get
+/// them from [`crate::Lexer::tokenize_with_comments`].
+#[derive(Debug, PartialEq)]
+pub struct Comment<'src> {
+    /// The text after `//`, up to but excluding the line break.
+    pub text: &'src str,
+    pub line: usize,
+    /// 1-based column of the first `/`, counted in characters.
+    pub column: usize,
+    /// Byte offset of the first `/` in the source.
+    pub offset: usize,
 }
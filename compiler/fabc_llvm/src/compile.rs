@@ -1,6 +1,7 @@
 use std::{collections::BTreeMap, path::Path};
 
-use fabc_analyzer::Analyzer;
+use fabc_analyzer::{Analyzer, HostFunctionDecl, StoryGraph};
+use fabc_error::{Diagnostic, Severity};
 use fabc_parser::{ast::init::Init, Parser};
 
 use crate::{
@@ -34,24 +35,129 @@ impl StoryCompiler {
     }
 
     pub fn lower_entry(&self, entry: impl AsRef<Path>) -> Result<StoryProgram> {
-        let linked_inits = ModuleLinker::default().link_inits(entry.as_ref())?;
-        let diagnostics = Analyzer::analyze(&linked_inits).errors;
-        if !diagnostics.is_empty() {
+        self.lower_entry_with_warnings(entry)
+            .map(|(program, _)| program)
+    }
+
+    /// Like [`StoryCompiler::lower_entry`], also returning analyzer warnings. Errors carry the
+    /// warnings alongside them.
+    pub fn lower_entry_with_warnings(
+        &self,
+        entry: impl AsRef<Path>,
+    ) -> Result<(StoryProgram, Vec<Diagnostic>)> {
+        self.lower_entry_with_host_functions(entry, &[])
+    }
+
+    /// Like [`StoryCompiler::lower_entry_with_warnings`], also accepting calls to functions the
+    /// embedding application will register on the story machine.
+    pub fn lower_entry_with_host_functions(
+        &self,
+        entry: impl AsRef<Path>,
+        host_functions: &[HostFunctionDecl],
+    ) -> Result<(StoryProgram, Vec<Diagnostic>)> {
+        let (linked_inits, _, diagnostics) =
+            self.analyze_entry(ModuleLinker::default(), entry.as_ref(), host_functions)?;
+        if diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.severity == Severity::Error)
+        {
+            return Err(Error::SemanticDiagnostics { diagnostics });
+        }
+
+        Ok((self.lower_inits(linked_inits)?, diagnostics))
+    }
+
+    /// Links, parses and analyzes a story without lowering it, returning every analyzer error
+    /// and warning. Link and parse failures are still reported as `Err`.
+    pub fn check_entry(&self, entry: impl AsRef<Path>) -> Result<Vec<Diagnostic>> {
+        self.check_entry_with_host_functions(entry, &[])
+    }
+
+    /// Like [`StoryCompiler::check_entry`], also accepting calls to declared host functions.
+    pub fn check_entry_with_host_functions(
+        &self,
+        entry: impl AsRef<Path>,
+        host_functions: &[HostFunctionDecl],
+    ) -> Result<Vec<Diagnostic>> {
+        self.check_linked(ModuleLinker::default(), entry, host_functions)
+    }
+
+    /// Like [`StoryCompiler::check_entry_with_host_functions`], but links through a
+    /// caller-configured linker.
+    pub fn check_linked(
+        &self,
+        linker: ModuleLinker,
+        entry: impl AsRef<Path>,
+        host_functions: &[HostFunctionDecl],
+    ) -> Result<Vec<Diagnostic>> {
+        self.analyze_entry(linker, entry.as_ref(), host_functions)
+            .map(|(_, _, diagnostics)| diagnostics)
+    }
+
+    /// Links and analyzes `entry`, returning the part graph of the linked story along with any
+    /// warnings. Analyzer errors fail like they do for lowering.
+    pub fn graph_entry(&self, entry: impl AsRef<Path>) -> Result<(StoryGraph, Vec<Diagnostic>)> {
+        self.graph_entry_with_host_functions(entry, &[])
+    }
+
+    /// Like [`StoryCompiler::graph_entry`], also accepting calls to declared host functions.
+    pub fn graph_entry_with_host_functions(
+        &self,
+        entry: impl AsRef<Path>,
+        host_functions: &[HostFunctionDecl],
+    ) -> Result<(StoryGraph, Vec<Diagnostic>)> {
+        let (_, graph, diagnostics) =
+            self.analyze_entry(ModuleLinker::default(), entry.as_ref(), host_functions)?;
+        if diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.severity == Severity::Error)
+        {
             return Err(Error::SemanticDiagnostics { diagnostics });
         }
 
-        self.lower_inits(linked_inits)
+        Ok((graph.unwrap_or_default(), diagnostics))
+    }
+
+    fn analyze_entry(
+        &self,
+        linker: ModuleLinker,
+        entry: &Path,
+        host_functions: &[HostFunctionDecl],
+    ) -> Result<(Vec<Init>, Option<StoryGraph>, Vec<Diagnostic>)> {
+        let (linked_inits, source_map) = linker.link_inits(entry)?;
+        let analyzed = Analyzer::analyze_with_host_functions(&linked_inits, host_functions);
+        let errors = analyzed
+            .errors
+            .into_iter()
+            .map(|error| source_map.diagnostic(Severity::Error, error));
+        let warnings = analyzed
+            .warnings
+            .into_iter()
+            .map(|warning| source_map.diagnostic(Severity::Warning, warning));
+
+        Ok((
+            linked_inits,
+            analyzed.story_graph,
+            errors.chain(warnings).collect(),
+        ))
     }
 
     pub fn lower_source(&self, source: &str) -> Result<StoryProgram> {
+        let source_diagnostics = |errors: Vec<fabc_error::Error>| {
+            errors
+                .into_iter()
+                .map(|error| Diagnostic::new(Severity::Error, None, source, error))
+                .collect::<Vec<_>>()
+        };
+
         let parsed = Parser::parse_str(source);
         if !parsed.errors.is_empty() {
-            return Err(Error::Diagnostics(parsed.errors));
+            return Err(Error::Diagnostics(source_diagnostics(parsed.errors)));
         }
 
         let diagnostics = Analyzer::analyze(&parsed.result).errors;
         if !diagnostics.is_empty() {
-            return Err(Error::Diagnostics(diagnostics));
+            return Err(Error::Diagnostics(source_diagnostics(diagnostics)));
         }
 
         self.lower_inits(parsed.result)
@@ -198,14 +304,22 @@ fn emit_program_llvm_artifact_with_options(
 #[cfg(test)]
 mod tests {
     use std::{
+        collections::BTreeMap,
         env, fs,
         path::PathBuf,
         time::{SystemTime, UNIX_EPOCH},
     };
 
-    use fabc_error::kind::{CompileErrorKind, ErrorKind};
+    use fabc_error::{
+        kind::{CompileErrorKind, ErrorKind},
+        Severity,
+    };
 
     use super::{Error, StoryCompiler};
+    use crate::{
+        ir::StepSpec,
+        runtime::{RuntimeError, StoryEvent, StoryMachine, Value},
+    };
 
     #[test]
     fn lower_entry_resolves_static_imports() {
@@ -251,6 +365,71 @@ mod tests {
         assert!(program.find_part_index("branch.end").is_some());
     }
 
+    #[test]
+    fn check_entry_follows_story_identifiers_inside_imports() {
+        let root = temp_case_dir("llvm_imported_story_identifiers");
+        fs::create_dir_all(&root).expect("create temp dir");
+
+        let entry = root.join("entry.fab");
+        let imported = root.join("branch.fab");
+
+        fs::write(
+            &entry,
+            "module \"./branch.fab\" as branch;\n\nStory { start: \"intro\" }\n\n# intro\n- \"Go\" { next: () => { goto branch.start; } }\n\n# orphan\n* \"Nobody comes here\"\n",
+        )
+        .expect("write entry");
+        fs::write(
+            &imported,
+            "Story {}\n\n# start\n- \"On\" { next: () => { goto @end; } }\n\n# end\n- \"Done\" { next: () => { goto @end; } }\n",
+        )
+        .expect("write import");
+
+        let diagnostics = StoryCompiler.check_entry(&entry).expect("check story");
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].error.kind,
+            ErrorKind::Compile(CompileErrorKind::UnreachablePart {
+                part: "orphan".to_string(),
+            })
+        );
+        let program = StoryCompiler.lower_entry(&entry).expect("lower story");
+        assert!(program.find_part_index("branch.end").is_some());
+    }
+
+    #[test]
+    fn graph_entry_names_imported_parts_by_namespace() {
+        let root = temp_case_dir("llvm_graph_imported_parts");
+        fs::create_dir_all(&root).expect("create temp dir");
+
+        let entry = root.join("entry.fab");
+        fs::write(
+            &entry,
+            "module \"./branch.fab\" as branch;\n\nStory { start: \"intro\" }\n\n# intro\n- \"Go\" { next: () => { goto branch.start; } }\n",
+        )
+        .expect("write entry");
+        fs::write(
+            root.join("branch.fab"),
+            "Story {}\n\n# start\n* \"On\" { next: () => { goto @end; } }\n\n# end\n* \"Done\"\n",
+        )
+        .expect("write import");
+
+        let (graph, diagnostics) = StoryCompiler.graph_entry(&entry).expect("graph story");
+
+        assert!(diagnostics.is_empty());
+        assert!(graph.parts.contains(&"branch.end".to_string()));
+        assert!(graph.edges.iter().any(|edge| edge.from == "intro"
+            && edge.to == "branch.start"
+            && edge.label.as_deref() == Some("Go")));
+        assert!(graph
+            .edges
+            .iter()
+            .any(|edge| edge.from == "branch.start" && edge.to == "branch.end"));
+        assert_eq!(graph.endings.iter().collect::<Vec<_>>(), ["branch.end"]);
+        assert!(graph.dead_ends.is_empty());
+        assert!(graph.unreachable.is_empty());
+    }
+
     #[test]
     fn lower_source_rejects_semantic_errors() {
         let error = StoryCompiler
@@ -274,11 +453,641 @@ mod tests {
         };
 
         assert!(diagnostics.iter().any(|diagnostic| matches!(
-            diagnostic.kind,
+            diagnostic.error.kind,
             ErrorKind::Compile(CompileErrorKind::UninitializedVariable)
         )));
     }
 
+    #[test]
+    fn lower_entry_attributes_diagnostics_to_their_module() {
+        let root = temp_case_dir("llvm_module_diagnostics");
+        fs::create_dir_all(&root).expect("create temp dir");
+
+        let entry = root.join("entry.fab");
+        let imported = root.join("branch.fab");
+
+        fs::write(
+            &entry,
+            "module \"./branch.fab\" as branch;\n\nStory { start: \"intro\" }\n\n# intro\n- \"Go\" { next: () => { goto branch.end; } }\n",
+        )
+        .expect("write entry");
+        fs::write(
+            &imported,
+            "Story {}\n\n# end\n- \"Stay\" { next: () => { let x = missing; } }\n",
+        )
+        .expect("write import");
+
+        let error = StoryCompiler
+            .lower_entry(&entry)
+            .expect_err("imported semantic error should fail");
+        let diagnostics = error.diagnostics().expect("diagnostics");
+        let diagnostic = diagnostics
+            .iter()
+            .find(|diagnostic| {
+                diagnostic.error.kind == ErrorKind::Compile(CompileErrorKind::UninitializedVariable)
+            })
+            .expect("uninitialized variable diagnostic");
+
+        assert_eq!(
+            diagnostic.path.as_deref(),
+            Some(fs::canonicalize(&imported).expect("canonical").as_path())
+        );
+        assert_eq!(diagnostic.error.span.start().line(), 4);
+        assert!(diagnostic.render(false).contains("branch.fab:4:"));
+    }
+
+    #[test]
+    fn check_entry_reports_warnings_without_lowering() {
+        let root = temp_case_dir("llvm_check_warnings");
+        fs::create_dir_all(&root).expect("create temp dir");
+
+        let entry = root.join("entry.fab");
+        fs::write(
+            &entry,
+            r#"
+            Story { start: "intro" }
+
+            # intro
+            * "Hello"
+
+            # orphan
+            * "Nobody comes here"
+            "#,
+        )
+        .expect("write entry");
+
+        let diagnostics = StoryCompiler.check_entry(&entry).expect("check story");
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+        assert_eq!(
+            diagnostics[0].error.kind,
+            ErrorKind::Compile(CompileErrorKind::UnreachablePart {
+                part: "orphan".to_string(),
+            })
+        );
+        assert!(StoryCompiler.lower_entry(&entry).is_ok());
+    }
+
+    #[test]
+    fn machine_from_source_runs_loops_with_break_continue_and_goto() {
+        let mut machine = StoryCompiler
+            .machine_from_source(
+                r#"
+                Story { start: "intro" }
+
+                # intro
+                - "Count" {
+                    next: () => {
+                        let total = 0;
+                        let i = 0;
+                        while (true) {
+                            i = i + 1;
+                            if (i == 2) { continue; }
+                            if (i > 4) { break; }
+                            total = total + i;
+                        }
+                        for (let j = 0; j < 3; j = j + 1) {
+                            total = total + 10;
+                        }
+                        context.total = total;
+                        for (;;) {
+                            goto outro;
+                        }
+                    }
+                }
+
+                # outro
+                * "Done"
+                "#,
+            )
+            .expect("build machine");
+
+        machine.start().expect("start story");
+        let event = machine.choose(0).expect("run looping closure");
+
+        assert_eq!(machine.context_value("total"), Some(Value::Number(38.0)));
+        assert!(matches!(event, StoryEvent::Narration(view) if view.text == "Done"));
+    }
+
+    #[test]
+    fn machine_from_source_builds_and_indexes_lists() {
+        let mut machine = StoryCompiler
+            .machine_from_source(
+                r#"
+                Story { start: "intro" }
+
+                # intro
+                - "Pack" {
+                    next: () => {
+                        let bag = ["torch", [1, 2]];
+                        bag[1][0] = bag[1][1] + 40;
+                        context.bag = bag;
+                        context.first = context.bag[0];
+                    }
+                }
+                "#,
+            )
+            .expect("build machine");
+
+        machine.start().expect("start story");
+        machine.choose(0).expect("run list closure");
+
+        assert_eq!(
+            machine.context_value("first"),
+            Some(Value::String("torch".to_string()))
+        );
+        assert_eq!(
+            machine.context_value("bag"),
+            Some(Value::list(vec![
+                Value::String("torch".to_string()),
+                Value::list(vec![Value::Number(42.0), Value::Number(2.0)]),
+            ]))
+        );
+    }
+
+    #[test]
+    fn machine_from_source_calls_builtins() {
+        let mut machine = StoryCompiler
+            .machine_from_source(
+                r#"
+                Story { start: "intro" }
+
+                # intro
+                - "Inspect" {
+                    next: () => {
+                        let bag = ["rope"];
+                        list.push(bag, "lamp");
+                        let stats = { hp: 7.8, name: "zoë" };
+                        context.len = str.len(stats.name);
+                        context.upper = str.upper(stats.name);
+                        context.floor = num.floor(stats.hp);
+                        context.clamped = num.clamp(stats.hp, 0, 5);
+                        context.label = num.to_string(num.floor(stats.hp)) + "hp";
+                        context.bag = bag;
+                        context.has_hp = obj.has(stats, "hp");
+                        context.keys = obj.keys(stats);
+                    }
+                }
+                "#,
+            )
+            .expect("build machine");
+
+        machine.start().expect("start story");
+        machine.choose(0).expect("run builtin closure");
+
+        let string = |value: &str| Value::String(value.to_string());
+        assert_eq!(machine.context_value("len"), Some(Value::Number(3.0)));
+        assert_eq!(machine.context_value("upper"), Some(string("ZOË")));
+        assert_eq!(machine.context_value("floor"), Some(Value::Number(7.0)));
+        assert_eq!(machine.context_value("clamped"), Some(Value::Number(5.0)));
+        assert_eq!(machine.context_value("label"), Some(string("7hp")));
+        assert_eq!(
+            machine.context_value("bag"),
+            Some(Value::list(vec![string("rope"), string("lamp")]))
+        );
+        assert_eq!(machine.context_value("has_hp"), Some(Value::Boolean(true)));
+        assert_eq!(
+            machine.context_value("keys"),
+            Some(Value::list(vec![string("hp"), string("name")]))
+        );
+    }
+
+    #[test]
+    fn machine_from_source_rolls_reproducibly_with_a_seed() {
+        let source = r#"
+            Story { start: "intro" }
+
+            # intro
+            - "Roll" {
+                next: () => {
+                    context.chance = random();
+                    context.die = random_int(1, 6);
+                    context.loot = pick(["sword", "shield", "lamp"]);
+                    context.nothing = pick([]);
+                }
+            }
+            "#;
+        let roll = || {
+            let mut machine = StoryCompiler
+                .machine_from_source(source)
+                .expect("build machine");
+            machine.set_seed(2024);
+            machine.start().expect("start story");
+            machine.choose(0).expect("run random closure");
+            machine.context_snapshot()
+        };
+
+        let context = roll();
+        assert_eq!(context, roll());
+        assert!(matches!(context["chance"], Value::Number(value) if (0.0..1.0).contains(&value)));
+        assert!(matches!(context["die"], Value::Number(value) if (1.0..=6.0).contains(&value)));
+        assert!(matches!(&context["loot"], Value::String(_)));
+        assert_eq!(context["nothing"], Value::None);
+    }
+
+    #[test]
+    fn machine_from_source_tracks_visits_and_seen_quotes() {
+        let source = r#"
+            Story { start: "hub" }
+
+            # hub
+            * "Visit {visits(@hub)}."
+            - "Ask about the key" {
+                when: () => !seen(),
+                next: () => { goto hub; }
+            }
+            - "Leave" { next: () => { goto outside; } }
+
+            # outside
+            * "Outside."
+            "#;
+        let mut machine = StoryCompiler
+            .machine_from_source(source)
+            .expect("build machine");
+        let StepSpec::Selection(selection) = &machine.program().parts[0].steps[1] else {
+            panic!("expected a selection");
+        };
+        let ask = selection.choices[0].node_id;
+
+        assert!(matches!(
+            machine.start().expect("start story"),
+            StoryEvent::Narration(view) if view.text == "Visit 1."
+        ));
+        machine.advance().expect("show choices");
+        assert!(!machine.has_seen(ask));
+        let event = machine.choose(0).expect("ask about the key");
+        assert!(matches!(event, StoryEvent::Narration(view) if view.text == "Visit 2."));
+        assert!(machine.has_seen(ask));
+        assert_eq!(machine.visits("hub"), 2);
+        assert_eq!(machine.visits("outside"), 0);
+
+        let snapshot = machine.snapshot().expect("snapshot");
+        let mut machine =
+            StoryMachine::restore(machine.program().clone(), &snapshot).expect("restore machine");
+        let StoryEvent::Selection(selection) = machine.advance().expect("show choices") else {
+            panic!("expected a selection");
+        };
+        assert_eq!(selection.choices.len(), 1);
+        assert_eq!(selection.choices[0].text, "Leave");
+        assert_eq!(machine.visits("hub"), 2);
+    }
+
+    #[test]
+    fn machine_from_source_interpolates_quote_text() {
+        let source = r#"
+            Story { start: "intro" }
+
+            # intro
+            [Guide]
+            > "You carry {context.gold} coins, {{friend}}."
+                - "Spend {context.gold - 2}" {
+                    next: () => {
+                        context.gold = context.gold - 2;
+                    }
+                }
+            * "Now {context.gold} remain, {num.floor(context.gold / 2)} each."
+            "#;
+        let mut context = BTreeMap::new();
+        context.insert("gold".to_string(), Value::Number(5.0));
+        let program = StoryCompiler.lower_source(source).expect("lower story");
+        let mut machine = StoryMachine::with_context(program, context).expect("build machine");
+
+        let event = machine.start().expect("start story");
+        assert!(
+            matches!(event, StoryEvent::Dialogue(view) if view.text == "You carry 5 coins, {friend}.")
+        );
+        let StoryEvent::Selection(selection) = machine.advance().expect("advance") else {
+            panic!("expected a selection");
+        };
+        assert_eq!(selection.choices[0].text, "Spend 3");
+        let event = machine.choose(0).expect("choose");
+        assert!(
+            matches!(event, StoryEvent::Narration(view) if view.text == "Now 3 remain, 1 each.")
+        );
+    }
+
+    #[test]
+    fn machine_from_source_renders_raw_quotes_verbatim() {
+        let source = r##"
+            Story { start: "intro" }
+
+            # intro
+            * r"literal {braces} here"
+            * r#"still {{literal}} "here""#
+            "##;
+        let program = StoryCompiler.lower_source(source).expect("lower story");
+        let mut machine = StoryMachine::new(program).expect("build machine");
+
+        let event = machine.start().expect("start story");
+        assert!(
+            matches!(event, StoryEvent::Narration(view) if view.text == "literal {braces} here")
+        );
+        let event = machine.advance().expect("advance");
+        assert!(
+            matches!(event, StoryEvent::Narration(view) if view.text == r#"still {{literal}} "here""#)
+        );
+    }
+
+    #[test]
+    fn machine_from_source_filters_and_disables_guarded_choices() {
+        let source = r#"
+            Story { start: "door" }
+
+            # door
+            - "Use the key" {
+                when: () => context.has_key,
+                next: () => { goto inside; }
+            }
+            - "Knock" {
+                if: () => context.knocks < 1,
+                show_disabled: true,
+                next: () => {
+                    context.knocks = context.knocks + 1;
+                    goto door;
+                }
+            }
+            - "Leave" { next: () => { goto outside; } }
+
+            # inside
+            * "Inside."
+
+            # outside
+            * "Outside."
+            "#;
+        let mut context = BTreeMap::new();
+        context.insert("has_key".to_string(), Value::Boolean(false));
+        context.insert("knocks".to_string(), Value::Number(0.0));
+        let program = StoryCompiler.lower_source(source).expect("lower story");
+        let mut machine = StoryMachine::with_context(program, context).expect("build machine");
+
+        let choices = |event: StoryEvent| match event {
+            StoryEvent::Selection(selection) => selection
+                .choices
+                .into_iter()
+                .map(|choice| (choice.text, choice.enabled))
+                .collect::<Vec<_>>(),
+            other => panic!("expected a selection, got {other:?}"),
+        };
+
+        let event = machine.start().expect("start story");
+        assert_eq!(
+            choices(event),
+            vec![("Knock".to_string(), true), ("Leave".to_string(), true)]
+        );
+
+        let event = machine.choose(0).expect("knock");
+        assert_eq!(
+            choices(event),
+            vec![("Knock".to_string(), false), ("Leave".to_string(), true)]
+        );
+        assert!(matches!(
+            machine.choose(0),
+            Err(RuntimeError::DisabledChoice { index: 0 })
+        ));
+
+        let event = machine.choose(1).expect("leave");
+        assert!(matches!(event, StoryEvent::Narration(view) if view.text == "Outside."));
+    }
+
+    #[test]
+    fn machine_from_source_chooses_from_the_choices_it_rendered() {
+        let source = r#"
+            Story { start: "door" }
+
+            # door
+            - "Peek" {
+                when: () => {
+                    context.checks = context.checks + 1;
+                    return context.checks == 1;
+                },
+                next: () => { goto inside; }
+            }
+            - "Leave" { next: () => { goto outside; } }
+
+            # inside
+            * "Inside."
+
+            # outside
+            * "Outside."
+            "#;
+        let mut context = BTreeMap::new();
+        context.insert("checks".to_string(), Value::Number(0.0));
+        let program = StoryCompiler.lower_source(source).expect("lower story");
+        let mut machine = StoryMachine::with_context(program, context).expect("build machine");
+
+        let event = machine.start().expect("start story");
+        assert!(matches!(event, StoryEvent::Selection(view) if view.choices.len() == 2));
+
+        let event = machine.choose(1).expect("leave");
+        assert!(matches!(event, StoryEvent::Narration(view) if view.text == "Outside."));
+        assert_eq!(machine.context_value("checks"), Some(Value::Number(1.0)));
+    }
+
+    #[test]
+    fn machine_from_source_retires_once_choices_and_takes_fallbacks() {
+        let source = r#"
+            Story { start: "interrogation" }
+
+            # interrogation
+            - "Where were you?" { once: true, next: () => { goto interrogation; } }
+            - "Who saw you?" { once: true, next: () => { goto interrogation; } }
+            - "That will be all." {
+                fallback: true,
+                next: () => { goto hallway; }
+            }
+
+            # hallway
+            * "You leave the room."
+            "#;
+        let program = StoryCompiler.lower_source(source).expect("lower story");
+        let mut machine = StoryMachine::new(program).expect("build machine");
+
+        let texts = |event: StoryEvent| match event {
+            StoryEvent::Selection(selection) => selection
+                .choices
+                .into_iter()
+                .map(|choice| choice.text)
+                .collect::<Vec<_>>(),
+            other => panic!("expected a selection, got {other:?}"),
+        };
+
+        let event = machine.start().expect("start story");
+        assert_eq!(texts(event), ["Where were you?", "Who saw you?"]);
+        let event = machine.choose(0).expect("ask where");
+        assert_eq!(texts(event), ["Who saw you?"]);
+
+        let snapshot = machine.snapshot().expect("snapshot");
+        let mut machine =
+            StoryMachine::restore(machine.program().clone(), &snapshot).expect("restore machine");
+        assert_eq!(texts(machine.current().expect("render")), ["Who saw you?"]);
+
+        let event = machine.choose(0).expect("ask who");
+        assert!(matches!(event, StoryEvent::Narration(view) if view.text == "You leave the room."));
+        assert_eq!(machine.current_part(), Some("hallway"));
+    }
+
+    #[test]
+    fn machine_from_source_fails_when_a_fallback_loops_back_to_its_selection() {
+        let source = r#"
+            Story { start: "hub" }
+
+            # hub
+            - "Ask" { once: true, next: () => { goto hub; } }
+            - "Wait" { fallback: true, next: () => { goto hub; } }
+            "#;
+        let program = StoryCompiler.lower_source(source).expect("lower story");
+        let mut machine = StoryMachine::new(program).expect("build machine");
+
+        machine.start().expect("start story");
+        assert_eq!(
+            machine.choose(0),
+            Err(RuntimeError::FallbackLoop {
+                part: "hub".to_string()
+            })
+        );
+
+        let exhausted = machine
+            .current()
+            .expect("render without taking the fallback");
+        assert!(matches!(&exhausted, StoryEvent::Selection(view) if view.choices.is_empty()));
+        assert_eq!(machine.current(), Ok(exhausted));
+    }
+
+    #[test]
+    fn lower_source_rejects_misplaced_or_non_literal_choice_modifiers() {
+        let outside_selection = r#"
+            Story { start: "intro" }
+
+            # intro
+            * "Hello" { once: true }
+            "#;
+        let not_a_literal = r#"
+            Story { start: "intro" }
+
+            # intro
+            - "Hello" { fallback: context.fallback }
+            "#;
+
+        assert!(matches!(
+            StoryCompiler.lower_source(outside_selection),
+            Err(Error::ChoiceModifierOutsideSelection)
+        ));
+        assert!(matches!(
+            StoryCompiler.lower_source(not_a_literal),
+            Err(Error::InvalidChoiceModifier)
+        ));
+    }
+
+    #[test]
+    fn machine_from_source_runs_part_hooks_and_reports_part_properties() {
+        let source = r#"
+            Story { start: "street" }
+
+            # street => { on_exit: () => { context.log = context.log + "left street;"; } }
+            - "Enter the alley" { next: () => { goto alley; } }
+
+            # alley => {
+                background: "bg_alley",
+                on_enter: () => { context.log = context.log + "entered alley;"; }
+            }
+            * "The alley."
+            "#;
+        let context = BTreeMap::from([("log".to_string(), Value::String(String::new()))]);
+        let program = StoryCompiler.lower_source(source).expect("lower story");
+        let mut machine = StoryMachine::with_context(program, context).expect("build machine");
+
+        assert!(matches!(
+            machine.start().expect("start story"),
+            StoryEvent::Selection(_)
+        ));
+        assert_eq!(
+            machine.choose(0).expect("enter the alley"),
+            StoryEvent::PartEntered {
+                id: "alley".to_string(),
+                properties: BTreeMap::from([(
+                    "background".to_string(),
+                    Value::String("bg_alley".to_string())
+                )]),
+            }
+        );
+        assert_eq!(
+            machine.context_value("log"),
+            Some(Value::String("left street;entered alley;".to_string()))
+        );
+        assert!(matches!(
+            machine.advance().expect("show the alley"),
+            StoryEvent::Narration(view) if view.text == "The alley."
+        ));
+    }
+
+    #[test]
+    fn machine_from_source_reenters_a_part_that_goes_to_itself() {
+        let source = r#"
+            Story { start: "hub" }
+
+            # hub => {
+                on_enter: () => { context.log = context.log + "enter;"; },
+                on_exit: () => { context.log = context.log + "exit;"; }
+            }
+            - "Wait" { next: () => { goto hub; } }
+            - "Leave" { next: () => { goto outside; } }
+
+            # outside
+            * "Gone."
+            "#;
+        let context = BTreeMap::from([("log".to_string(), Value::String(String::new()))]);
+        let program = StoryCompiler.lower_source(source).expect("lower story");
+        let mut machine = StoryMachine::with_context(program, context).expect("build machine");
+
+        machine.start().expect("start story");
+        assert!(matches!(
+            machine.choose(0).expect("wait in the hub"),
+            StoryEvent::Selection(_)
+        ));
+        assert_eq!(machine.visits("hub"), 2);
+        assert_eq!(
+            machine.context_value("log"),
+            Some(Value::String("enter;exit;enter;".to_string()))
+        );
+
+        machine.choose(1).expect("leave the hub");
+        assert_eq!(
+            machine.context_value("log"),
+            Some(Value::String("enter;exit;enter;exit;".to_string()))
+        );
+    }
+
+    #[test]
+    fn lower_source_rejects_part_hooks_that_are_not_closures() {
+        let source = r#"
+            Story { start: "intro" }
+
+            # intro => { on_enter: "not a closure" }
+            * "Hello"
+            "#;
+
+        assert!(matches!(
+            StoryCompiler.lower_source(source),
+            Err(Error::InvalidPartHook)
+        ));
+    }
+
+    #[test]
+    fn lower_source_rejects_guards_outside_selections() {
+        let source = r#"
+            Story { start: "intro" }
+
+            # intro
+            * "Hello" { when: () => true }
+            "#;
+
+        assert!(matches!(
+            StoryCompiler.lower_source(source),
+            Err(Error::GuardOutsideSelection)
+        ));
+    }
+
     fn temp_case_dir(name: &str) -> PathBuf {
         let nonce = SystemTime::now()
             .duration_since(UNIX_EPOCH)
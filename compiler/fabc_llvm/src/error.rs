@@ -1,24 +1,25 @@
 use std::{io, path::PathBuf, result::Result as StdResult};
 
+use fabc_error::Diagnostic;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
 pub enum Error {
-    #[error("compiler emitted diagnostics: {0:?}")]
-    Diagnostics(Vec<fabc_error::Error>),
+    #[error("compiler emitted diagnostics:\n{}", Diagnostic::render_all(.0, false))]
+    Diagnostics(Vec<Diagnostic>),
     #[error("failed to read `{path}`: {source}")]
     Io {
         path: PathBuf,
         #[source]
         source: io::Error,
     },
-    #[error("parser reported diagnostics for `{path}`: {diagnostics:?}")]
+    #[error("parser reported diagnostics for `{path}`:\n{}", Diagnostic::render_all(.diagnostics, false))]
     ParseDiagnostics {
         path: PathBuf,
-        diagnostics: Vec<fabc_error::Error>,
+        diagnostics: Vec<Diagnostic>,
     },
-    #[error("semantic analysis reported diagnostics: {diagnostics:?}")]
-    SemanticDiagnostics { diagnostics: Vec<fabc_error::Error> },
+    #[error("semantic analysis reported diagnostics:\n{}", Diagnostic::render_all(.diagnostics, false))]
+    SemanticDiagnostics { diagnostics: Vec<Diagnostic> },
     #[error("`{path}` does not contain a story")]
     MissingStory { path: PathBuf },
     #[error("`{path}` contains multiple story blocks: {count}")]
@@ -51,6 +52,16 @@ pub enum Error {
     UnsupportedModuleImport(String),
     #[error("`next` properties must be closures")]
     InvalidNextHandler,
+    #[error("`when`/`if` guards must be closures and `show_disabled` a boolean literal")]
+    InvalidChoiceGuard,
+    #[error("`when`/`if` guards are only allowed on selection choices")]
+    GuardOutsideSelection,
+    #[error("`once`/`fallback` choice modifiers must be boolean literals")]
+    InvalidChoiceModifier,
+    #[error("`once`/`fallback` modifiers are only allowed on selection choices")]
+    ChoiceModifierOutsideSelection,
+    #[error("`on_enter`/`on_exit` part properties must be closures")]
+    InvalidPartHook,
     #[error("closure parameters must be identifiers")]
     InvalidClosureParameter,
     #[error("runtime initialization failed: {0}")]
@@ -70,3 +81,15 @@ pub enum Error {
 }
 
 pub type Result<T> = StdResult<T, Error>;
+
+impl Error {
+    /// Source diagnostics carried by this error, if it failed on the story text itself.
+    pub fn diagnostics(&self) -> Option<&[Diagnostic]> {
+        match self {
+            Error::Diagnostics(diagnostics)
+            | Error::ParseDiagnostics { diagnostics, .. }
+            | Error::SemanticDiagnostics { diagnostics } => Some(diagnostics),
+            _ => None,
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use std::collections::BTreeMap;
 
 use fabc_parser::ast::{
-    decl::quote::QuoteDecl,
+    decl::quote::{QuoteDecl, TextSegment as ParserTextSegment},
     expr::{
         literal::Literal as ParserLiteral, primitive::Primitive,
         BinaryOperator as ParserBinaryOperator, Expr as ParserExpr, Primary,
@@ -15,16 +15,17 @@ use fabc_parser::ast::{
         Init,
     },
     stmt::{
-        block::BlockStmt, expr::ExprStmt, goto::GotoStmt, r#if::ElseClause, r#if::IfStmt,
-        r#let::LetStmt, r#return::ReturnStmt, Stmt as ParserStmt,
+        block::BlockStmt, expr::ExprStmt, goto::GotoStmt, r#for::ForStmt, r#if::ElseClause,
+        r#if::IfStmt, r#let::LetStmt, r#return::ReturnStmt, r#while::WhileStmt, Stmt as ParserStmt,
     },
 };
 
 use crate::{
     error::{Error, Result},
     ir::{
-        BinaryOperator, Block, DialogueSpec, Expr, FunctionSpec, Literal, MemberSegment, PartSpec,
-        QuoteSpec, SelectionSpec, StepSpec, Stmt, StoryProgram, UnaryOperator,
+        BinaryOperator, Block, ChoiceGuard, DialogueSpec, DirectiveSpec, Expr, FunctionSpec,
+        Literal, MemberSegment, PartSpec, QuoteSpec, SelectionSpec, StepSpec, Stmt, StoryProgram,
+        TextSegment, UnaryOperator,
     },
 };
 
@@ -105,13 +106,15 @@ impl Lowerer {
         for element in &part.elements {
             match element {
                 StoryElement::Narration(narration) => {
-                    steps.push(StepSpec::Narration(self.lower_quote(&narration.quote)?));
+                    steps.push(StepSpec::Narration(
+                        self.lower_unguarded_quote(&narration.quote)?,
+                    ));
                 }
                 StoryElement::Dialogue(dialogue) => {
                     for quote in &dialogue.quotes {
                         steps.push(StepSpec::Dialogue(DialogueSpec {
                             speaker: dialogue.speaker.clone(),
-                            quote: self.lower_quote(quote)?,
+                            quote: self.lower_unguarded_quote(quote)?,
                         }));
                     }
                 }
@@ -123,11 +126,44 @@ impl Lowerer {
 
                     steps.push(StepSpec::Selection(SelectionSpec { choices }));
                 }
+                StoryElement::Directive(directive) => {
+                    let mut args = Vec::with_capacity(directive.arguments.len());
+                    for argument in &directive.arguments {
+                        args.push(self.lower_expr(argument)?);
+                    }
+
+                    steps.push(StepSpec::Directive(DirectiveSpec {
+                        node_id: directive.info.id,
+                        name: directive.name.clone(),
+                        args,
+                    }));
+                }
+            }
+        }
+
+        let mut properties = BTreeMap::new();
+        let mut on_enter = None;
+        let mut on_exit = None;
+
+        if let Some(object) = &part.properties {
+            for (key, value) in &object.map {
+                let lowered = self.lower_expr(value)?;
+                match (key.as_str(), lowered) {
+                    ("on_enter", Expr::Closure(function_id)) => on_enter = Some(function_id),
+                    ("on_exit", Expr::Closure(function_id)) => on_exit = Some(function_id),
+                    ("on_enter" | "on_exit", _) => return Err(Error::InvalidPartHook),
+                    (_, lowered) => {
+                        properties.insert(key.clone(), lowered);
+                    }
+                }
             }
         }
 
         Ok(PartSpec {
             id: part.ident.clone(),
+            properties,
+            on_enter,
+            on_exit,
             steps,
         })
     }
@@ -135,29 +171,106 @@ impl Lowerer {
     fn lower_quote(&mut self, quote: &QuoteDecl) -> Result<QuoteSpec> {
         let mut properties = BTreeMap::new();
         let mut next_action = None;
+        let mut condition = None;
+        let mut show_disabled = None;
+        let mut once = false;
+        let mut fallback = false;
 
         if let Some(object) = &quote.properties {
             for (key, value) in &object.map {
                 let lowered = self.lower_expr(value)?;
-                if key == "next" {
-                    match lowered {
-                        Expr::Closure(function_id) => next_action = Some(function_id),
-                        _ => return Err(Error::InvalidNextHandler),
+                match (key.as_str(), lowered) {
+                    ("next", Expr::Closure(function_id)) => next_action = Some(function_id),
+                    ("next", _) => return Err(Error::InvalidNextHandler),
+                    ("when" | "if", Expr::Closure(function_id)) if condition.is_none() => {
+                        condition = Some(function_id)
+                    }
+                    ("when" | "if", _) => return Err(Error::InvalidChoiceGuard),
+                    ("show_disabled", Expr::Literal(Literal::Boolean(value))) => {
+                        show_disabled = Some(value)
+                    }
+                    ("show_disabled", _) => return Err(Error::InvalidChoiceGuard),
+                    ("once", Expr::Literal(Literal::Boolean(value))) => once = value,
+                    ("fallback", Expr::Literal(Literal::Boolean(value))) => fallback = value,
+                    ("once" | "fallback", _) => return Err(Error::InvalidChoiceModifier),
+                    (_, lowered) => {
+                        properties.insert(key.clone(), lowered);
                     }
-                } else {
-                    properties.insert(key.clone(), lowered);
                 }
             }
         }
 
+        let guard = match (condition, show_disabled) {
+            (Some(condition), show_disabled) => Some(ChoiceGuard {
+                condition,
+                show_disabled: show_disabled.unwrap_or(false),
+            }),
+            (None, Some(_)) => return Err(Error::InvalidChoiceGuard),
+            (None, None) => None,
+        };
+        let (text, segments) = self.lower_quote_text(quote)?;
+
         Ok(QuoteSpec {
             node_id: quote.info.id,
-            text: quote.text.clone(),
+            text,
+            segments,
             properties,
             next_action,
+            guard,
+            once,
+            fallback,
         })
     }
 
+    fn lower_unguarded_quote(&mut self, quote: &QuoteDecl) -> Result<QuoteSpec> {
+        let quote = self.lower_quote(quote)?;
+        if quote.guard.is_some() {
+            return Err(Error::GuardOutsideSelection);
+        }
+        if quote.once || quote.fallback {
+            return Err(Error::ChoiceModifierOutsideSelection);
+        }
+        Ok(quote)
+    }
+
+    // Each interpolation becomes a zero-argument function returning its value, so native
+    // builds evaluate quote text the same way they evaluate `next` handlers.
+    fn lower_quote_text(&mut self, quote: &QuoteDecl) -> Result<(String, Vec<TextSegment>)> {
+        if !quote.is_interpolated() {
+            let text = quote
+                .segments
+                .iter()
+                .map(|segment| match segment {
+                    ParserTextSegment::Text(text) => text.as_str(),
+                    ParserTextSegment::Expr(_) => "",
+                })
+                .collect();
+            return Ok((text, Vec::new()));
+        }
+
+        let mut segments = Vec::with_capacity(quote.segments.len());
+        for segment in &quote.segments {
+            segments.push(match segment {
+                ParserTextSegment::Text(text) => TextSegment::Text(text.clone()),
+                ParserTextSegment::Expr(expr) => {
+                    let value = self.lower_expr(expr)?;
+                    let function_id = self.functions.len();
+                    self.functions.push(FunctionSpec {
+                        id: function_id,
+                        node_id: expr.info().id,
+                        params: Vec::new(),
+                        body: Block {
+                            statements: vec![Stmt::Return(Some(value))],
+                        },
+                    });
+                    TextSegment::Interpolation(function_id)
+                }
+            });
+        }
+
+        Ok((quote.text.clone(), segments))
+    }
+
     fn lower_object_map(
         &mut self,
         map: &BTreeMap<String, ParserExpr>,
@@ -197,6 +310,35 @@ impl Lowerer {
                     .map(|expr| self.lower_expr(expr))
                     .transpose()?,
             ),
+            ParserStmt::While(WhileStmt {
+                condition, body, ..
+            }) => Stmt::While {
+                condition: self.lower_expr(condition)?,
+                body: self.lower_block(body)?,
+            },
+            ParserStmt::For(ForStmt {
+                initializer,
+                condition,
+                increment,
+                body,
+                ..
+            }) => Stmt::For {
+                initializer: initializer
+                    .as_ref()
+                    .map(|stmt| self.lower_stmt(stmt).map(Box::new))
+                    .transpose()?,
+                condition: condition
+                    .as_ref()
+                    .map(|expr| self.lower_expr(expr))
+                    .transpose()?,
+                increment: increment
+                    .as_ref()
+                    .map(|expr| self.lower_expr(expr))
+                    .transpose()?,
+                body: self.lower_block(body)?,
+            },
+            ParserStmt::Break(_) => Stmt::Break,
+            ParserStmt::Continue(_) => Stmt::Continue,
         })
     }
 
@@ -310,6 +452,12 @@ impl Lowerer {
             Primary::Primitive(Primitive::Object { value, .. }) => {
                 Expr::Object(self.lower_object_map(&value.map)?)
             }
+            Primary::Primitive(Primitive::List { elements, .. }) => Expr::List(
+                elements
+                    .iter()
+                    .map(|element| self.lower_expr(element))
+                    .collect::<Result<_>>()?,
+            ),
             Primary::Primitive(Primitive::Closure { info, params, body }) => {
                 let mut lowered_params = Vec::with_capacity(params.len());
                 for param in params {
@@ -1,4 +1,5 @@
 pub use fabc_ir::{
-    BinaryOperator, Block, DialogueSpec, Expr, FunctionId, FunctionSpec, Literal, MemberSegment,
-    PartSpec, QuoteSpec, SelectionSpec, StepSpec, Stmt, StoryProgram, UnaryOperator,
+    BinaryOperator, Block, ChoiceGuard, DialogueSpec, DirectiveSpec, Expr, FunctionId,
+    FunctionSpec, Literal, MemberSegment, PartSpec, QuoteSpec, SelectionSpec, StepSpec, Stmt,
+    StoryProgram, TextSegment, UnaryOperator,
 };
@@ -2,8 +2,9 @@ pub mod compile;
 pub mod error;
 pub mod frontend;
 pub mod ir;
-mod link;
+pub mod link;
 pub mod runtime;
+mod source_map;
 
 #[cfg(feature = "llvm-backend")]
 pub mod llvm;
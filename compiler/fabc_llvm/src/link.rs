@@ -6,7 +6,10 @@ use std::{
 
 use fabc_parser::{
     ast::{
-        decl::{object::ObjectDecl, quote::QuoteDecl},
+        decl::{
+            object::ObjectDecl,
+            quote::{QuoteDecl, TextSegment},
+        },
         expr::{literal::Literal, primitive::Primitive, Expr, Primary},
         init::{
             module::ModuleInit,
@@ -14,8 +17,8 @@ use fabc_parser::{
                 metadata::Metadata,
                 part::{
                     element::{
-                        dialogue::DialogueElement, narration::NarrationElement,
-                        selection::SelectionElement, Element,
+                        dialogue::DialogueElement, directive::DirectiveElement,
+                        narration::NarrationElement, selection::SelectionElement, Element,
                     },
                     Part,
                 },
@@ -27,9 +30,11 @@ use fabc_parser::{
             block::BlockStmt,
             expr::ExprStmt,
             goto::GotoStmt,
+            r#for::ForStmt,
             r#if::{ElseClause, IfStmt},
             r#let::LetStmt,
             r#return::ReturnStmt,
+            r#while::WhileStmt,
             Stmt,
         },
         NodeInfo,
@@ -37,7 +42,12 @@ use fabc_parser::{
     Parser,
 };
 
-use crate::error::{Error, Result};
+use fabc_error::Severity;
+
+use crate::{
+    error::{Error, Result},
+    source_map::SourceMap,
+};
 
 #[derive(Debug, Clone, PartialEq)]
 enum ExportValue {
@@ -48,9 +58,13 @@ enum ExportValue {
     None,
 }
 
+/// Resolves `module` imports from an entry file and merges every linked story into one, with
+/// imported parts namespaced by their module alias.
 #[derive(Default)]
-pub(crate) struct ModuleLinker {
+pub struct ModuleLinker {
     part_origins: BTreeMap<String, PathBuf>,
+    source_map: SourceMap,
+    overlay: BTreeMap<PathBuf, String>,
 }
 
 struct LinkedStory {
@@ -69,15 +83,30 @@ struct ExportContext<'a> {
 }
 
 impl ModuleLinker {
-    pub(crate) fn link_inits(mut self, entry: &Path) -> Result<Vec<Init>> {
+    /// A linker that reads the given sources instead of the files on disk, such as unsaved
+    /// editor buffers.
+    pub fn with_overlay(overlay: BTreeMap<PathBuf, String>) -> Self {
+        Self {
+            overlay: overlay
+                .into_iter()
+                .map(|(path, source)| (fs::canonicalize(&path).unwrap_or(path), source))
+                .collect(),
+            ..Self::default()
+        }
+    }
+
+    pub(crate) fn link_inits(mut self, entry: &Path) -> Result<(Vec<Init>, SourceMap)> {
         let mut stack = Vec::new();
         let linked = self.load_file(entry, None, true, &mut stack)?;
 
-        Ok(vec![Init::Story(StoryInit {
-            info: linked.info,
-            metadata: linked.metadata,
-            parts: linked.parts,
-        })])
+        Ok((
+            vec![Init::Story(StoryInit {
+                info: linked.info,
+                metadata: linked.metadata,
+                parts: linked.parts,
+            })],
+            self.source_map,
+        ))
     }
 
     fn load_file(
@@ -87,10 +116,16 @@ impl ModuleLinker {
         keep_metadata: bool,
         stack: &mut Vec<PathBuf>,
     ) -> Result<LinkedStory> {
-        let canonical = fs::canonicalize(path).map_err(|source| Error::Io {
-            path: path.to_path_buf(),
-            source,
-        })?;
+        let canonical = match fs::canonicalize(path) {
+            Ok(canonical) => canonical,
+            Err(_) if self.overlay.contains_key(path) => path.to_path_buf(),
+            Err(source) => {
+                return Err(Error::Io {
+                    path: path.to_path_buf(),
+                    source,
+                })
+            }
+        };
 
         if let Some(start) = stack.iter().position(|current| current == &canonical) {
             let mut chain = stack[start..].to_vec();
@@ -98,16 +133,24 @@ impl ModuleLinker {
             return Err(Error::CircularImport { chain });
         }
 
-        let source = fs::read_to_string(&canonical).map_err(|source| Error::Io {
-            path: canonical.clone(),
-            source,
-        })?;
+        let source = match self.overlay.get(&canonical) {
+            Some(source) => source.clone(),
+            None => fs::read_to_string(&canonical).map_err(|source| Error::Io {
+                path: canonical.clone(),
+                source,
+            })?,
+        };
 
-        let parsed = Parser::parse_str(&source);
+        let tokens = self.source_map.tokenize(&canonical, &source);
+        let parsed = Parser::parse(&tokens);
         if !parsed.errors.is_empty() {
             return Err(Error::ParseDiagnostics {
                 path: canonical.clone(),
-                diagnostics: parsed.errors,
+                diagnostics: parsed
+                    .errors
+                    .into_iter()
+                    .map(|error| self.source_map.diagnostic(Severity::Error, error))
+                    .collect(),
             });
         }
 
@@ -278,6 +321,12 @@ impl ModuleLinker {
         aliases: &BTreeMap<String, String>,
         imported_exports: &BTreeMap<String, BTreeMap<String, ExportValue>>,
     ) {
+        if let Some(properties) = part.properties.as_mut() {
+            for value in properties.map.values_mut() {
+                self.rewrite_expr(value, namespace, local_parts, aliases, imported_exports);
+            }
+        }
+
         for element in &mut part.elements {
             match element {
                 Element::Dialogue(DialogueElement { quotes, .. }) => {
@@ -305,6 +354,17 @@ impl ModuleLinker {
                         );
                     }
                 }
+                Element::Directive(DirectiveElement { arguments, .. }) => {
+                    for argument in arguments {
+                        self.rewrite_expr(
+                            argument,
+                            namespace,
+                            local_parts,
+                            aliases,
+                            imported_exports,
+                        );
+                    }
+                }
             }
         }
     }
@@ -317,6 +377,12 @@ impl ModuleLinker {
         aliases: &BTreeMap<String, String>,
         imported_exports: &BTreeMap<String, BTreeMap<String, ExportValue>>,
     ) {
+        for segment in &mut quote.segments {
+            if let TextSegment::Expr(expr) = segment {
+                self.rewrite_expr(expr, namespace, local_parts, aliases, imported_exports);
+            }
+        }
+
         let Some(properties) = quote.properties.as_mut() else {
             return;
         };
@@ -380,6 +446,34 @@ impl ModuleLinker {
                     self.rewrite_expr(value, namespace, local_parts, aliases, imported_exports);
                 }
             }
+            Stmt::While(WhileStmt {
+                condition, body, ..
+            }) => {
+                self.rewrite_expr(condition, namespace, local_parts, aliases, imported_exports);
+                self.rewrite_block(body, namespace, local_parts, aliases, imported_exports);
+            }
+            Stmt::For(ForStmt {
+                initializer,
+                condition,
+                increment,
+                body,
+                ..
+            }) => {
+                if let Some(initializer) = initializer {
+                    self.rewrite_stmt(
+                        initializer,
+                        namespace,
+                        local_parts,
+                        aliases,
+                        imported_exports,
+                    );
+                }
+                for expr in [condition, increment].into_iter().flatten() {
+                    self.rewrite_expr(expr, namespace, local_parts, aliases, imported_exports);
+                }
+                self.rewrite_block(body, namespace, local_parts, aliases, imported_exports);
+            }
+            Stmt::Break(_) | Stmt::Continue(_) => {}
         }
     }
 
@@ -472,11 +566,24 @@ impl ModuleLinker {
                 Primary::Primitive(Primitive::Object { value, .. }) => {
                     self.rewrite_object(value, namespace, local_parts, aliases, imported_exports);
                 }
+                Primary::Primitive(Primitive::List { elements, .. }) => {
+                    for element in elements {
+                        self.rewrite_expr(
+                            element,
+                            namespace,
+                            local_parts,
+                            aliases,
+                            imported_exports,
+                        );
+                    }
+                }
                 Primary::Primitive(Primitive::Closure { body, .. }) => {
                     self.rewrite_block(body, namespace, local_parts, aliases, imported_exports);
                 }
+                Primary::Primitive(Primitive::StoryIdentifier { name, .. }) => {
+                    *name = qualify_story_target(namespace, local_parts, name);
+                }
                 Primary::Primitive(Primitive::Identifier { .. })
-                | Primary::Primitive(Primitive::StoryIdentifier { .. })
                 | Primary::Primitive(Primitive::Context { .. }) => {}
             },
             Expr::Grouping { expression, .. } => {
@@ -675,7 +782,7 @@ fn resolve_story_target(
         Expr::Primary {
             value: Primary::Primitive(Primitive::StoryIdentifier { name, .. }),
             ..
-        } => Some(name.clone()),
+        } => Some(qualify_story_target(namespace, local_parts, name)),
         Expr::Primary {
             value: Primary::Primitive(Primitive::Identifier { name, .. }),
             ..
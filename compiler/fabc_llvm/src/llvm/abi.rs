@@ -24,6 +24,8 @@ pub struct RuntimeAbi<'ctx> {
     pub context_value: FunctionValue<'ctx>,
     pub object_new: FunctionValue<'ctx>,
     pub object_insert: FunctionValue<'ctx>,
+    pub list_new: FunctionValue<'ctx>,
+    pub list_push: FunctionValue<'ctx>,
     pub env_child: FunctionValue<'ctx>,
     pub env_load: FunctionValue<'ctx>,
     pub env_define: FunctionValue<'ctx>,
@@ -114,6 +116,13 @@ impl<'ctx> RuntimeAbi<'ctx> {
             ),
             None,
         );
+        let list_new =
+            module.add_function("fabc_rt_list_new", value_ptr_type.fn_type(&[], false), None);
+        let list_push = module.add_function(
+            "fabc_rt_list_push",
+            void_type.fn_type(&[value_ptr_type.into(), value_ptr_type.into()], false),
+            None,
+        );
         let env_child = module.add_function(
             "fabc_rt_env_child",
             value_ptr_type.fn_type(&[value_ptr_type.into()], false),
@@ -317,6 +326,8 @@ impl<'ctx> RuntimeAbi<'ctx> {
             context_value,
             object_new,
             object_insert,
+            list_new,
+            list_push,
             env_child,
             env_load,
             env_define,
@@ -1,10 +1,11 @@
 use std::collections::BTreeMap;
 
 use inkwell::{
+    basic_block::BasicBlock,
     builder::Builder,
     context::Context,
     module::Module,
-    values::{BasicMetadataValueEnum, BasicValueEnum, FunctionValue, PointerValue},
+    values::{BasicMetadataValueEnum, BasicValueEnum, FunctionValue, IntValue, PointerValue},
     IntPredicate,
 };
 
@@ -30,6 +31,13 @@ pub struct LlvmEmitter<'ctx> {
     abi: RuntimeAbi<'ctx>,
     function_symbols: BTreeMap<FunctionId, String>,
     string_counter: usize,
+    loop_targets: Vec<LoopTargets<'ctx>>,
+}
+
+#[derive(Clone, Copy)]
+struct LoopTargets<'ctx> {
+    continue_block: BasicBlock<'ctx>,
+    break_block: BasicBlock<'ctx>,
 }
 
 impl<'ctx> LlvmEmitter<'ctx> {
@@ -45,6 +53,7 @@ impl<'ctx> LlvmEmitter<'ctx> {
             abi,
             function_symbols: BTreeMap::new(),
             string_counter: 0,
+            loop_targets: Vec::new(),
         })
     }
 
@@ -173,17 +182,7 @@ impl<'ctx> LlvmEmitter<'ctx> {
                 then_branch,
                 else_branch,
             } => {
-                let condition_value = self.emit_expr(condition, frame, context)?;
-                let condition_bool = self
-                    .builder
-                    .build_call(self.abi.is_truthy, &[condition_value.into()], "cond.bool")
-                    .map_err(|err| Error::Codegen(err.to_string()))?
-                    .try_as_basic_value()
-                    .left()
-                    .ok_or_else(|| {
-                        Error::Codegen("truthiness helper did not return a value".to_string())
-                    })?
-                    .into_int_value();
+                let condition_bool = self.emit_condition(condition, frame, context)?;
 
                 let then_block = self.context.append_basic_block(llvm_fn, "if.then");
                 let else_block = self.context.append_basic_block(llvm_fn, "if.else");
@@ -229,11 +228,160 @@ impl<'ctx> LlvmEmitter<'ctx> {
                     .build_return(Some(&outcome))
                     .map_err(|err| Error::Codegen(err.to_string()))?;
             }
+            Stmt::While { condition, body } => {
+                let cond_block = self.context.append_basic_block(llvm_fn, "while.cond");
+                let body_block = self.context.append_basic_block(llvm_fn, "while.body");
+                let exit_block = self.context.append_basic_block(llvm_fn, "while.exit");
+
+                self.builder
+                    .build_unconditional_branch(cond_block)
+                    .map_err(|err| Error::Codegen(err.to_string()))?;
+
+                self.builder.position_at_end(cond_block);
+                let condition_bool = self.emit_condition(condition, frame, context)?;
+                self.builder
+                    .build_conditional_branch(condition_bool, body_block, exit_block)
+                    .map_err(|err| Error::Codegen(err.to_string()))?;
+
+                self.builder.position_at_end(body_block);
+                self.emit_loop_body(
+                    body,
+                    frame,
+                    context,
+                    llvm_fn,
+                    LoopTargets {
+                        continue_block: cond_block,
+                        break_block: exit_block,
+                    },
+                )?;
+
+                self.builder.position_at_end(exit_block);
+            }
+            Stmt::For {
+                initializer,
+                condition,
+                increment,
+                body,
+            } => {
+                let loop_frame =
+                    self.call_value(self.abi.env_child, &[frame.into()], "for.frame")?;
+                if let Some(initializer) = initializer {
+                    self.emit_stmt(initializer, loop_frame, context, llvm_fn)?;
+                }
+
+                let cond_block = self.context.append_basic_block(llvm_fn, "for.cond");
+                let body_block = self.context.append_basic_block(llvm_fn, "for.body");
+                let step_block = self.context.append_basic_block(llvm_fn, "for.step");
+                let exit_block = self.context.append_basic_block(llvm_fn, "for.exit");
+
+                self.builder
+                    .build_unconditional_branch(cond_block)
+                    .map_err(|err| Error::Codegen(err.to_string()))?;
+
+                self.builder.position_at_end(cond_block);
+                match condition {
+                    Some(condition) => {
+                        let condition_bool = self.emit_condition(condition, loop_frame, context)?;
+                        self.builder
+                            .build_conditional_branch(condition_bool, body_block, exit_block)
+                            .map_err(|err| Error::Codegen(err.to_string()))?;
+                    }
+                    None => {
+                        self.builder
+                            .build_unconditional_branch(body_block)
+                            .map_err(|err| Error::Codegen(err.to_string()))?;
+                    }
+                }
+
+                self.builder.position_at_end(body_block);
+                self.emit_loop_body(
+                    body,
+                    loop_frame,
+                    context,
+                    llvm_fn,
+                    LoopTargets {
+                        continue_block: step_block,
+                        break_block: exit_block,
+                    },
+                )?;
+
+                self.builder.position_at_end(step_block);
+                if let Some(increment) = increment {
+                    let _ = self.emit_expr(increment, loop_frame, context)?;
+                }
+                if !self.current_block_has_terminator() {
+                    self.builder
+                        .build_unconditional_branch(cond_block)
+                        .map_err(|err| Error::Codegen(err.to_string()))?;
+                }
+
+                self.builder.position_at_end(exit_block);
+            }
+            Stmt::Break => {
+                let targets = self.current_loop_targets("break")?;
+                self.builder
+                    .build_unconditional_branch(targets.break_block)
+                    .map_err(|err| Error::Codegen(err.to_string()))?;
+            }
+            Stmt::Continue => {
+                let targets = self.current_loop_targets("continue")?;
+                self.builder
+                    .build_unconditional_branch(targets.continue_block)
+                    .map_err(|err| Error::Codegen(err.to_string()))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn emit_loop_body(
+        &mut self,
+        body: &Block,
+        frame: PointerValue<'ctx>,
+        context: PointerValue<'ctx>,
+        llvm_fn: FunctionValue<'ctx>,
+        targets: LoopTargets<'ctx>,
+    ) -> Result<()> {
+        let body_frame = self.call_value(self.abi.env_child, &[frame.into()], "loop.frame")?;
+
+        self.loop_targets.push(targets);
+        let emitted = self.emit_block(body, body_frame, context, llvm_fn);
+        self.loop_targets.pop();
+        emitted?;
+
+        if !self.current_block_has_terminator() {
+            self.builder
+                .build_unconditional_branch(targets.continue_block)
+                .map_err(|err| Error::Codegen(err.to_string()))?;
         }
 
         Ok(())
     }
 
+    fn current_loop_targets(&self, keyword: &str) -> Result<LoopTargets<'ctx>> {
+        self.loop_targets
+            .last()
+            .copied()
+            .ok_or_else(|| Error::Codegen(format!("`{keyword}` used outside of a loop")))
+    }
+
+    fn emit_condition(
+        &mut self,
+        condition: &Expr,
+        frame: PointerValue<'ctx>,
+        context: PointerValue<'ctx>,
+    ) -> Result<IntValue<'ctx>> {
+        let condition_value = self.emit_expr(condition, frame, context)?;
+        Ok(self
+            .builder
+            .build_call(self.abi.is_truthy, &[condition_value.into()], "cond.bool")
+            .map_err(|err| Error::Codegen(err.to_string()))?
+            .try_as_basic_value()
+            .left()
+            .ok_or_else(|| Error::Codegen("truthiness helper did not return a value".to_string()))?
+            .into_int_value())
+    }
+
     fn emit_expr(
         &mut self,
         expr: &Expr,
@@ -269,6 +417,20 @@ impl<'ctx> LlvmEmitter<'ctx> {
                 }
                 Ok(object)
             }
+            Expr::List(elements) => {
+                let list = self.call_value(self.abi.list_new, &[], "list.new")?;
+                for element in elements {
+                    let value = self.emit_expr(element, frame, context)?;
+                    self.builder
+                        .build_call(
+                            self.abi.list_push,
+                            &[list.into(), value.into()],
+                            "list.push",
+                        )
+                        .map_err(|err| Error::Codegen(err.to_string()))?;
+                }
+                Ok(list)
+            }
             Expr::Closure(function_id) => {
                 let symbol = format!("fabc_fn_{function_id}");
                 let (symbol_ptr, symbol_len) =
@@ -668,6 +830,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn emitter_lowers_loops_to_branching_blocks() {
+        let context = Context::create();
+        let mut program = program_with_addition_and_goto();
+        program.functions[0].body.statements.insert(
+            0,
+            Stmt::For {
+                initializer: Some(Box::new(Stmt::Let {
+                    name: "i".to_string(),
+                    initializer: Expr::Literal(Literal::Number(0.0)),
+                })),
+                condition: Some(Expr::Binary {
+                    left: Box::new(Expr::Identifier("i".to_string())),
+                    operator: BinaryOperator::Less,
+                    right: Box::new(Expr::Literal(Literal::Number(3.0))),
+                }),
+                increment: None,
+                body: Block {
+                    statements: vec![Stmt::While {
+                        condition: Expr::Literal(Literal::Boolean(true)),
+                        body: Block {
+                            statements: vec![Stmt::Break],
+                        },
+                    }],
+                },
+            },
+        );
+
+        let artifact = LlvmEmitter::new(&context, "emitter_loop_test")
+            .expect("create emitter")
+            .emit(&program)
+            .expect("emit llvm artifact");
+
+        artifact.module.verify().expect("loop IR should verify");
+        let ir = artifact.module.print_to_string().to_string();
+        assert!(ir.contains("for.cond"));
+        assert!(ir.contains("for.step"));
+        assert!(ir.contains("while.body"));
+        assert!(ir.contains("while.exit"));
+    }
+
     fn program_with_addition_and_goto() -> StoryProgram {
         StoryProgram {
             start_part: "part_1".to_string(),
@@ -675,10 +878,16 @@ mod tests {
             parts: vec![
                 PartSpec {
                     id: "part_1".to_string(),
+                    properties: BTreeMap::new(),
+                    on_enter: None,
+                    on_exit: None,
                     steps: Vec::new(),
                 },
                 PartSpec {
                     id: "part_2".to_string(),
+                    properties: BTreeMap::new(),
+                    on_enter: None,
+                    on_exit: None,
                     steps: Vec::new(),
                 },
             ],
@@ -0,0 +1,71 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use fabc_error::{Diagnostic, Error as CompileError, LineCol, Severity, Span};
+use fabc_lexer::{tokens::Token, Lexer};
+
+struct SourceFile {
+    path: PathBuf,
+    source: Arc<str>,
+    line_offset: usize,
+    line_count: usize,
+}
+
+/// Linked modules are lexed with their lines shifted past every earlier file, so any span in
+/// the linked AST belongs to exactly one file.
+#[derive(Default)]
+pub(crate) struct SourceMap {
+    files: Vec<SourceFile>,
+    next_line_offset: usize,
+}
+
+impl SourceMap {
+    pub(crate) fn tokenize<'src>(&mut self, path: &Path, source: &'src str) -> Vec<Token<'src>> {
+        let line_offset = self.next_line_offset;
+        let line_count = source.split('\n').count();
+        self.next_line_offset += line_count;
+        self.files.push(SourceFile {
+            path: path.to_path_buf(),
+            source: source.into(),
+            line_offset,
+            line_count,
+        });
+
+        Lexer::tokenize(source)
+            .into_iter()
+            .map(|token| Token {
+                line: token.line + line_offset,
+                ..token
+            })
+            .collect()
+    }
+
+    pub(crate) fn diagnostic(&self, severity: Severity, error: CompileError) -> Diagnostic {
+        let line = error.span.start().line();
+        let Some(file) = self
+            .files
+            .iter()
+            .find(|file| line > file.line_offset && line <= file.line_offset + file.line_count)
+            .or(self.files.first())
+        else {
+            return Diagnostic::new(severity, None, "", error);
+        };
+
+        let rebase = |position: &LineCol| {
+            LineCol::new(
+                position.line().saturating_sub(file.line_offset),
+                position.col(),
+            )
+        };
+        let span = Span::new(rebase(error.span.start()), rebase(error.span.end()));
+
+        Diagnostic::new(
+            severity,
+            Some(file.path.clone()),
+            file.source.clone(),
+            CompileError::new(error.kind, span),
+        )
+    }
+}
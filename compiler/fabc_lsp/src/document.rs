@@ -0,0 +1,465 @@
+use std::collections::BTreeSet;
+
+use fabc_error::{LineCol, Span};
+use fabc_lexer::{
+    tokens::{Token, TokenKind},
+    Lexer,
+};
+use fabc_parser::{
+    ast::{
+        decl::{
+            object::ObjectDecl,
+            quote::{QuoteDecl, TextSegment},
+        },
+        expr::{literal::Literal, primitive::Primitive, Expr, Primary},
+        init::{
+            story::{part::element::Element, StoryInit},
+            Init,
+        },
+        stmt::{
+            block::BlockStmt,
+            r#if::{ElseClause, IfStmt},
+            Stmt,
+        },
+    },
+    Parser,
+};
+
+/// A name and the span it was written at.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Symbol {
+    pub name: String,
+    pub span: Span,
+}
+
+/// A part named by a `goto`, an `@part`, the story `start` or a `module.part` member.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartReference {
+    /// Module aliases walked before the part, outermost first; empty for local parts.
+    pub modules: Vec<Symbol>,
+    pub part: Symbol,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModuleImport {
+    pub path: String,
+    pub alias: Option<Symbol>,
+    pub span: Span,
+}
+
+/// The navigable symbols of one `.fab` source. Built from whatever the parser recovered, so a
+/// document with syntax errors still indexes its intact parts.
+#[derive(Debug, Default)]
+pub struct DocumentIndex {
+    pub parts: Vec<Symbol>,
+    pub references: Vec<PartReference>,
+    pub modules: Vec<ModuleImport>,
+    pub speakers: BTreeSet<String>,
+    pub context_keys: BTreeSet<String>,
+}
+
+impl DocumentIndex {
+    pub fn new(source: &str) -> Self {
+        let tokens = Lexer::tokenize(source);
+        let inits = Parser::parse(&tokens).result;
+        let mut index = Self::default();
+
+        for init in &inits {
+            match init {
+                Init::Module(module) => index.modules.push(ModuleImport {
+                    path: module.path.clone(),
+                    alias: module.alias.as_ref().and_then(|alias| {
+                        token_span(&tokens, module.info.span.start(), 3)
+                            .map(|span| symbol(alias, span))
+                    }),
+                    span: module.info.span.clone(),
+                }),
+                Init::Story(story) => {
+                    for part in &story.parts {
+                        let span = token_span(&tokens, part.info.span.start(), 1)
+                            .unwrap_or_else(|| part.info.span.clone());
+                        index.parts.push(symbol(&part.ident, span));
+                    }
+                }
+            }
+        }
+
+        for init in &inits {
+            if let Init::Story(story) = init {
+                index.collect_story(story);
+            }
+        }
+
+        index
+    }
+
+    pub fn part(&self, name: &str) -> Option<&Symbol> {
+        self.parts.iter().find(|part| part.name == name)
+    }
+
+    pub fn module(&self, alias: &str) -> Option<&ModuleImport> {
+        self.modules.iter().find(|module| {
+            module
+                .alias
+                .as_ref()
+                .is_some_and(|symbol| symbol.name == alias)
+        })
+    }
+
+    pub fn part_at(&self, line_col: &LineCol) -> Option<&Symbol> {
+        self.parts
+            .iter()
+            .find(|part| crate::position::contains(&part.span, line_col))
+    }
+
+    pub fn reference_at(&self, line_col: &LineCol) -> Option<&PartReference> {
+        self.references.iter().find(|reference| {
+            reference
+                .modules
+                .iter()
+                .chain([&reference.part])
+                .any(|symbol| crate::position::contains(&symbol.span, line_col))
+        })
+    }
+
+    pub fn module_at(&self, line_col: &LineCol) -> Option<&ModuleImport> {
+        self.modules
+            .iter()
+            .find(|module| crate::position::contains(&module.span, line_col))
+    }
+
+    fn collect_story(&mut self, story: &StoryInit) {
+        if let Some(metadata) = &story.metadata {
+            for (key, value) in &metadata.object.map {
+                if key == "start" {
+                    self.collect_target(value);
+                } else {
+                    self.collect_expr(value);
+                }
+            }
+        }
+
+        for part in &story.parts {
+            if let Some(properties) = &part.properties {
+                self.collect_object(properties);
+            }
+            for element in &part.elements {
+                match element {
+                    Element::Narration(narration) => self.collect_quote(&narration.quote),
+                    Element::Dialogue(dialogue) => {
+                        self.speakers.insert(dialogue.speaker.clone());
+                        for quote in &dialogue.quotes {
+                            self.collect_quote(quote);
+                        }
+                    }
+                    Element::Selection(selection) => {
+                        for quote in &selection.choices {
+                            self.collect_quote(quote);
+                        }
+                    }
+                    Element::Directive(directive) => {
+                        for argument in &directive.arguments {
+                            self.collect_expr(argument);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn collect_quote(&mut self, quote: &QuoteDecl) {
+        for segment in &quote.segments {
+            if let TextSegment::Expr(expr) = segment {
+                self.collect_expr(expr);
+            }
+        }
+        if let Some(properties) = &quote.properties {
+            self.collect_object(properties);
+        }
+    }
+
+    fn collect_object(&mut self, object: &ObjectDecl) {
+        for value in object.map.values() {
+            self.collect_expr(value);
+        }
+    }
+
+    fn collect_block(&mut self, block: &BlockStmt) {
+        for statement in &block.statements {
+            self.collect_stmt(statement);
+        }
+    }
+
+    fn collect_stmt(&mut self, statement: &Stmt) {
+        match statement {
+            Stmt::Expr(stmt) => self.collect_expr(&stmt.expr),
+            Stmt::Block(block) => self.collect_block(block),
+            Stmt::Let(stmt) => self.collect_expr(&stmt.initializer),
+            Stmt::Goto(stmt) => self.collect_target(&stmt.target),
+            Stmt::If(stmt) => self.collect_if(stmt),
+            Stmt::Return(stmt) => {
+                if let Some(value) = &stmt.value {
+                    self.collect_expr(value);
+                }
+            }
+            Stmt::While(stmt) => {
+                self.collect_expr(&stmt.condition);
+                self.collect_block(&stmt.body);
+            }
+            Stmt::For(stmt) => {
+                if let Some(initializer) = &stmt.initializer {
+                    self.collect_stmt(initializer);
+                }
+                for expr in [&stmt.condition, &stmt.increment].into_iter().flatten() {
+                    self.collect_expr(expr);
+                }
+                self.collect_block(&stmt.body);
+            }
+            Stmt::Break(_) | Stmt::Continue(_) => {}
+        }
+    }
+
+    fn collect_if(&mut self, stmt: &IfStmt) {
+        self.collect_expr(&stmt.condition);
+        self.collect_block(&stmt.then_branch);
+        match &stmt.else_branch {
+            Some(ElseClause::If(stmt)) => self.collect_if(stmt),
+            Some(ElseClause::Block(block)) => self.collect_block(block),
+            None => {}
+        }
+    }
+
+    /// Bare identifiers and strings only name a part where the linker reads them as story
+    /// targets, and only when such a part exists.
+    fn collect_target(&mut self, expr: &Expr) {
+        let local = match expr {
+            Expr::Primary {
+                value: Primary::Primitive(Primitive::Identifier { info, name }),
+                ..
+            }
+            | Expr::Primary {
+                value: Primary::Literal(Literal::String { info, value: name }),
+                ..
+            } if self.part(name).is_some() => Some(symbol(name, info.span.clone())),
+            _ => None,
+        };
+
+        match local {
+            Some(part) => self.references.push(PartReference {
+                modules: Vec::new(),
+                part,
+            }),
+            None => self.collect_expr(expr),
+        }
+    }
+
+    fn collect_expr(&mut self, expr: &Expr) {
+        if let Some(reference) = self.module_member(expr) {
+            self.references.push(reference);
+            return;
+        }
+
+        match expr {
+            Expr::Binary { left, right, .. } => {
+                self.collect_expr(left);
+                self.collect_expr(right);
+            }
+            Expr::Unary { right, .. } => self.collect_expr(right),
+            Expr::Assignment { name, value, .. } => {
+                self.collect_expr(name);
+                self.collect_expr(value);
+            }
+            Expr::MemberAccess { left, members, .. } => {
+                if let (
+                    Expr::Primary {
+                        value: Primary::Primitive(Primitive::Context { .. }),
+                        ..
+                    },
+                    Some(Expr::Primary {
+                        value: Primary::Primitive(Primitive::Identifier { name, .. }),
+                        ..
+                    }),
+                ) = (left.as_ref(), members.first())
+                {
+                    self.context_keys.insert(name.clone());
+                }
+
+                self.collect_expr(left);
+                for member in members {
+                    self.collect_expr(member);
+                }
+            }
+            Expr::Call {
+                callee, arguments, ..
+            } => {
+                self.collect_expr(callee);
+                for argument in arguments {
+                    self.collect_expr(argument);
+                }
+            }
+            Expr::Primary { value, .. } => match value {
+                Primary::Primitive(Primitive::StoryIdentifier { info, name }) => {
+                    self.references.push(PartReference {
+                        modules: Vec::new(),
+                        part: symbol(name, info.span.clone()),
+                    });
+                }
+                Primary::Primitive(Primitive::Grouping { expr, .. }) => self.collect_expr(expr),
+                Primary::Primitive(Primitive::Object { value, .. }) => self.collect_object(value),
+                Primary::Primitive(Primitive::List { elements, .. }) => {
+                    for element in elements {
+                        self.collect_expr(element);
+                    }
+                }
+                Primary::Primitive(Primitive::Closure { body, .. }) => self.collect_block(body),
+                Primary::Literal(_)
+                | Primary::Primitive(Primitive::Identifier { .. })
+                | Primary::Primitive(Primitive::Context { .. }) => {}
+            },
+            Expr::Grouping { expression, .. } => self.collect_expr(expression),
+        }
+    }
+
+    /// `alias.part` or `alias.nested.part`, where `alias` is one of this document's modules.
+    fn module_member(&self, expr: &Expr) -> Option<PartReference> {
+        let Expr::MemberAccess { left, members, .. } = expr else {
+            return None;
+        };
+
+        let alias = static_symbol(left)?;
+        self.module(&alias.name)?;
+
+        let mut symbols = members
+            .iter()
+            .map(static_symbol)
+            .collect::<Option<Vec<_>>>()?;
+        let part = symbols.pop()?;
+
+        let mut modules = vec![alias];
+        modules.extend(symbols);
+        Some(PartReference { modules, part })
+    }
+}
+
+fn static_symbol(expr: &Expr) -> Option<Symbol> {
+    match expr {
+        Expr::Primary {
+            value: Primary::Primitive(Primitive::Identifier { info, name }),
+            ..
+        }
+        | Expr::Primary {
+            value: Primary::Primitive(Primitive::StoryIdentifier { info, name }),
+            ..
+        }
+        | Expr::Primary {
+            value: Primary::Literal(Literal::String { info, value: name }),
+            ..
+        } => Some(symbol(name, info.span.clone())),
+        _ => None,
+    }
+}
+
+fn symbol(name: &str, span: Span) -> Symbol {
+    Symbol {
+        name: name.to_string(),
+        span,
+    }
+}
+
+/// Span of the token `skip` tokens after the one starting at `start`. Nodes only record their
+/// whole extent, so this recovers e.g. the identifier after a part's `#`.
+fn token_span(tokens: &[Token<'_>], start: &LineCol, skip: usize) -> Option<Span> {
+    let index = tokens
+        .iter()
+        .position(|token| token.line == start.line() && token.column == start.col())?;
+    let token = tokens.get(index + skip)?;
+
+    matches!(token.kind, TokenKind::Identifier(_)).then(|| Span::from(token))
+}
+
+#[cfg(test)]
+mod tests {
+    use fabc_error::LineCol;
+
+    use super::DocumentIndex;
+
+    const STORY: &str = r#"module "./branch.fab" as branch;
+
+Story { start: "intro" }
+
+# intro
+[guide]
+> "Hello {context.name}." {
+    next: () => {
+        context.met_guide = true;
+        if (context.brave) { goto branch.end; }
+        goto outro;
+    }
+}
+
+# outro
+* "Back to @intro." { next: () => { let target = @intro; goto target; } }
+"#;
+
+    #[test]
+    fn indexes_parts_references_and_completion_sources() {
+        let index = DocumentIndex::new(STORY);
+
+        let parts = index
+            .parts
+            .iter()
+            .map(|part| {
+                (
+                    part.name.as_str(),
+                    part.span.start().line(),
+                    part.span.start().col(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(parts, vec![("intro", 5, 3), ("outro", 15, 3)]);
+
+        let references = index
+            .references
+            .iter()
+            .map(|reference| {
+                let mut path = reference
+                    .modules
+                    .iter()
+                    .map(|module| module.name.as_str())
+                    .collect::<Vec<_>>();
+                path.push(&reference.part.name);
+                path.join(".")
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(references, vec!["intro", "branch.end", "outro", "intro"]);
+
+        let alias = index.modules[0].alias.as_ref().expect("module alias");
+        assert_eq!(
+            (alias.span.start().line(), alias.span.start().col()),
+            (1, 26)
+        );
+
+        assert_eq!(index.speakers.iter().collect::<Vec<_>>(), vec!["guide"]);
+        assert_eq!(
+            index.context_keys.iter().collect::<Vec<_>>(),
+            vec!["brave", "met_guide", "name"]
+        );
+    }
+
+    #[test]
+    fn finds_symbols_under_the_cursor() {
+        let index = DocumentIndex::new(STORY);
+
+        let reference = index
+            .reference_at(&LineCol::new(10, 35))
+            .expect("reference under cursor");
+        assert_eq!(reference.part.name, "end");
+        assert_eq!(
+            index
+                .part_at(&LineCol::new(15, 4))
+                .map(|part| part.name.as_str()),
+            Some("outro")
+        );
+        assert!(index.module_at(&LineCol::new(1, 10)).is_some());
+    }
+}
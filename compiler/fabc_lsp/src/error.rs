@@ -0,0 +1,17 @@
+use std::{io, result::Result as StdResult};
+
+use lsp_server::ProtocolError;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Protocol(#[from] ProtocolError),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error("the client connection closed")]
+    Disconnected,
+}
+
+pub type Result<T> = StdResult<T, Error>;
@@ -0,0 +1,10 @@
+//! A language server for `.fab` stories, speaking LSP over stdio.
+
+pub mod document;
+pub mod error;
+mod position;
+mod server;
+pub mod workspace;
+
+pub use error::{Error, Result};
+pub use server::run;
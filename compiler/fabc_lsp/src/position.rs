@@ -0,0 +1,66 @@
+use fabc_error::{LineCol, Span};
+use lsp_types::{Position, Range};
+
+/// Converts an LSP position, counted in UTF-16 code units, to the 1-based character column the
+/// lexer reports.
+pub(crate) fn line_col(source: &str, position: Position) -> LineCol {
+    let line = source.split('\n').nth(position.line as usize).unwrap_or("");
+    let mut units = 0;
+    let mut col = 1;
+
+    for ch in line.chars() {
+        if units >= position.character as usize {
+            break;
+        }
+        units += ch.len_utf16();
+        col += 1;
+    }
+
+    LineCol::new(position.line as usize + 1, col)
+}
+
+pub(crate) fn position(source: &str, line_col: &LineCol) -> Position {
+    let line_index = line_col.line().saturating_sub(1);
+    let line = source.split('\n').nth(line_index).unwrap_or("");
+    let character = line
+        .chars()
+        .take(line_col.col().saturating_sub(1))
+        .map(char::len_utf16)
+        .sum::<usize>();
+
+    Position::new(line_index as u32, character as u32)
+}
+
+/// Spans end on their last character, LSP ranges just past it.
+pub(crate) fn range(source: &str, span: &Span) -> Range {
+    let end = LineCol::new(span.end().line(), span.end().col() + 1);
+    Range::new(position(source, span.start()), position(source, &end))
+}
+
+/// Whether `line_col` falls inside `span`, counting the column right after it so a cursor at
+/// the end of a word still hits it.
+pub(crate) fn contains(span: &Span, line_col: &LineCol) -> bool {
+    let point = (line_col.line(), line_col.col());
+    (span.start().line(), span.start().col()) <= point
+        && point <= (span.end().line(), span.end().col() + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use fabc_error::{LineCol, Span};
+    use lsp_types::{Position, Range};
+
+    use super::{line_col, range};
+
+    #[test]
+    fn counts_utf16_units_against_character_columns() {
+        let source = "* \"😀 Zoë\"\n[Zoë]";
+
+        assert_eq!(line_col(source, Position::new(0, 5)), LineCol::new(1, 5));
+        assert_eq!(line_col(source, Position::new(1, 1)), LineCol::new(2, 2));
+        assert_eq!(
+            range(source, &Span::new(LineCol::new(1, 5), LineCol::new(1, 7))),
+            Range::new(Position::new(0, 5), Position::new(0, 8))
+        );
+    }
+}
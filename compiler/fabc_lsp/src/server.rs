@@ -0,0 +1,334 @@
+use std::path::PathBuf;
+
+use fabc::HostFunctions;
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::{
+    notification::{
+        DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument,
+        Notification as LspNotification, PublishDiagnostics, ShowMessage,
+    },
+    request::{Completion, GotoDefinition, References, Request as LspRequest},
+    CompletionOptions, CompletionResponse, DidChangeTextDocumentParams, DidCloseTextDocumentParams,
+    DidOpenTextDocumentParams, GotoDefinitionParams, GotoDefinitionResponse, MessageType, OneOf,
+    PublishDiagnosticsParams, ReferenceParams, ServerCapabilities, ShowMessageParams,
+    TextDocumentSyncCapability, TextDocumentSyncKind, Url,
+};
+use serde::de::DeserializeOwned;
+
+use crate::{
+    error::{Error, Result},
+    workspace::Workspace,
+};
+
+/// Serves LSP over stdin/stdout until the client shuts the server down.
+pub fn run() -> Result<()> {
+    let (connection, io_threads) = Connection::stdio();
+    serve(&connection)?;
+    io_threads.join()?;
+    Ok(())
+}
+
+fn serve(connection: &Connection) -> Result<()> {
+    let params = connection.initialize(serde_json::to_value(capabilities())?)?;
+
+    let mut server = Server {
+        connection,
+        workspace: Workspace::default(),
+    };
+    server.load_host_functions(&params)?;
+
+    for message in &connection.receiver {
+        match message {
+            Message::Request(request) => {
+                if connection.handle_shutdown(&request)? {
+                    return Ok(());
+                }
+                server.handle_request(request)?;
+            }
+            Message::Notification(notification) => server.handle_notification(notification)?,
+            Message::Response(_) => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        definition_provider: Some(OneOf::Left(true)),
+        references_provider: Some(OneOf::Left(true)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec![".".to_string(), "@".to_string(), "[".to_string()]),
+            ..CompletionOptions::default()
+        }),
+        ..ServerCapabilities::default()
+    }
+}
+
+struct Server<'a> {
+    connection: &'a Connection,
+    workspace: Workspace,
+}
+
+impl Server<'_> {
+    fn handle_request(&mut self, request: Request) -> Result<()> {
+        let id = request.id.clone();
+
+        let response = match request.method.as_str() {
+            GotoDefinition::METHOD => params::<GotoDefinitionParams>(request)
+                .map(|params| {
+                    let position = params.text_document_position_params;
+                    file_path(&position.text_document.uri)
+                        .and_then(|path| self.workspace.definition(&path, position.position))
+                        .map(GotoDefinitionResponse::Scalar)
+                })
+                .map(|result| Response::new_ok(id.clone(), result)),
+            References::METHOD => params::<ReferenceParams>(request)
+                .map(|params| {
+                    let position = params.text_document_position;
+                    file_path(&position.text_document.uri)
+                        .map(|path| {
+                            self.workspace.references(
+                                &path,
+                                position.position,
+                                params.context.include_declaration,
+                            )
+                        })
+                        .unwrap_or_default()
+                })
+                .map(|result| Response::new_ok(id.clone(), result)),
+            Completion::METHOD => params::<lsp_types::CompletionParams>(request)
+                .map(|params| {
+                    let position = params.text_document_position;
+                    file_path(&position.text_document.uri)
+                        .map(|path| self.workspace.completion(&path, position.position))
+                        .map(CompletionResponse::Array)
+                })
+                .map(|result| Response::new_ok(id.clone(), result)),
+            method => Err((
+                ErrorCode::MethodNotFound,
+                format!("unsupported request `{method}`"),
+            )),
+        };
+
+        self.send(
+            response
+                .unwrap_or_else(|(code, message)| Response::new_err(id, code as i32, message))
+                .into(),
+        )
+    }
+
+    fn handle_notification(&mut self, notification: Notification) -> Result<()> {
+        // Malformed or unsupported notifications are dropped; there is nobody to answer.
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let Ok(params) =
+                    notification.extract::<DidOpenTextDocumentParams>(DidOpenTextDocument::METHOD)
+                else {
+                    return Ok(());
+                };
+                if let Some(path) = file_path(&params.text_document.uri) {
+                    self.workspace.open(path, params.text_document.text);
+                }
+            }
+            DidChangeTextDocument::METHOD => {
+                let Ok(params) = notification
+                    .extract::<DidChangeTextDocumentParams>(DidChangeTextDocument::METHOD)
+                else {
+                    return Ok(());
+                };
+                let (Some(path), Some(change)) = (
+                    file_path(&params.text_document.uri),
+                    params.content_changes.into_iter().last(),
+                ) else {
+                    return Ok(());
+                };
+                self.workspace.open(path, change.text);
+            }
+            DidCloseTextDocument::METHOD => {
+                let Ok(params) = notification
+                    .extract::<DidCloseTextDocumentParams>(DidCloseTextDocument::METHOD)
+                else {
+                    return Ok(());
+                };
+                if let Some(path) = file_path(&params.text_document.uri) {
+                    self.workspace.close(&path);
+                    self.publish(params.text_document.uri, Vec::new())?;
+                }
+            }
+            _ => return Ok(()),
+        }
+
+        // An edit to a module changes the diagnostics of every story importing it.
+        let paths = self
+            .workspace
+            .paths()
+            .map(PathBuf::from)
+            .collect::<Vec<_>>();
+        for path in paths {
+            let Ok(uri) = Url::from_file_path(&path) else {
+                continue;
+            };
+            let diagnostics = self.workspace.diagnostics(&path);
+            self.publish(uri, diagnostics)?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads the manifest named by the `hostFunctions` initialization option, the same file
+    /// `fabulate --host-functions` takes. Relative paths start from the workspace root.
+    fn load_host_functions(&mut self, params: &serde_json::Value) -> Result<()> {
+        let Some(manifest) = params
+            .pointer("/initializationOptions/hostFunctions")
+            .and_then(serde_json::Value::as_str)
+        else {
+            return Ok(());
+        };
+        let root = params
+            .get("rootUri")
+            .and_then(serde_json::Value::as_str)
+            .and_then(|uri| Url::parse(uri).ok())
+            .and_then(|uri| file_path(&uri));
+        let path = root.map_or_else(|| PathBuf::from(manifest), |root| root.join(manifest));
+
+        match HostFunctions::load(&path) {
+            Ok(functions) => self.workspace.set_host_functions(functions.declarations),
+            Err(error) => {
+                let params = ShowMessageParams {
+                    typ: MessageType::ERROR,
+                    message: error.to_string(),
+                };
+                self.send(Notification::new(ShowMessage::METHOD.to_string(), params).into())?;
+            }
+        }
+        Ok(())
+    }
+
+    fn publish(&self, uri: Url, diagnostics: Vec<lsp_types::Diagnostic>) -> Result<()> {
+        let params = PublishDiagnosticsParams::new(uri, diagnostics, None);
+        self.send(Notification::new(PublishDiagnostics::METHOD.to_string(), params).into())
+    }
+
+    fn send(&self, message: Message) -> Result<()> {
+        self.connection
+            .sender
+            .send(message)
+            .map_err(|_| Error::Disconnected)
+    }
+}
+
+fn params<P: DeserializeOwned>(request: Request) -> std::result::Result<P, (ErrorCode, String)> {
+    serde_json::from_value(request.params)
+        .map_err(|error| (ErrorCode::InvalidParams, error.to_string()))
+}
+
+/// Only `file:` documents can take part in module linking; other schemes are ignored.
+fn file_path(uri: &Url) -> Option<PathBuf> {
+    uri.to_file_path().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, thread};
+
+    use fabc_reg_test::temp_case_dir;
+    use lsp_server::{Connection, Message, Notification, Request, RequestId};
+    use lsp_types::{
+        notification::{
+            DidOpenTextDocument, Exit, Initialized, Notification as LspNotification,
+            PublishDiagnostics,
+        },
+        request::{GotoDefinition, Initialize, Request as LspRequest, Shutdown},
+        DidOpenTextDocumentParams, GotoDefinitionResponse, PublishDiagnosticsParams,
+        TextDocumentItem, Url,
+    };
+    use serde_json::json;
+
+    use super::serve;
+
+    #[test]
+    fn serves_diagnostics_and_definitions_over_a_connection() {
+        let root = temp_case_dir("fabc_lsp_server");
+        fs::create_dir_all(&root).expect("create temp dir");
+        let uri = Url::from_file_path(root.join("story.fab")).expect("file uri");
+
+        let (server, client) = Connection::memory();
+        let handle = thread::spawn(move || serve(&server));
+        let send = |message: Message| client.sender.send(message).expect("send to server");
+        let receive = || client.receiver.recv().expect("receive from server");
+
+        send(
+            Request::new(
+                RequestId::from(1),
+                Initialize::METHOD.to_string(),
+                json!({ "capabilities": {} }),
+            )
+            .into(),
+        );
+        assert!(matches!(receive(), Message::Response(response) if response.error.is_none()));
+        send(Notification::new(Initialized::METHOD.to_string(), json!({})).into());
+
+        send(
+            Notification::new(
+                DidOpenTextDocument::METHOD.to_string(),
+                DidOpenTextDocumentParams {
+                    text_document: TextDocumentItem::new(
+                        uri.clone(),
+                        "fab".to_string(),
+                        1,
+                        "Story { start: \"intro\" }\n\n# intro\n* \"Hi\" { next: () => { goto outro; } }\n\n# outro\n* \"Bye\"\n\n# lost\n* \"Never\"\n".to_string(),
+                    ),
+                },
+            )
+            .into(),
+        );
+        let Message::Notification(notification) = receive() else {
+            panic!("expected published diagnostics");
+        };
+        assert_eq!(notification.method, PublishDiagnostics::METHOD);
+        let published: PublishDiagnosticsParams =
+            serde_json::from_value(notification.params).expect("diagnostics params");
+        assert_eq!(published.diagnostics.len(), 1);
+        assert_eq!(published.diagnostics[0].range.start.line, 8);
+
+        send(
+            Request::new(
+                RequestId::from(2),
+                GotoDefinition::METHOD.to_string(),
+                json!({
+                    "textDocument": { "uri": uri },
+                    "position": { "line": 3, "character": 31 },
+                }),
+            )
+            .into(),
+        );
+        let Message::Response(response) = receive() else {
+            panic!("expected a definition response");
+        };
+        let definition: GotoDefinitionResponse =
+            serde_json::from_value(response.result.expect("definition result"))
+                .expect("definition response");
+        let GotoDefinitionResponse::Scalar(location) = definition else {
+            panic!("expected a single location");
+        };
+        assert_eq!(location.range.start.line, 5);
+
+        send(
+            Request::new(
+                RequestId::from(3),
+                Shutdown::METHOD.to_string(),
+                json!(null),
+            )
+            .into(),
+        );
+        assert!(matches!(receive(), Message::Response(_)));
+        send(Notification::new(Exit::METHOD.to_string(), json!(null)).into());
+
+        handle
+            .join()
+            .expect("server thread")
+            .expect("server exits cleanly");
+    }
+}
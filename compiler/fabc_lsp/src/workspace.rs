@@ -0,0 +1,495 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use fabc::HostFunctionDecl;
+use fabc_error::{Diagnostic, Severity};
+use fabc_llvm::{compile::StoryCompiler, link::ModuleLinker};
+use lsp_types::{
+    CompletionItem, CompletionItemKind, Diagnostic as LspDiagnostic, DiagnosticSeverity, Location,
+    NumberOrString, Position, Range, Url,
+};
+
+use crate::{
+    document::{DocumentIndex, PartReference, Symbol},
+    position,
+};
+
+/// Open editor buffers, keyed by file path. Buffers shadow the files on disk, both for
+/// navigation and for the linker when checking a story.
+#[derive(Debug, Default)]
+pub struct Workspace {
+    documents: BTreeMap<PathBuf, String>,
+    /// Host functions stories may call, from the `hostFunctions` manifest.
+    host_functions: Vec<HostFunctionDecl>,
+}
+
+impl Workspace {
+    pub fn set_host_functions(&mut self, host_functions: Vec<HostFunctionDecl>) {
+        self.host_functions = host_functions;
+    }
+
+    pub fn open(&mut self, path: PathBuf, source: String) {
+        self.documents.insert(path, source);
+    }
+
+    pub fn close(&mut self, path: &Path) {
+        self.documents.remove(path);
+    }
+
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.documents.keys().map(PathBuf::as_path)
+    }
+
+    /// The open buffer for `path`, or the file on disk.
+    pub fn source(&self, path: &Path) -> Option<String> {
+        self.documents
+            .get(path)
+            .cloned()
+            .or_else(|| fs::read_to_string(path).ok())
+    }
+
+    /// Links and analyzes `path` as a story entry. Only diagnostics inside `path` are returned;
+    /// failures in an imported file are reported on the `module` line that imports it.
+    pub fn diagnostics(&self, path: &Path) -> Vec<LspDiagnostic> {
+        let Some(source) = self.source(path) else {
+            return Vec::new();
+        };
+        let linker = ModuleLinker::with_overlay(self.documents.clone());
+
+        let diagnostics = match StoryCompiler.check_linked(linker, path, &self.host_functions) {
+            Ok(diagnostics) => diagnostics,
+            Err(error) => match error.diagnostics() {
+                Some(diagnostics) => diagnostics.to_vec(),
+                None => {
+                    return vec![lsp_diagnostic(
+                        Range::default(),
+                        DiagnosticSeverity::ERROR,
+                        None,
+                        error.to_string(),
+                    )]
+                }
+            },
+        };
+
+        let entry = canonical(path);
+        let index = DocumentIndex::new(&source);
+        diagnostics
+            .iter()
+            .map(|diagnostic| match diagnostic.path.as_deref() {
+                Some(origin) if origin != entry => {
+                    let range = index
+                        .modules
+                        .iter()
+                        .find(|module| canonical(&resolve_import(path, &module.path)) == origin)
+                        .map(|module| position::range(&source, &module.span))
+                        .unwrap_or_default();
+                    lsp_diagnostic(
+                        range,
+                        severity(diagnostic),
+                        Some(diagnostic.error.kind.name()),
+                        format!("{}: {}", origin.display(), diagnostic.error.kind.message()),
+                    )
+                }
+                _ => lsp_diagnostic(
+                    position::range(&source, &diagnostic.error.span),
+                    severity(diagnostic),
+                    Some(diagnostic.error.kind.name()),
+                    diagnostic.error.kind.message(),
+                ),
+            })
+            .collect()
+    }
+
+    /// Where the part, `@part` reference or module alias under the cursor is defined.
+    pub fn definition(&self, path: &Path, position: Position) -> Option<Location> {
+        let source = self.source(path)?;
+        let index = DocumentIndex::new(&source);
+        let line_col = position::line_col(&source, position);
+
+        if let Some(part) = index.part_at(&line_col) {
+            return location(path, &source, part);
+        }
+
+        if let Some(reference) = index.reference_at(&line_col) {
+            if let Some(depth) = reference
+                .modules
+                .iter()
+                .position(|module| position::contains(&module.span, &line_col))
+            {
+                let aliases = reference.modules[..=depth].iter();
+                let module =
+                    self.resolve_modules(path, aliases.map(|alias| alias.name.as_str()))?;
+                return file_location(&module);
+            }
+
+            let (target, name) = self.resolve_reference(path, reference)?;
+            let source = self.source(&target)?;
+            return location(&target, &source, DocumentIndex::new(&source).part(&name)?);
+        }
+
+        let module = index.module_at(&line_col)?;
+        file_location(&resolve_import(path, &module.path))
+    }
+
+    /// Every reference, across the open documents, to the part under the cursor.
+    pub fn references(
+        &self,
+        path: &Path,
+        position: Position,
+        include_declaration: bool,
+    ) -> Vec<Location> {
+        let Some(source) = self.source(path) else {
+            return Vec::new();
+        };
+        let index = DocumentIndex::new(&source);
+        let line_col = position::line_col(&source, position);
+
+        let target = match (index.part_at(&line_col), index.reference_at(&line_col)) {
+            (Some(part), _) => Some((canonical(path), part.name.clone())),
+            (None, Some(reference)) => self.resolve_reference(path, reference),
+            (None, None) => None,
+        };
+        let Some((target_path, name)) = target else {
+            return Vec::new();
+        };
+
+        let mut paths = self.documents.keys().cloned().collect::<Vec<_>>();
+        if !self.documents.contains_key(path) {
+            paths.push(path.to_path_buf());
+        }
+
+        let mut locations = Vec::new();
+        for document in paths {
+            let Some(source) = self.source(&document) else {
+                continue;
+            };
+            let index = DocumentIndex::new(&source);
+
+            if include_declaration && canonical(&document) == target_path {
+                if let Some(part) = index.part(&name) {
+                    locations.extend(location(&document, &source, part));
+                }
+            }
+
+            for reference in &index.references {
+                if self.resolve_reference(&document, reference).as_ref()
+                    == Some(&(target_path.clone(), name.clone()))
+                {
+                    locations.extend(location(&document, &source, &reference.part));
+                }
+            }
+        }
+
+        locations
+    }
+
+    /// Part names and module aliases, speakers inside `[...]`, `context` keys after
+    /// `context.`, and a module's parts after `alias.`.
+    pub fn completion(&self, path: &Path, position: Position) -> Vec<CompletionItem> {
+        let Some(source) = self.source(path) else {
+            return Vec::new();
+        };
+        let index = DocumentIndex::new(&source);
+        let line_col = position::line_col(&source, position);
+        let line = source.split('\n').nth(line_col.line() - 1).unwrap_or("");
+        let prefix = line.chars().take(line_col.col() - 1).collect::<String>();
+        let word_start = prefix.trim_end_matches(is_ident_char);
+
+        if let Some(qualifier) = word_start.strip_suffix('.') {
+            let qualifier = qualifier
+                .rsplit(|ch: char| !is_ident_char(ch) && ch != '.')
+                .next()
+                .unwrap_or_default();
+            if qualifier == "context" {
+                return items(&index.context_keys, CompletionItemKind::FIELD);
+            }
+
+            let Some(module) = self.resolve_modules(path, qualifier.split('.')) else {
+                return Vec::new();
+            };
+            let Some(source) = self.source(&module) else {
+                return Vec::new();
+            };
+            let index = DocumentIndex::new(&source);
+            return items(
+                index.parts.iter().map(|part| &part.name),
+                CompletionItemKind::REFERENCE,
+            );
+        }
+
+        if word_start.trim_start().starts_with('[') && !word_start.contains(']') {
+            return items(&index.speakers, CompletionItemKind::VALUE);
+        }
+
+        let mut completions = items(
+            index.parts.iter().map(|part| &part.name),
+            CompletionItemKind::REFERENCE,
+        );
+        completions.extend(items(
+            index
+                .modules
+                .iter()
+                .filter_map(|module| module.alias.as_ref().map(|alias| &alias.name)),
+            CompletionItemKind::MODULE,
+        ));
+        completions
+    }
+
+    /// The file that declares the part `reference` names, with the part's local name.
+    fn resolve_reference(
+        &self,
+        path: &Path,
+        reference: &PartReference,
+    ) -> Option<(PathBuf, String)> {
+        let target = if reference.modules.is_empty() {
+            path.to_path_buf()
+        } else {
+            let aliases = reference.modules.iter();
+            self.resolve_modules(path, aliases.map(|alias| alias.name.as_str()))?
+        };
+
+        Some((canonical(&target), reference.part.name.clone()))
+    }
+
+    /// Follows a chain of module aliases, e.g. `outer.inner`, to the file it ends at.
+    fn resolve_modules<'a>(
+        &self,
+        path: &Path,
+        aliases: impl IntoIterator<Item = &'a str>,
+    ) -> Option<PathBuf> {
+        let mut current = path.to_path_buf();
+        for alias in aliases {
+            let source = self.source(&current)?;
+            let index = DocumentIndex::new(&source);
+            current = resolve_import(&current, &index.module(alias)?.path);
+        }
+        Some(current)
+    }
+}
+
+/// Mirrors the linker: relative imports resolve against the importing file's directory.
+fn resolve_import(from: &Path, import: &str) -> PathBuf {
+    match from.parent() {
+        Some(parent) => parent.join(import),
+        None => PathBuf::from(import),
+    }
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn location(path: &Path, source: &str, symbol: &Symbol) -> Option<Location> {
+    Some(Location::new(
+        Url::from_file_path(path).ok()?,
+        position::range(source, &symbol.span),
+    ))
+}
+
+fn file_location(path: &Path) -> Option<Location> {
+    Some(Location::new(
+        Url::from_file_path(canonical(path)).ok()?,
+        Range::default(),
+    ))
+}
+
+fn is_ident_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_'
+}
+
+fn items<'a>(
+    labels: impl IntoIterator<Item = &'a String>,
+    kind: CompletionItemKind,
+) -> Vec<CompletionItem> {
+    labels
+        .into_iter()
+        .map(|label| CompletionItem {
+            label: label.clone(),
+            kind: Some(kind),
+            ..CompletionItem::default()
+        })
+        .collect()
+}
+
+fn severity(diagnostic: &Diagnostic) -> DiagnosticSeverity {
+    match diagnostic.severity {
+        Severity::Error => DiagnosticSeverity::ERROR,
+        Severity::Warning => DiagnosticSeverity::WARNING,
+    }
+}
+
+fn lsp_diagnostic(
+    range: Range,
+    severity: DiagnosticSeverity,
+    code: Option<&str>,
+    message: String,
+) -> LspDiagnostic {
+    LspDiagnostic {
+        range,
+        severity: Some(severity),
+        code: code.map(|code| NumberOrString::String(code.to_string())),
+        source: Some("fabc".to_string()),
+        message,
+        ..LspDiagnostic::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use fabc::{DataType, HostFunctionDecl};
+    use fabc_reg_test::temp_case_dir;
+    use lsp_types::{DiagnosticSeverity, Position};
+
+    use super::Workspace;
+
+    const ENTRY: &str = r#"module "./branch.fab" as branch;
+
+Story { start: "intro" }
+
+# intro
+[guide]
+> "Choose." {
+    next: () => {
+        context.seen_guide = true;
+        goto branch.end;
+    }
+}
+
+# orphan
+* "Nobody comes here."
+"#;
+
+    const BRANCH: &str = r#"Story {}
+
+# end
+* "Imported ending" { next: () => { goto @end; } }
+"#;
+
+    fn workspace() -> (Workspace, std::path::PathBuf, std::path::PathBuf) {
+        let root = temp_case_dir("fabc_lsp_workspace");
+        fs::create_dir_all(&root).expect("create temp dir");
+        let entry = root.join("entry.fab");
+        let branch = root.join("branch.fab");
+        fs::write(&branch, BRANCH).expect("write import");
+
+        let mut workspace = Workspace::default();
+        workspace.open(entry.clone(), ENTRY.to_string());
+        (workspace, entry, branch)
+    }
+
+    #[test]
+    fn publishes_analyzer_diagnostics_for_unsaved_buffers() {
+        let (workspace, entry, _) = workspace();
+
+        let diagnostics = workspace.diagnostics(&entry);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Some(DiagnosticSeverity::WARNING));
+        assert_eq!(diagnostics[0].range.start, Position::new(13, 0));
+    }
+
+    #[test]
+    fn accepts_calls_to_declared_host_functions() {
+        let (mut workspace, entry, _) = workspace();
+        workspace.open(
+            entry.clone(),
+            "Story { start: \"intro\" }\n\n# intro\n* \"Hi\" { next: () => { context.rep = reputation(\"guild\"); } }\n"
+                .to_string(),
+        );
+        assert_eq!(
+            workspace.diagnostics(&entry)[0].severity,
+            Some(DiagnosticSeverity::ERROR)
+        );
+
+        workspace.set_host_functions(vec![HostFunctionDecl::new(
+            "reputation",
+            vec![DataType::String],
+            DataType::Number,
+        )]);
+
+        assert!(workspace.diagnostics(&entry).is_empty());
+    }
+
+    #[test]
+    fn reports_import_failures_on_the_module_line() {
+        let (mut workspace, entry, branch) = workspace();
+        workspace.open(branch, "Story {}\n# end\n* \"Unclosed".to_string());
+
+        let diagnostics = workspace.diagnostics(&entry);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].range.start, Position::new(0, 0));
+        assert!(diagnostics[0].message.contains("branch.fab"));
+    }
+
+    #[test]
+    fn jumps_to_parts_and_modules_across_files() {
+        let (workspace, entry, branch) = workspace();
+        let branch = fs::canonicalize(branch).expect("canonical import");
+
+        let part = workspace
+            .definition(&entry, Position::new(9, 22))
+            .expect("definition of branch.end");
+        assert_eq!(part.uri.to_file_path().ok(), Some(branch.clone()));
+        assert_eq!(part.range.start, Position::new(2, 2));
+
+        let module = workspace
+            .definition(&entry, Position::new(9, 15))
+            .expect("definition of branch alias");
+        assert_eq!(module.uri.to_file_path().ok(), Some(branch));
+        assert_eq!(module.range.start, Position::new(0, 0));
+
+        let start = workspace
+            .definition(&entry, Position::new(2, 17))
+            .expect("definition of start part");
+        assert_eq!(start.range.start, Position::new(4, 2));
+    }
+
+    #[test]
+    fn finds_part_references_from_importers() {
+        let (workspace, _, branch) = workspace();
+
+        let references = workspace.references(&branch, Position::new(2, 3), true);
+
+        let mut files = references
+            .iter()
+            .map(|location| {
+                location
+                    .uri
+                    .to_file_path()
+                    .expect("file uri")
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .map(str::to_string)
+                    .expect("file name")
+            })
+            .collect::<Vec<_>>();
+        files.sort();
+        assert_eq!(files, vec!["branch.fab", "branch.fab", "entry.fab"]);
+    }
+
+    #[test]
+    fn completes_parts_speakers_and_context_keys() {
+        let (mut workspace, entry, _) = workspace();
+        let labels = |workspace: &Workspace, line, character| {
+            workspace
+                .completion(&entry, Position::new(line, character))
+                .into_iter()
+                .map(|item| item.label)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(labels(&workspace, 8, 16), vec!["seen_guide"]);
+        assert_eq!(labels(&workspace, 9, 20), vec!["end"]);
+        assert_eq!(labels(&workspace, 5, 1), vec!["guide"]);
+        assert_eq!(labels(&workspace, 2, 16), vec!["intro", "orphan", "branch"]);
+
+        workspace.open(entry.clone(), ENTRY.replace("goto branch.end", "goto "));
+        assert!(labels(&workspace, 9, 13).contains(&"orphan".to_string()));
+    }
+}
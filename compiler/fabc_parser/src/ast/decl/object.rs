@@ -23,7 +23,15 @@ impl Parsable for ObjectDecl {
                 TokenKind::RightBrace,
                 TokenKind::Comma,
                 |parser| {
-                    let key = expect_token!(parser, TokenKind::Identifier, "identifier")?;
+                    // Keywords are allowed as keys so choices can use `if:` guards.
+                    let key = match parser.peek() {
+                        TokenKind::Keyword(keyword) => {
+                            let key = keyword.to_string();
+                            parser.advance();
+                            key
+                        }
+                        _ => expect_token!(parser, TokenKind::Identifier, "identifier")?,
+                    };
                     parser.consume(TokenKind::Colon)?;
                     let value = Expr::parse(parser)?;
                     Ok((key, value))
@@ -65,4 +73,15 @@ mod tests {
 
         assert_debug_snapshot!(object_decl);
     }
+
+    #[test]
+    fn parses_keyword_keys() {
+        let object_decl = Parser::parse_ast_str::<ObjectDecl>("{ if: true, next: 1 }")
+            .expect("Failed to parse object declaration");
+
+        assert_eq!(
+            object_decl.map.keys().collect::<Vec<_>>(),
+            vec!["if", "next"]
+        );
+    }
 }
@@ -1,15 +1,27 @@
-use fabc_error::{Error, Span};
-use fabc_lexer::tokens::TokenKind;
+use fabc_error::{kind::CompileErrorKind, Error, LineCol, Span};
+use std::ops::Range;
+
+use fabc_lexer::{
+    tokens::{StringLiteral, StringStyle, Token, TokenKind},
+    Lexer,
+};
 
 use crate::{
-    ast::{decl::object::ObjectDecl, NodeInfo},
+    ast::{decl::object::ObjectDecl, expr::Expr, NodeInfo},
     expect_token, Parsable, Parser,
 };
 
+#[derive(Debug, PartialEq)]
+pub enum TextSegment {
+    Text(String),
+    Expr(Expr),
+}
+
 #[derive(Debug, PartialEq)]
 pub struct QuoteDecl {
     pub info: NodeInfo,
     pub text: String,
+    pub segments: Vec<TextSegment>,
     pub properties: Option<ObjectDecl>,
 }
 
@@ -18,6 +30,13 @@ impl Parsable for QuoteDecl {
         let start_span = parser.start_span();
 
         let text = expect_token!(parser, TokenKind::String, "quote text")?;
+        let segments = match &parser.previous_token().kind {
+            TokenKind::String(literal) => {
+                let body = QuoteBody::new(parser.previous_token(), literal);
+                Self::segments(parser, &text, &body)?
+            }
+            _ => unreachable!("expect_token! only accepts string tokens"),
+        };
         let properties = if parser.peek() == &TokenKind::LeftBrace {
             Some(ObjectDecl::parse(parser)?)
         } else {
@@ -32,16 +51,206 @@ impl Parsable for QuoteDecl {
                 span: Span::from((start_span, end_span)),
             },
             text,
+            segments,
             properties,
         })
     }
 }
 
+impl QuoteDecl {
+    pub fn is_interpolated(&self) -> bool {
+        self.segments
+            .iter()
+            .any(|segment| matches!(segment, TextSegment::Expr(_)))
+    }
+
+    /// Splits quote text into literal runs and `{expr}` interpolations. `{{` and `}}` stand for
+    /// literal braces. Raw strings are taken verbatim and never interpolate.
+    fn segments(
+        parser: &mut Parser<'_, '_>,
+        text: &str,
+        body: &QuoteBody<'_>,
+    ) -> Result<Vec<TextSegment>, Error> {
+        if body.raw {
+            return Ok(if text.is_empty() {
+                Vec::new()
+            } else {
+                vec![TextSegment::Text(text.to_string())]
+            });
+        }
+
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = text.char_indices().peekable();
+
+        while let Some((index, ch)) = chars.next() {
+            match ch {
+                '{' | '}' if chars.peek().map(|(_, next)| *next) == Some(ch) => {
+                    chars.next();
+                    literal.push(ch);
+                }
+                '{' => {
+                    let mut depth = 1;
+                    let mut close = None;
+                    let (mut in_literal, mut escaped) = (false, false);
+
+                    for (index, ch) in chars.by_ref() {
+                        // Braces inside a nested string literal do not count.
+                        if in_literal {
+                            if escaped {
+                                escaped = false;
+                            } else if ch == '\\' {
+                                escaped = true;
+                            } else if ch == '"' {
+                                in_literal = false;
+                            }
+                            continue;
+                        }
+                        match ch {
+                            '"' => in_literal = true,
+                            '{' => depth += 1,
+                            '}' => depth -= 1,
+                            _ => {}
+                        }
+                        if depth == 0 {
+                            close = Some(index);
+                            break;
+                        }
+                    }
+
+                    let Some(close) = close else {
+                        let open = body.position(index);
+                        return Err(Error::new(
+                            CompileErrorKind::UnclosedDelimiter,
+                            Span::from((open, open)),
+                        ));
+                    };
+
+                    if text[index + 1..close].trim().is_empty() {
+                        return Err(Error::new(
+                            CompileErrorKind::EmptyInterpolation,
+                            Span::from((body.position(index), body.position(close))),
+                        ));
+                    }
+                    if !literal.is_empty() {
+                        segments.push(TextSegment::Text(std::mem::take(&mut literal)));
+                    }
+                    let expr = Self::interpolation(parser, text, index + 1..close, body)?;
+                    segments.push(TextSegment::Expr(expr));
+                }
+                '}' => {
+                    let position = body.position(index);
+                    return Err(Error::new(
+                        CompileErrorKind::ExpectedSymbol {
+                            expected: "}}".to_string(),
+                            found: "}".to_string(),
+                        },
+                        Span::from((position, position)),
+                    ));
+                }
+                _ => literal.push(ch),
+            }
+        }
+
+        if !literal.is_empty() {
+            segments.push(TextSegment::Text(literal));
+        }
+
+        Ok(segments)
+    }
+
+    fn interpolation(
+        parser: &mut Parser<'_, '_>,
+        text: &str,
+        range: Range<usize>,
+        body: &QuoteBody<'_>,
+    ) -> Result<Expr, Error> {
+        // Tokens are re-lexed from the decoded interpolation alone, so map them back onto the
+        // positions they occupy in the source.
+        let tokens: Vec<Token<'_>> = Lexer::tokenize(&text[range.clone()])
+            .into_iter()
+            .map(|token| {
+                let decoded = range.start + token.offset;
+                let position = body.position(decoded);
+                Token {
+                    line: position.line(),
+                    column: position.col(),
+                    offset: body.offset(decoded),
+                    ..token
+                }
+            })
+            .collect();
+
+        parser.nested(&tokens, |parser| {
+            let expr = Expr::parse(parser)?;
+            if !parser.is_terminated() {
+                return Err(Error::new(
+                    CompileErrorKind::ExpectedSymbol {
+                        expected: "}".to_string(),
+                        found: parser.peek().to_string(),
+                    },
+                    parser.peek_token(),
+                ));
+            }
+            Ok(expr)
+        })
+    }
+}
+
+/// Where a quote's body sits in the source, for placing positions found in its decoded text.
+struct QuoteBody<'src> {
+    raw: bool,
+    source: &'src str,
+    /// Source offset, relative to `source`, of every decoded byte plus the end of the body.
+    offsets: Vec<usize>,
+    start: LineCol,
+    start_offset: usize,
+}
+
+impl<'src> QuoteBody<'src> {
+    fn new(token: &Token<'_>, literal: &StringLiteral<'src>) -> Self {
+        // Delimiters are ASCII, so their byte length is also their width in columns.
+        let opening = literal.style.opening_len();
+        QuoteBody {
+            raw: matches!(literal.style, StringStyle::Raw { .. }),
+            source: literal.raw,
+            offsets: literal.source_offsets(),
+            start: LineCol::new(token.line, token.column + opening),
+            start_offset: token.offset + opening,
+        }
+    }
+
+    /// Absolute byte offset of the decoded byte at `decoded`.
+    fn offset(&self, decoded: usize) -> usize {
+        self.start_offset + self.offsets[decoded]
+    }
+
+    /// Line and column of the decoded byte at `decoded`.
+    fn position(&self, decoded: usize) -> LineCol {
+        let preceding = &self.source[..self.offsets[decoded]];
+        match preceding.rfind('\n') {
+            Some(newline) => LineCol::new(
+                self.start.line() + preceding.matches('\n').count(),
+                preceding[newline + 1..].chars().count() + 1,
+            ),
+            None => LineCol::new(
+                self.start.line(),
+                self.start.col() + preceding.chars().count(),
+            ),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use insta::assert_debug_snapshot;
 
-    use crate::{ast::decl::quote::QuoteDecl, Parser};
+    use fabc_error::kind::CompileErrorKind;
+
+    use crate::{
+        ast::decl::quote::{QuoteDecl, TextSegment},
+        Parser,
+    };
 
     #[test]
     fn parses_quote_decl_without_properties() {
@@ -60,4 +269,113 @@ mod tests {
 
         assert_debug_snapshot!(quote_decl);
     }
+
+    #[test]
+    fn parses_quote_decl_with_interpolations() {
+        let quote_decl = Parser::parse_ast_str::<QuoteDecl>(
+            "\"You have {context.gold + 1} coins, {{friend}}.\"",
+        )
+        .expect("Failed to parse quote");
+
+        assert_debug_snapshot!(quote_decl);
+    }
+
+    #[test]
+    fn rejects_unclosed_interpolation() {
+        let error = Parser::parse_ast_str::<QuoteDecl>("\"You have {gold coins\"")
+            .expect_err("Expected an unclosed interpolation");
+
+        assert_eq!(error.kind, CompileErrorKind::UnclosedDelimiter.into());
+    }
+
+    #[test]
+    fn parses_string_literals_nested_in_interpolations() {
+        let quote_decl = Parser::parse_ast_str::<QuoteDecl>(r#""Shout {str.upper("}")}!""#)
+            .expect("Failed to parse quote");
+
+        assert_eq!(quote_decl.segments.len(), 3);
+        assert!(matches!(quote_decl.segments[1], TextSegment::Expr(_)));
+        assert_eq!(quote_decl.segments[2], TextSegment::Text("!".to_string()));
+    }
+
+    #[test]
+    fn rejects_empty_interpolations() {
+        let error = Parser::parse_ast_str::<QuoteDecl>(r#""Nothing { } here""#)
+            .expect_err("Expected an empty interpolation");
+
+        assert_eq!(error.kind, CompileErrorKind::EmptyInterpolation.into());
+        assert_eq!((error.span.start().col(), error.span.end().col()), (10, 12));
+    }
+
+    #[test]
+    fn keeps_braces_in_raw_quotes() {
+        for source in [
+            r#"r"literal {braces} here""#,
+            r##"r#"literal {braces} here"#"##,
+        ] {
+            let quote_decl =
+                Parser::parse_ast_str::<QuoteDecl>(source).expect("Failed to parse quote");
+
+            assert_eq!(
+                quote_decl.segments,
+                vec![TextSegment::Text("literal {braces} here".to_string())]
+            );
+        }
+    }
+
+    #[test]
+    fn places_interpolation_errors_at_their_source_position() {
+        let cases = [
+            // Each escape is two source columns for one decoded character.
+            (r#""Say \"hi\" \"there\" {context.x +}""#, (1, 35)),
+            // Stripped indentation and the opening line break still count.
+            (
+                "\"\"\"\n    First line\n      {context.x +}\n    \"\"\"",
+                (3, 19),
+            ),
+        ];
+        for (source, (line, col)) in cases {
+            let error =
+                Parser::parse_ast_str::<QuoteDecl>(source).expect_err("Expected a bad expression");
+
+            assert_eq!(
+                (error.span.start().line(), error.span.start().col()),
+                (line, col)
+            );
+        }
+    }
+
+    #[test]
+    fn places_interpolated_expressions_at_their_source_position() {
+        let quote_decl =
+            Parser::parse_ast_str::<QuoteDecl>(r#""\t{gold}""#).expect("Failed to parse quote");
+        let TextSegment::Expr(expr) = &quote_decl.segments[1] else {
+            panic!("Expected an interpolation, found {:?}", quote_decl.segments);
+        };
+
+        assert_eq!(expr.info().span.start().col(), 5);
+    }
+
+    #[test]
+    fn decodes_escaped_quote_text() {
+        let quote_decl = Parser::parse_ast_str::<QuoteDecl>(r#""\"Run,\" she said.\n\tNow.""#)
+            .expect("Failed to parse quote");
+
+        assert_eq!(quote_decl.text, "\"Run,\" she said.\n\tNow.");
+    }
+
+    #[test]
+    fn reports_invalid_escapes_at_the_escape() {
+        let error = Parser::parse_ast_str::<QuoteDecl>(r#""Bad \x escape""#)
+            .expect_err("Expected an invalid escape");
+
+        assert_eq!(
+            error.kind,
+            CompileErrorKind::InvalidEscape {
+                escape: r"\x".to_string()
+            }
+            .into()
+        );
+        assert_eq!((error.span.start().col(), error.span.end().col()), (6, 7));
+    }
 }
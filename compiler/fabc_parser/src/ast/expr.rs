@@ -1,7 +1,7 @@
 use fabc_error::{kind::CompileErrorKind, Error, LineCol, Span};
 use fabc_lexer::{
     keywords::KeywordKind,
-    tokens::{Token, TokenKind},
+    tokens::{ErrorKind, Token, TokenKind},
 };
 
 use crate::{
@@ -330,57 +330,82 @@ impl Expr {
 
     fn member_access(parser: &mut Parser<'_, '_>) -> Result<Expr, Error> {
         let start_span = parser.start_span();
-        let mut expr = Self::call(parser)?;
-
-        if parser.r#match(&[TokenKind::Dot]) {
-            let mut members = Vec::new();
-            loop {
-                let member = Self::call(parser)?;
-                members.push(member);
+        let mut expr = Self::primary(parser)?;
+        let mut members = Vec::new();
 
-                if !parser.r#match(&[TokenKind::Dot]) {
-                    break;
+        loop {
+            match parser.peek() {
+                TokenKind::Dot => {
+                    parser.consume(TokenKind::Dot)?;
+                    members.push(Self::primary(parser)?);
+                }
+                TokenKind::LeftBracket => members.push(Self::index(parser)?),
+                TokenKind::LeftParen => {
+                    // A call applies to the whole chain before it, so `obj.run(x)` calls `obj.run`.
+                    let callee = Self::wrap_members(parser, start_span, expr, &mut members);
+                    expr = Self::call(parser, start_span, callee)?;
                 }
+                _ => break,
             }
-            let end_span = parser.end_span();
-
-            expr = Expr::MemberAccess {
-                info: NodeInfo {
-                    id: parser.assign_id(),
-                    span: Span::from((start_span, end_span)),
-                },
-                left: Box::new(expr),
-                members,
-            };
         }
 
-        Ok(expr)
+        Ok(Self::wrap_members(parser, start_span, expr, &mut members))
     }
 
-    fn call(parser: &mut Parser<'_, '_>) -> Result<Expr, Error> {
-        let start_span = parser.start_span();
-        let mut expr = Self::primary(parser)?;
-
-        if parser.peek() == &TokenKind::LeftParen {
-            let arguments = parser.punctuated(
-                TokenKind::LeftParen,
-                TokenKind::RightParen,
-                TokenKind::Comma,
-                |parser| Expr::parse(parser),
-            )?;
-            let end_span = parser.end_span();
+    fn wrap_members(
+        parser: &mut Parser<'_, '_>,
+        start_span: LineCol,
+        expr: Expr,
+        members: &mut Vec<Expr>,
+    ) -> Expr {
+        if members.is_empty() {
+            return expr;
+        }
 
-            expr = Expr::Call {
-                info: NodeInfo {
-                    id: parser.assign_id(),
-                    span: Span::from((start_span, end_span)),
-                },
-                callee: Box::new(expr),
-                arguments,
-            };
+        let end_span = parser.end_span();
+        Expr::MemberAccess {
+            info: NodeInfo {
+                id: parser.assign_id(),
+                span: Span::from((start_span, end_span)),
+            },
+            left: Box::new(expr),
+            members: std::mem::take(members),
         }
+    }
 
-        Ok(expr)
+    fn index(parser: &mut Parser<'_, '_>) -> Result<Expr, Error> {
+        // Indexes are wrapped in a grouping so `list[i]` never reads as the key `i`.
+        let start_span = parser.start_span();
+        let index =
+            parser.enclosed(TokenKind::LeftBracket, TokenKind::RightBracket, Expr::parse)?;
+        let end_span = parser.end_span();
+
+        Ok(Expr::Grouping {
+            info: NodeInfo {
+                id: parser.assign_id(),
+                span: Span::from((start_span, end_span)),
+            },
+            expression: Box::new(index),
+        })
+    }
+
+    fn call(parser: &mut Parser<'_, '_>, start_span: LineCol, callee: Expr) -> Result<Expr, Error> {
+        let arguments = parser.punctuated(
+            TokenKind::LeftParen,
+            TokenKind::RightParen,
+            TokenKind::Comma,
+            |parser| Expr::parse(parser),
+        )?;
+        let end_span = parser.end_span();
+
+        Ok(Expr::Call {
+            info: NodeInfo {
+                id: parser.assign_id(),
+                span: Span::from((start_span, end_span)),
+            },
+            callee: Box::new(callee),
+            arguments,
+        })
     }
 
     fn primary(parser: &mut Parser<'_, '_>) -> Result<Expr, Error> {
@@ -388,7 +413,8 @@ impl Expr {
             // Literals
             TokenKind::String(_)
             | TokenKind::Number(_)
-            | TokenKind::Keyword(KeywordKind::True | KeywordKind::False | KeywordKind::None) => {
+            | TokenKind::Keyword(KeywordKind::True | KeywordKind::False | KeywordKind::None)
+            | TokenKind::Error(ErrorKind::InvalidEscape(_) | ErrorKind::UnterminatedString) => {
                 let literal = Literal::parse(parser)?;
 
                 Ok(Expr::Primary {
@@ -403,7 +429,9 @@ impl Expr {
             // Primitives
             TokenKind::LeftParen
             | TokenKind::LeftBrace
+            | TokenKind::LeftBracket
             | TokenKind::Identifier(_)
+            | TokenKind::Commat
             | TokenKind::Keyword(KeywordKind::Context) => {
                 let primitive = Primitive::parse(parser)?;
 
@@ -472,6 +500,20 @@ mod tests {
         assert_debug_snapshot!(expr);
     }
 
+    #[test]
+    fn parses_index_access_expr() {
+        let expr = Parser::parse_ast_str::<Expr>("obj.items[i + 1].name")
+            .expect("Failed to parse expression");
+        assert_debug_snapshot!(expr);
+    }
+
+    #[test]
+    fn parses_method_call_expr() {
+        let expr =
+            Parser::parse_ast_str::<Expr>("str.len(name)").expect("Failed to parse expression");
+        assert_debug_snapshot!(expr);
+    }
+
     #[test]
     fn parses_unary_expr() {
         let expr = Parser::parse_ast_str::<Expr>("-!42").expect("Failed to parse expression");
@@ -485,6 +527,12 @@ mod tests {
         assert_debug_snapshot!(expr);
     }
 
+    #[test]
+    fn parses_story_identifier_expr() {
+        let expr = Parser::parse_ast_str::<Expr>("@intro").expect("Failed to parse expression");
+        assert_debug_snapshot!(expr);
+    }
+
     #[test]
     fn parses_assignment_expr() {
         let expr =
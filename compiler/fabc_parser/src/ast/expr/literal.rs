@@ -79,12 +79,14 @@ impl Parsable for Literal {
                     },
                 })
             }
-            _ => Err(Error::new(
-                CompileErrorKind::UnrecognizedLiteral {
-                    literal: parser.previous().to_string(),
-                },
-                parser.peek_token(),
-            )),
+            _ => Err(parser.string_error().unwrap_or_else(|| {
+                Error::new(
+                    CompileErrorKind::UnrecognizedLiteral {
+                        literal: parser.previous().to_string(),
+                    },
+                    parser.peek_token(),
+                )
+            })),
         }
     }
 }
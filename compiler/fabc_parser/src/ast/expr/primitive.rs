@@ -2,7 +2,12 @@ use fabc_error::{kind::CompileErrorKind, Error, Span};
 use fabc_lexer::{keywords::KeywordKind, tokens::TokenKind};
 
 use crate::{
-    ast::{decl::object::ObjectDecl, expr::Expr, stmt::block::BlockStmt, NodeInfo},
+    ast::{
+        decl::object::ObjectDecl,
+        expr::Expr,
+        stmt::{block::BlockStmt, r#return::ReturnStmt, Stmt},
+        NodeInfo,
+    },
     expect_token, Parsable, Parser,
 };
 
@@ -20,6 +25,10 @@ pub enum Primitive {
         info: NodeInfo,
         value: ObjectDecl,
     },
+    List {
+        info: NodeInfo,
+        elements: Vec<Expr>,
+    },
     Closure {
         info: NodeInfo,
         params: Vec<Primitive>,
@@ -40,6 +49,7 @@ impl Primitive {
             Primitive::Identifier { info, .. } => info,
             Primitive::Grouping { info, .. } => info,
             Primitive::Object { info, .. } => info,
+            Primitive::List { info, .. } => info,
             Primitive::Closure { info, .. } => info,
             Primitive::StoryIdentifier { info, .. } => info,
             Primitive::Context { info } => info,
@@ -47,6 +57,29 @@ impl Primitive {
     }
 }
 
+impl Primitive {
+    // `() => expr` is shorthand for `() => { return expr; }`.
+    fn expression_body(parser: &mut Parser<'_, '_>) -> Result<BlockStmt, Error> {
+        let value = Expr::parse(parser)?;
+        let span = value.info().span.clone();
+
+        Ok(BlockStmt {
+            info: NodeInfo {
+                id: parser.assign_id(),
+                span: span.clone(),
+            },
+            first_return: Some(0),
+            statements: vec![Stmt::Return(ReturnStmt {
+                info: NodeInfo {
+                    id: parser.assign_id(),
+                    span,
+                },
+                value: Some(value),
+            })],
+        })
+    }
+}
+
 impl Parsable for Primitive {
     fn parse(parser: &mut Parser<'_, '_>) -> Result<Self, Error> {
         match parser.peek() {
@@ -95,7 +128,11 @@ impl Parsable for Primitive {
                         Primitive::parse,
                     )?;
                     parser.consume(TokenKind::ArrowRight)?;
-                    let body = BlockStmt::parse(parser)?;
+                    let body = if parser.peek() == &TokenKind::LeftBrace {
+                        BlockStmt::parse(parser)?
+                    } else {
+                        Self::expression_body(parser)?
+                    };
                     let end_span = parser.end_span();
 
                     Ok(Primitive::Closure {
@@ -139,6 +176,24 @@ impl Parsable for Primitive {
                     value: object,
                 })
             }
+            TokenKind::LeftBracket => {
+                let start_span = parser.start_span();
+                let elements = parser.punctuated(
+                    TokenKind::LeftBracket,
+                    TokenKind::RightBracket,
+                    TokenKind::Comma,
+                    Expr::parse,
+                )?;
+                let end_span = parser.end_span();
+
+                Ok(Primitive::List {
+                    info: NodeInfo {
+                        id: parser.assign_id(),
+                        span: Span::from((start_span, end_span)),
+                    },
+                    elements,
+                })
+            }
             _ => Err(Error::new(
                 CompileErrorKind::UnrecognizedPrimitive {
                     primitive: parser.peek().to_string(),
@@ -182,6 +237,14 @@ mod tests {
         assert_debug_snapshot!(primitive);
     }
 
+    #[test]
+    fn parses_list_primitive() {
+        let primitive = Parser::parse_ast_str::<Primitive>("[1, \"two\", [3]]")
+            .expect("Failed to parse primitive");
+
+        assert_debug_snapshot!(primitive);
+    }
+
     #[test]
     fn parses_closure_primitive() {
         let primitive = Parser::parse_ast_str::<Primitive>("(x, y) => { x + y; }")
@@ -189,4 +252,12 @@ mod tests {
 
         assert_debug_snapshot!(primitive);
     }
+
+    #[test]
+    fn parses_expression_bodied_closure_primitive() {
+        let primitive = Parser::parse_ast_str::<Primitive>("() => context.has_key")
+            .expect("Failed to parse primitive");
+
+        assert_debug_snapshot!(primitive);
+    }
 }
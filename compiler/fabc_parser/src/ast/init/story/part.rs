@@ -2,7 +2,7 @@ use fabc_error::{Error, Span};
 use fabc_lexer::tokens::TokenKind;
 
 use crate::{
-    ast::{init::story::part::element::Element, NodeInfo},
+    ast::{decl::object::ObjectDecl, init::story::part::element::Element, NodeInfo},
     expect_token, Parsable, Parser,
 };
 
@@ -12,6 +12,8 @@ pub mod element;
 pub struct Part {
     pub info: NodeInfo,
     pub ident: String,
+    /// `# ident => { ... }` properties, including the `on_enter`/`on_exit` hooks.
+    pub properties: Option<ObjectDecl>,
     pub elements: Vec<Element>,
 }
 
@@ -24,6 +26,11 @@ impl Parsable for Part {
         let start_span = parser.start_span();
         parser.consume(TokenKind::Pound)?;
         let ident = expect_token!(parser, TokenKind::Identifier, "identifier")?;
+        let properties = if parser.r#match(&[TokenKind::ArrowRight]) {
+            Some(ObjectDecl::parse(parser)?)
+        } else {
+            None
+        };
         let elements =
             parser.invariant_parse(Element::SYNC_DELIMITERS, Part::SYNC_DELIMITERS, false);
         let end_span = parser.end_span();
@@ -34,6 +41,7 @@ impl Parsable for Part {
                 span: Span::from((start_span, end_span)),
             },
             ident,
+            properties,
             elements,
         })
     }
@@ -57,4 +65,22 @@ mod tests {
 
         assert_debug_snapshot!(part);
     }
+
+    #[test]
+    fn parses_part_with_properties() {
+        let part = Parser::parse_ast_str::<Part>(
+            r##"
+            # alley => { background: "bg_alley", on_enter: () => { context.visited = true; } }
+            * "Rain drips from the gutters."
+        "##,
+        )
+        .expect("Failed to parse part");
+
+        let properties = part.properties.expect("part properties");
+        assert_eq!(
+            properties.map.keys().collect::<Vec<_>>(),
+            ["background", "on_enter"]
+        );
+        assert_eq!(part.elements.len(), 1);
+    }
 }
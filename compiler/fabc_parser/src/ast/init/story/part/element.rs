@@ -3,12 +3,14 @@ use fabc_lexer::tokens::TokenKind;
 
 use crate::{
     ast::init::story::part::element::{
-        dialogue::DialogueElement, narration::NarrationElement, selection::SelectionElement,
+        dialogue::DialogueElement, directive::DirectiveElement, narration::NarrationElement,
+        selection::SelectionElement,
     },
     Parsable, Parser,
 };
 
 pub mod dialogue;
+pub mod directive;
 pub mod narration;
 pub mod selection;
 
@@ -17,6 +19,7 @@ pub enum Element {
     Narration(NarrationElement),
     Dialogue(DialogueElement),
     Selection(SelectionElement),
+    Directive(DirectiveElement),
 }
 
 impl Element {
@@ -24,6 +27,7 @@ impl Element {
         TokenKind::Minus,
         TokenKind::LeftBracket,
         TokenKind::Asterisk,
+        TokenKind::Bang,
     ];
 }
 
@@ -33,6 +37,7 @@ impl Parsable for Element {
             TokenKind::Minus => Ok(Element::Selection(SelectionElement::parse(parser)?)),
             TokenKind::LeftBracket => Ok(Element::Dialogue(DialogueElement::parse(parser)?)),
             TokenKind::Asterisk => Ok(Element::Narration(NarrationElement::parse(parser)?)),
+            TokenKind::Bang => Ok(Element::Directive(DirectiveElement::parse(parser)?)),
             _ => Err(Error::new(
                 CompileErrorKind::UnrecognizedElement {
                     element: parser.peek().to_string(),
@@ -59,4 +59,17 @@ mod tests {
 
         assert_debug_snapshot!(dialogue);
     }
+
+    #[test]
+    fn parses_unicode_speaker() {
+        let dialogue = Parser::parse_ast_str::<DialogueElement>(
+            r#"
+            [Zoë]
+            > "Ça va — 你好?"
+        "#,
+        )
+        .expect("Failed to parse dialogue");
+
+        assert_debug_snapshot!(dialogue);
+    }
 }
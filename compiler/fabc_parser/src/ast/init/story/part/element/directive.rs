@@ -0,0 +1,62 @@
+use fabc_error::{Error, Span};
+use fabc_lexer::tokens::TokenKind;
+
+use crate::{
+    ast::{expr::Expr, NodeInfo},
+    expect_token, Parsable, Parser,
+};
+
+#[derive(Debug, PartialEq)]
+pub struct DirectiveElement {
+    pub info: NodeInfo,
+    pub name: String,
+    pub arguments: Vec<Expr>,
+}
+
+impl Parsable for DirectiveElement {
+    fn parse(parser: &mut Parser<'_, '_>) -> Result<Self, Error> {
+        let start_span = parser.start_span();
+        parser.consume(TokenKind::Bang)?;
+        let name = expect_token!(parser, TokenKind::Identifier, "directive name")?;
+        let arguments = parser.punctuated(
+            TokenKind::LeftParen,
+            TokenKind::RightParen,
+            TokenKind::Comma,
+            |parser| Expr::parse(parser),
+        )?;
+        let end_span = parser.end_span();
+
+        Ok(DirectiveElement {
+            info: NodeInfo {
+                id: parser.assign_id(),
+                span: Span::from((start_span, end_span)),
+            },
+            name,
+            arguments,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use insta::assert_debug_snapshot;
+
+    use crate::{ast::init::story::part::element::directive::DirectiveElement, Parser};
+
+    #[test]
+    fn parses_directive_element() {
+        let directive = Parser::parse_ast_str::<DirectiveElement>("! play_music(\"rain\", 0.5)")
+            .expect("Failed to parse directive");
+
+        assert_debug_snapshot!(directive);
+    }
+
+    #[test]
+    fn parses_directive_without_arguments() {
+        let directive = Parser::parse_ast_str::<DirectiveElement>("! clear_screen()")
+            .expect("Failed to parse directive");
+
+        assert!(directive.arguments.is_empty());
+        assert_eq!(directive.name, "clear_screen");
+    }
+}
@@ -4,8 +4,9 @@ use fabc_lexer::{keywords::KeywordKind, tokens::TokenKind};
 use crate::{
     ast::{
         stmt::{
-            block::BlockStmt, expr::ExprStmt, goto::GotoStmt, r#if::IfStmt, r#let::LetStmt,
-            r#return::ReturnStmt,
+            block::BlockStmt, expr::ExprStmt, goto::GotoStmt, r#break::BreakStmt,
+            r#continue::ContinueStmt, r#for::ForStmt, r#if::IfStmt, r#let::LetStmt,
+            r#return::ReturnStmt, r#while::WhileStmt,
         },
         NodeInfo,
     },
@@ -13,11 +14,15 @@ use crate::{
 };
 
 pub mod block;
+pub mod r#break;
+pub mod r#continue;
 pub mod expr;
+pub mod r#for;
 pub mod goto;
 pub mod r#if;
 pub mod r#let;
 pub mod r#return;
+pub mod r#while;
 
 #[derive(Debug, PartialEq)]
 pub enum ElseClause {
@@ -33,6 +38,10 @@ pub enum Stmt {
     Goto(GotoStmt),
     If(IfStmt),
     Return(ReturnStmt),
+    While(WhileStmt),
+    For(ForStmt),
+    Break(BreakStmt),
+    Continue(ContinueStmt),
 }
 
 impl Stmt {
@@ -44,6 +53,10 @@ impl Stmt {
             Stmt::Goto(stmt) => &stmt.info,
             Stmt::If(stmt) => &stmt.info,
             Stmt::Return(stmt) => &stmt.info,
+            Stmt::While(stmt) => &stmt.info,
+            Stmt::For(stmt) => &stmt.info,
+            Stmt::Break(stmt) => &stmt.info,
+            Stmt::Continue(stmt) => &stmt.info,
         }
     }
 }
@@ -56,6 +69,12 @@ impl Parsable for Stmt {
             TokenKind::LeftBrace => Ok(Stmt::Block(BlockStmt::parse(parser)?)),
             TokenKind::Keyword(KeywordKind::Let) => Ok(Stmt::Let(LetStmt::parse(parser)?)),
             TokenKind::Keyword(KeywordKind::Return) => Ok(Stmt::Return(ReturnStmt::parse(parser)?)),
+            TokenKind::Keyword(KeywordKind::While) => Ok(Stmt::While(WhileStmt::parse(parser)?)),
+            TokenKind::Keyword(KeywordKind::For) => Ok(Stmt::For(ForStmt::parse(parser)?)),
+            TokenKind::Keyword(KeywordKind::Break) => Ok(Stmt::Break(BreakStmt::parse(parser)?)),
+            TokenKind::Keyword(KeywordKind::Continue) => {
+                Ok(Stmt::Continue(ContinueStmt::parse(parser)?))
+            }
             _ => Ok(Stmt::Expr(ExprStmt::parse(parser)?)),
         }
     }
@@ -33,7 +33,19 @@ impl Parsable for BlockStmt {
                             }
                             stmt_vec.push(stmt);
                         }
-                        Err(err) => parser.push_error(err),
+                        Err(err) => {
+                            parser.push_error(err);
+                            // Skip the rest of the broken statement so the loop makes progress.
+                            while !parser.is_terminated()
+                                && !matches!(
+                                    parser.peek(),
+                                    TokenKind::Semicolon | TokenKind::RightBrace
+                                )
+                            {
+                                parser.advance();
+                            }
+                            parser.r#match(&[TokenKind::Semicolon]);
+                        }
                     }
                     idx_count += 1;
                 }
@@ -0,0 +1,27 @@
+use fabc_error::{Error, Span};
+use fabc_lexer::{keywords::KeywordKind, tokens::TokenKind};
+
+use crate::{ast::NodeInfo, Parsable, Parser};
+
+#[derive(Debug, PartialEq)]
+pub struct BreakStmt {
+    pub info: NodeInfo,
+}
+
+impl Parsable for BreakStmt {
+    fn parse(parser: &mut Parser<'_, '_>) -> Result<Self, Error> {
+        let start_span = parser.start_span();
+
+        parser.consume(TokenKind::Keyword(KeywordKind::Break))?;
+        parser.consume(TokenKind::Semicolon)?;
+
+        let end_span = parser.end_span();
+
+        Ok(BreakStmt {
+            info: NodeInfo {
+                id: parser.assign_id(),
+                span: Span::from((start_span, end_span)),
+            },
+        })
+    }
+}
@@ -0,0 +1,27 @@
+use fabc_error::{Error, Span};
+use fabc_lexer::{keywords::KeywordKind, tokens::TokenKind};
+
+use crate::{ast::NodeInfo, Parsable, Parser};
+
+#[derive(Debug, PartialEq)]
+pub struct ContinueStmt {
+    pub info: NodeInfo,
+}
+
+impl Parsable for ContinueStmt {
+    fn parse(parser: &mut Parser<'_, '_>) -> Result<Self, Error> {
+        let start_span = parser.start_span();
+
+        parser.consume(TokenKind::Keyword(KeywordKind::Continue))?;
+        parser.consume(TokenKind::Semicolon)?;
+
+        let end_span = parser.end_span();
+
+        Ok(ContinueStmt {
+            info: NodeInfo {
+                id: parser.assign_id(),
+                span: Span::from((start_span, end_span)),
+            },
+        })
+    }
+}
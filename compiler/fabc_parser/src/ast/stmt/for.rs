@@ -0,0 +1,96 @@
+use fabc_error::{Error, Span};
+use fabc_lexer::{keywords::KeywordKind, tokens::TokenKind};
+
+use crate::{
+    ast::{
+        expr::Expr,
+        stmt::{block::BlockStmt, expr::ExprStmt, r#let::LetStmt, Stmt},
+        NodeInfo,
+    },
+    Parsable, Parser,
+};
+
+#[derive(Debug, PartialEq)]
+pub struct ForStmt {
+    pub info: NodeInfo,
+    pub initializer: Option<Box<Stmt>>,
+    pub condition: Option<Expr>,
+    pub increment: Option<Expr>,
+    pub body: Box<BlockStmt>,
+}
+
+impl Parsable for ForStmt {
+    fn parse(parser: &mut Parser<'_, '_>) -> Result<Self, Error> {
+        let start_span = parser.start_span();
+
+        parser.consume(TokenKind::Keyword(KeywordKind::For))?;
+
+        let (initializer, condition, increment) =
+            parser.enclosed(TokenKind::LeftParen, TokenKind::RightParen, |parser| {
+                let initializer = match parser.peek() {
+                    TokenKind::Semicolon => {
+                        parser.consume(TokenKind::Semicolon)?;
+                        None
+                    }
+                    TokenKind::Keyword(KeywordKind::Let) => {
+                        Some(Box::new(Stmt::Let(LetStmt::parse(parser)?)))
+                    }
+                    _ => Some(Box::new(Stmt::Expr(ExprStmt::parse(parser)?))),
+                };
+
+                let condition = if parser.peek() != &TokenKind::Semicolon {
+                    Some(Expr::parse(parser)?)
+                } else {
+                    None
+                };
+                parser.consume(TokenKind::Semicolon)?;
+
+                let increment = if parser.peek() != &TokenKind::RightParen {
+                    Some(Expr::parse(parser)?)
+                } else {
+                    None
+                };
+
+                Ok((initializer, condition, increment))
+            })?;
+
+        let body = Box::new(BlockStmt::parse(parser)?);
+
+        let end_span = parser.end_span();
+
+        Ok(ForStmt {
+            info: NodeInfo {
+                id: parser.assign_id(),
+                span: Span::from((start_span, end_span)),
+            },
+            initializer,
+            condition,
+            increment,
+            body,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use insta::assert_debug_snapshot;
+
+    use crate::{ast::stmt::r#for::ForStmt, Parser};
+
+    #[test]
+    fn parses_for_stmt() {
+        let for_stmt =
+            Parser::parse_ast_str::<ForStmt>("for (let i = 0; i < 3; i = i + 1) { continue; }")
+                .expect("Failed to parse for statement");
+
+        assert_debug_snapshot!(for_stmt);
+    }
+
+    #[test]
+    fn parses_for_stmt_with_empty_clauses() {
+        let for_stmt = Parser::parse_ast_str::<ForStmt>("for (;;) { break; }")
+            .expect("Failed to parse for statement");
+
+        assert_debug_snapshot!(for_stmt);
+    }
+}
@@ -33,7 +33,7 @@ impl Parsable for IfStmt {
         let then_branch = Box::new(BlockStmt::parse(parser)?);
 
         let else_branch = if parser.r#match(&[TokenKind::Keyword(KeywordKind::Else)]) {
-            if parser.r#match(&[TokenKind::Keyword(KeywordKind::If)]) {
+            if parser.peek() == &TokenKind::Keyword(KeywordKind::If) {
                 Some(ElseClause::If(Box::new(IfStmt::parse(parser)?)))
             } else {
                 Some(ElseClause::Block(Box::new(BlockStmt::parse(parser)?)))
@@ -77,4 +77,12 @@ mod tests {
 
         assert_debug_snapshot!(if_stmt);
     }
+
+    #[test]
+    fn parses_if_stmt_with_else_if() {
+        let if_stmt = Parser::parse_ast_str::<IfStmt>("if (false) { } else if (true) { }")
+            .expect("Failed to parse if statement");
+
+        assert_debug_snapshot!(if_stmt);
+    }
 }
@@ -0,0 +1,54 @@
+use fabc_error::{Error, Span};
+use fabc_lexer::{keywords::KeywordKind, tokens::TokenKind};
+
+use crate::{
+    ast::{expr::Expr, stmt::block::BlockStmt, NodeInfo},
+    Parsable, Parser,
+};
+
+#[derive(Debug, PartialEq)]
+pub struct WhileStmt {
+    pub info: NodeInfo,
+    pub condition: Expr,
+    pub body: Box<BlockStmt>,
+}
+
+impl Parsable for WhileStmt {
+    fn parse(parser: &mut Parser<'_, '_>) -> Result<Self, Error> {
+        let start_span = parser.start_span();
+
+        parser.consume(TokenKind::Keyword(KeywordKind::While))?;
+
+        let condition = parser.enclosed(TokenKind::LeftParen, TokenKind::RightParen, |parser| {
+            Expr::parse(parser)
+        })?;
+
+        let body = Box::new(BlockStmt::parse(parser)?);
+
+        let end_span = parser.end_span();
+
+        Ok(WhileStmt {
+            info: NodeInfo {
+                id: parser.assign_id(),
+                span: Span::from((start_span, end_span)),
+            },
+            condition,
+            body,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use insta::assert_debug_snapshot;
+
+    use crate::{ast::stmt::r#while::WhileStmt, Parser};
+
+    #[test]
+    fn parses_while_stmt() {
+        let while_stmt = Parser::parse_ast_str::<WhileStmt>("while (x < 3) { x = x + 1; }")
+            .expect("Failed to parse while statement");
+
+        assert_debug_snapshot!(while_stmt);
+    }
+}
@@ -2,7 +2,7 @@ use std::slice;
 
 use fabc_error::{kind::CompileErrorKind, Error, LineCol};
 use fabc_lexer::{
-    tokens::{Token, TokenKind},
+    tokens::{ErrorKind, Token, TokenKind},
     Lexer,
 };
 
@@ -83,6 +83,30 @@ impl<'src, 'tok> Parser<'src, 'tok> {
         T::parse(&mut parser)
     }
 
+    /// Parses a separately lexed token stream, such as a quote interpolation, sharing node ids
+    /// and collected errors with this parser.
+    pub(crate) fn nested<'s, 't, F, T>(
+        &mut self,
+        tokens: &'t [Token<'s>],
+        parser_fn: F,
+    ) -> Result<T, Error>
+    where
+        F: FnOnce(&mut Parser<'s, 't>) -> Result<T, Error>,
+    {
+        let mut parser = Parser {
+            tokens,
+            current: 0,
+            save: None,
+            id_counter: self.id_counter,
+            errors: Vec::new(),
+        };
+
+        let result = parser_fn(&mut parser);
+        self.id_counter = parser.id_counter;
+        self.errors.append(&mut parser.errors);
+        result
+    }
+
     pub(crate) fn start_span(&self) -> LineCol {
         LineCol::from_token(self.peek_token())
     }
@@ -91,6 +115,18 @@ impl<'src, 'tok> Parser<'src, 'tok> {
         LineCol::from_token_end(self.previous_token())
     }
 
+    /// The error behind a string literal the lexer could not decode, if the next token is one.
+    pub(crate) fn string_error(&self) -> Option<Error> {
+        let kind = match self.peek() {
+            TokenKind::Error(ErrorKind::InvalidEscape(escape)) => CompileErrorKind::InvalidEscape {
+                escape: escape.clone(),
+            },
+            TokenKind::Error(ErrorKind::UnterminatedString) => CompileErrorKind::UnterminatedString,
+            _ => return None,
+        };
+        Some(Error::new(kind, self.peek_token()))
+    }
+
     pub(crate) fn push_error(&mut self, error: Error) {
         self.errors.push(error);
     }
@@ -128,8 +164,9 @@ impl<'src, 'tok> Parser<'src, 'tok> {
         &self.tokens[self.current].kind
     }
 
+    /// The next token, or the trailing `EoF` once the parser has consumed everything.
     pub(crate) fn peek_token(&self) -> &Token<'src> {
-        &self.tokens[self.current]
+        &self.tokens[self.current.min(self.tokens.len() - 1)]
     }
 
     pub(crate) fn advance(&mut self) -> &TokenKind<'src> {
@@ -287,4 +324,21 @@ mod tests {
 
         assert!(story.errors.is_empty());
     }
+
+    #[test]
+    fn reports_unclosed_object_at_end_of_input() {
+        let result = Parser::parse_str("Story {}\n# intro\n* \"Hi\" {\n");
+
+        assert!(!result.errors.is_empty());
+    }
+
+    #[test]
+    fn recovers_from_broken_statements_in_blocks() {
+        let story = Parser::parse_str(
+            "Story {}\n# intro\n* \"Hi\" { next: () => { let = 1; ) ); goto intro; } }\n# outro\n* \"Bye\"",
+        );
+
+        assert_eq!(story.errors.len(), 2);
+        assert_eq!(story.result.len(), 1);
+    }
 }
@@ -14,7 +14,9 @@ macro_rules! expect_token {
         }
     }};
     ($parser:expr, $variant:path, $expected:expr) => {{
-        if let $variant(value) = $parser.advance() {
+        if let Some(error) = $parser.string_error() {
+            Err(error)
+        } else if let $variant(value) = $parser.advance() {
             Ok(value.to_string())
         } else {
             Err(fabc_error::Error::new(
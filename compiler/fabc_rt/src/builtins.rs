@@ -0,0 +1,201 @@
+use std::collections::BTreeSet;
+
+use fabc_ir::{BuiltinSignature, BUILTINS};
+
+use super::{
+    error::{Result, RuntimeError},
+    rng::StoryRng,
+    scope::Scope,
+    value::Value,
+    visits::VisitView,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Builtin {
+    StrLen,
+    StrUpper,
+    NumFloor,
+    NumClamp,
+    NumToString,
+    ListPush,
+    ObjHas,
+    ObjKeys,
+    Random,
+    RandomInt,
+    Pick,
+    Visits,
+    Seen,
+}
+
+impl Builtin {
+    pub const ALL: [Builtin; BUILTINS.len()] = [
+        Builtin::StrLen,
+        Builtin::StrUpper,
+        Builtin::NumFloor,
+        Builtin::NumClamp,
+        Builtin::NumToString,
+        Builtin::ListPush,
+        Builtin::ObjHas,
+        Builtin::ObjKeys,
+        Builtin::Random,
+        Builtin::RandomInt,
+        Builtin::Pick,
+        Builtin::Visits,
+        Builtin::Seen,
+    ];
+
+    /// The entry in the shared builtin table; its order matches [`Builtin::ALL`].
+    pub fn signature(self) -> &'static BuiltinSignature {
+        &BUILTINS[self as usize]
+    }
+
+    pub fn namespace(self) -> Option<&'static str> {
+        self.signature().namespace
+    }
+
+    pub fn name(self) -> &'static str {
+        self.signature().name
+    }
+
+    pub fn path(self) -> String {
+        match self.namespace() {
+            Some(namespace) => format!("{namespace}.{}", self.name()),
+            None => self.name().to_string(),
+        }
+    }
+
+    pub fn arity(self) -> usize {
+        self.signature().parameters.len()
+    }
+
+    /// The builtin reached as `namespace.name`.
+    pub(crate) fn in_namespace(namespace: &str, name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|builtin| builtin.namespace() == Some(namespace) && builtin.name() == name)
+    }
+
+    pub(crate) fn call(
+        self,
+        args: &[Value],
+        rng: &mut StoryRng,
+        visits: VisitView<'_>,
+    ) -> Result<Value> {
+        if args.len() != self.arity() {
+            return Err(RuntimeError::ArityMismatch {
+                expected: self.arity(),
+                got: args.len(),
+            });
+        }
+
+        Ok(match self {
+            Builtin::StrLen => Value::Number(self.string_arg(&args[0])?.chars().count() as f64),
+            Builtin::StrUpper => Value::String(self.string_arg(&args[0])?.to_uppercase()),
+            Builtin::NumFloor => Value::Number(args[0].to_number()?.floor()),
+            Builtin::NumClamp => {
+                let (min, max) = (args[1].to_number()?, args[2].to_number()?);
+                Value::Number(args[0].to_number()?.max(min).min(max))
+            }
+            Builtin::NumToString => Value::String(Value::Number(args[0].to_number()?).to_string()),
+            Builtin::ListPush => match &args[0] {
+                Value::List(list) => {
+                    list.borrow_mut().push(args[1].clone());
+                    Value::None
+                }
+                other => return Err(self.invalid_argument("List", other)),
+            },
+            Builtin::ObjHas => match &args[0] {
+                Value::Object(object) => {
+                    Value::Boolean(object.borrow().contains_key(&args[1].to_member_key()?))
+                }
+                other => return Err(self.invalid_argument("Object", other)),
+            },
+            Builtin::ObjKeys => match &args[0] {
+                Value::Object(object) => Value::list(
+                    object
+                        .borrow()
+                        .keys()
+                        .map(|key| Value::String(key.clone()))
+                        .collect(),
+                ),
+                other => return Err(self.invalid_argument("Object", other)),
+            },
+            Builtin::Random => Value::Number(rng.next_f64()),
+            Builtin::RandomInt => {
+                let (min, max) = (args[0].to_number()?.ceil(), args[1].to_number()?.floor());
+                if min > max {
+                    return Err(RuntimeError::InvalidRandomRange {
+                        min: args[0].to_string(),
+                        max: args[1].to_string(),
+                    });
+                }
+                Value::Number(min + (rng.next_f64() * (max - min + 1.0)).floor())
+            }
+            Builtin::Pick => match &args[0] {
+                Value::List(list) => {
+                    let list = list.borrow();
+                    if list.is_empty() {
+                        Value::None
+                    } else {
+                        list[(rng.next_f64() * list.len() as f64) as usize].clone()
+                    }
+                }
+                other => return Err(self.invalid_argument("List", other)),
+            },
+            Builtin::Visits => match visits.visits(&args[0]) {
+                Some(count) => count,
+                None => return Err(self.invalid_argument("StoryRef", &args[0])),
+            },
+            Builtin::Seen => visits.seen(),
+        })
+    }
+
+    fn string_arg(self, value: &Value) -> Result<&str> {
+        match value {
+            Value::String(value) => Ok(value),
+            other => Err(self.invalid_argument("String", other)),
+        }
+    }
+
+    fn invalid_argument(self, expected: &'static str, found: &Value) -> RuntimeError {
+        RuntimeError::InvalidBuiltinArgument {
+            builtin: self.path(),
+            expected,
+            found: found.kind_name().to_string(),
+        }
+    }
+}
+
+// Namespaces live in the global scope so a local `let list = ...` simply shadows them.
+pub(crate) fn define_builtins(globals: &Scope) {
+    let mut namespaces = BTreeSet::new();
+    for builtin in Builtin::ALL {
+        match builtin.namespace() {
+            Some(namespace) => {
+                namespaces.insert(namespace);
+            }
+            None => globals.define(builtin.name(), Value::Builtin(builtin)),
+        }
+    }
+
+    for namespace in namespaces {
+        globals.define(namespace, Value::Namespace(namespace));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fabc_ir::BUILTINS;
+
+    use super::Builtin;
+
+    #[test]
+    fn builtins_follow_the_shared_table() {
+        for (index, builtin) in Builtin::ALL.into_iter().enumerate() {
+            assert_eq!(builtin as usize, index);
+            assert_eq!(builtin.signature(), &BUILTINS[index]);
+        }
+        assert_eq!(Builtin::ALL[0].path(), "str.len");
+        assert_eq!(Builtin::NumClamp.arity(), 3);
+    }
+}
@@ -1,15 +1,17 @@
 use std::{
-    cell::RefCell,
+    cell::{Cell, RefCell},
     collections::BTreeMap,
     ffi::c_void,
     fmt::{Debug, Formatter, Result as FmtResult},
-    slice, str,
+    mem, slice, str,
 };
 
 use fabc_ir::FunctionId;
 
 use super::{
-    ClosureValue, CompiledFunctionHost, CompiledInvocationResult, ObjectRef, Scope, Value,
+    rng::StoryRng,
+    visits::{VisitLog, VisitView},
+    Builtin, ClosureValue, CompiledFunctionHost, CompiledInvocationResult, ObjectRef, Scope, Value,
 };
 
 type RawPtr = *mut c_void;
@@ -99,6 +101,8 @@ impl Debug for LinkedCompiledFunctionHost {
 thread_local! {
     static ACTIVE_HOST: RefCell<Option<ActiveHostDispatch>> = const { RefCell::new(None) };
     static LAST_ERROR: RefCell<Option<String>> = const { RefCell::new(None) };
+    static ACTIVE_RNG: Cell<Option<StoryRng>> = const { Cell::new(None) };
+    static ACTIVE_VISITS: RefCell<Option<(VisitLog, Option<usize>)>> = const { RefCell::new(None) };
 }
 
 enum NativeOutcome {
@@ -189,7 +193,7 @@ pub fn invoke_compiled_with_active_host<T: CompiledFunctionHost>(
     })
 }
 
-pub fn runtime_symbols() -> [RuntimeSymbol; 36] {
+pub fn runtime_symbols() -> [RuntimeSymbol; 38] {
     [
         (
             "fabc_rt_value_none",
@@ -223,6 +227,14 @@ pub fn runtime_symbols() -> [RuntimeSymbol; 36] {
             "fabc_rt_object_insert",
             runtime_addr(fabc_rt_object_insert as *const ()),
         ),
+        (
+            "fabc_rt_list_new",
+            runtime_addr(fabc_rt_list_new as *const ()),
+        ),
+        (
+            "fabc_rt_list_push",
+            runtime_addr(fabc_rt_list_push as *const ()),
+        ),
         (
             "fabc_rt_env_child",
             runtime_addr(fabc_rt_env_child as *const ()),
@@ -380,6 +392,51 @@ fn set_last_error(error: String) {
     });
 }
 
+// Native code has no handle on the `StoryMachine`, so its generator is lent out for the
+// duration of a compiled call and written back afterwards.
+pub(crate) fn with_active_rng<T>(rng: &mut StoryRng, f: impl FnOnce() -> T) -> T {
+    let previous = ACTIVE_RNG.with(|slot| slot.replace(Some(*rng)));
+    let result = f();
+    if let Some(state) = ACTIVE_RNG.with(|slot| slot.replace(previous)) {
+        *rng = state;
+    }
+    result
+}
+
+// The visit log is lent the same way, together with the quote being evaluated, for `visits`
+// and `seen`.
+pub(crate) fn with_active_visits<T>(
+    log: &mut VisitLog,
+    quote: Option<usize>,
+    f: impl FnOnce() -> T,
+) -> T {
+    let previous = ACTIVE_VISITS.with(|slot| slot.replace(Some((mem::take(log), quote))));
+    let result = f();
+    if let Some((lent, _)) = ACTIVE_VISITS.with(|slot| slot.replace(previous)) {
+        *log = lent;
+    }
+    result
+}
+
+fn call_builtin(builtin: Builtin, args: &[Value]) -> Result<Value, String> {
+    ACTIVE_RNG.with(|slot| {
+        let mut rng = slot.get().unwrap_or_else(StoryRng::from_entropy);
+        let result = ACTIVE_VISITS.with(|visits| {
+            let visits = visits.borrow();
+            let empty = VisitLog::default();
+            let (log, quote) = match visits.as_ref() {
+                Some((log, quote)) => (log, *quote),
+                None => (&empty, None),
+            };
+            builtin.call(args, &mut rng, VisitView { log, quote })
+        });
+        if slot.get().is_some() {
+            slot.set(Some(rng));
+        }
+        result.map_err(|error| error.to_string())
+    })
+}
+
 fn with_active_host<T>(
     f: impl FnOnce(&dyn CompiledFunctionHost) -> Result<T, String>,
 ) -> Result<T, String> {
@@ -511,6 +568,20 @@ pub unsafe extern "C" fn fabc_rt_object_insert(
     }
 }
 
+#[unsafe(no_mangle)]
+pub extern "C" fn fabc_rt_list_new() -> RawPtr {
+    box_value(Value::list(Vec::new()))
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn fabc_rt_list_push(list: RawPtr, value: RawPtr) {
+    let value = take_value(value);
+    match &mut *(list as *mut Value) {
+        Value::List(list) => list.borrow_mut().push(value),
+        other => set_last_error(format!("cannot push element into `{}`", other.kind_name())),
+    }
+}
+
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn fabc_rt_env_child(parent: RawPtr) -> RawPtr {
     Box::into_raw(Box::new(clone_scope(parent).child())) as RawPtr
@@ -570,8 +641,8 @@ pub unsafe extern "C" fn fabc_rt_env_assign(
     };
 
     let value = take_value(value);
-    if !clone_scope(frame).assign(&name, value) {
-        set_last_error(format!("undefined variable `{name}`"));
+    if let Err(error) = clone_scope(frame).assign(&name, value) {
+        set_last_error(error.to_string());
     }
 }
 
@@ -579,27 +650,11 @@ pub unsafe extern "C" fn fabc_rt_env_assign(
 pub unsafe extern "C" fn fabc_rt_member_get(base: RawPtr, key: RawPtr) -> RawPtr {
     let base = take_value(base);
     let key = take_value(key);
-    let key = match key.to_member_key() {
-        Ok(key) => key,
+
+    match base.get_member(&key) {
+        Ok(value) => box_value(value),
         Err(error) => {
             set_last_error(error.to_string());
-            return box_value(Value::None);
-        }
-    };
-
-    match base {
-        Value::Object(object) => match object.borrow().get(&key).cloned() {
-            Some(value) => box_value(value),
-            None => {
-                set_last_error(format!("cannot read member `{key}` from `Object`"));
-                box_value(Value::None)
-            }
-        },
-        other => {
-            set_last_error(format!(
-                "cannot read member `{key}` from `{}`",
-                other.kind_name()
-            ));
             box_value(Value::None)
         }
     }
@@ -607,23 +662,11 @@ pub unsafe extern "C" fn fabc_rt_member_get(base: RawPtr, key: RawPtr) -> RawPtr
 
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn fabc_rt_member_assign(container: RawPtr, key: RawPtr, value: RawPtr) {
-    let key = match take_value(key).to_member_key() {
-        Ok(key) => key,
-        Err(error) => {
-            set_last_error(error.to_string());
-            return;
-        }
-    };
+    let key = take_value(key);
     let value = take_value(value);
 
-    match &mut *(container as *mut Value) {
-        Value::Object(object) => {
-            object.borrow_mut().insert(key, value);
-        }
-        other => set_last_error(format!(
-            "cannot assign member `{key}` on `{}`",
-            other.kind_name()
-        )),
+    if let Err(error) = (*(container as *mut Value)).set_member(&key, value) {
+        set_last_error(error.to_string());
     }
 }
 
@@ -647,6 +690,13 @@ pub unsafe extern "C" fn fabc_rt_call(
             function_id,
             captured,
         }) => with_active_host(|host| host.invoke_function(function_id, captured, context, args)),
+        Value::Builtin(builtin) => {
+            call_builtin(builtin, &args).map(|value| CompiledInvocationResult { value, goto: None })
+        }
+        Value::HostFunction(function) => function
+            .call(&args)
+            .map(|value| CompiledInvocationResult { value, goto: None })
+            .map_err(|error| error.to_string()),
         other => Err(format!("invalid callable value `{}`", other.kind_name())),
     };
 
@@ -817,8 +867,8 @@ mod tests {
     use std::{collections::BTreeMap, rc::Rc};
 
     use fabc_ir::{
-        Block, DialogueSpec, FunctionSpec, PartSpec, QuoteSpec, SelectionSpec, StepSpec,
-        StoryProgram,
+        Block, DialogueSpec, Expr, FunctionSpec, Literal, MemberSegment, PartSpec, QuoteSpec,
+        SelectionSpec, StepSpec, Stmt, StoryProgram, TextSegment,
     };
 
     use super::*;
@@ -932,6 +982,240 @@ mod tests {
         );
     }
 
+    #[test]
+    fn linked_host_choices_can_be_rewound() {
+        let host = Rc::new(LinkedCompiledFunctionHost::new(&[
+            LinkedFunctionDescriptor {
+                id: 0,
+                symbol: "fabc_fn_0",
+                params: NO_PARAMS,
+                function: compiled_sum_and_goto,
+            },
+        ]));
+        let program = story_program_with_selection(
+            "Hero",
+            "Hello there!",
+            "Hi!",
+            "Villain",
+            "I've been expecting you.",
+            vec![function_spec(0)],
+        );
+
+        let mut machine = StoryMachine::with_compiled_executor(program, BTreeMap::new(), host)
+            .expect("build story machine");
+
+        machine.start().expect("start compiled story");
+        machine.advance().expect("reach selection");
+        machine.choose(0).expect("resolve compiled choice");
+        assert_eq!(machine.context_value("total"), Some(Value::Number(30.0)));
+
+        let event = machine.rewind(1).expect("rewind compiled choice");
+        let StoryEvent::Selection(selection) = event else {
+            panic!("expected selection event");
+        };
+        assert_eq!(selection.choices[0].text, "Hi!");
+        assert_eq!(machine.context_value("total"), None);
+
+        machine.choose(0).expect("replay compiled choice");
+        assert_eq!(machine.context_value("total"), Some(Value::Number(30.0)));
+    }
+
+    #[test]
+    fn linked_host_calls_builtins_from_global_scope() {
+        let host = Rc::new(LinkedCompiledFunctionHost::new(&[
+            LinkedFunctionDescriptor {
+                id: 0,
+                symbol: "fabc_fn_0",
+                params: NO_PARAMS,
+                function: compiled_builtin_len,
+            },
+        ]));
+        let program = story_program_with_selection(
+            "Hero",
+            "Hello there!",
+            "Measure",
+            "Villain",
+            "I've been expecting you.",
+            vec![function_spec(0)],
+        );
+
+        let mut machine = StoryMachine::with_compiled_executor(program, BTreeMap::new(), host)
+            .expect("build story machine");
+
+        machine.start().expect("start compiled story");
+        machine.advance().expect("reach selection");
+        machine.choose(0).expect("resolve compiled choice");
+        assert_eq!(machine.context_value("len"), Some(Value::Number(5.0)));
+    }
+
+    #[test]
+    fn linked_host_calls_registered_host_functions() {
+        let host = Rc::new(LinkedCompiledFunctionHost::new(&[
+            LinkedFunctionDescriptor {
+                id: 0,
+                symbol: "fabc_fn_0",
+                params: NO_PARAMS,
+                function: compiled_give_item,
+            },
+        ]));
+        let program = story_program_with_selection(
+            "Hero",
+            "Hello there!",
+            "Take the sword",
+            "Villain",
+            "I've been expecting you.",
+            vec![function_spec(0)],
+        );
+
+        let mut machine = StoryMachine::with_compiled_executor(program, BTreeMap::new(), host)
+            .expect("build story machine");
+        let inventory = Rc::new(RefCell::new(Vec::new()));
+        let given = Rc::clone(&inventory);
+        machine.register_function("give_item", move |args| {
+            given.borrow_mut().push(args[0].to_string());
+            Ok(Value::Number(given.borrow().len() as f64))
+        });
+
+        machine.start().expect("start compiled story");
+        machine.advance().expect("reach selection");
+        machine.choose(0).expect("resolve compiled choice");
+        assert_eq!(*inventory.borrow(), vec!["sword".to_string()]);
+        assert_eq!(machine.context_value("items"), Some(Value::Number(1.0)));
+    }
+
+    #[test]
+    fn linked_host_reads_visits_and_seen_quotes() {
+        let host = Rc::new(LinkedCompiledFunctionHost::new(&[
+            LinkedFunctionDescriptor {
+                id: 0,
+                symbol: "fabc_fn_0",
+                params: NO_PARAMS,
+                function: compiled_visits,
+            },
+        ]));
+        let program = story_program_with_selection(
+            "Hero",
+            "Hello there!",
+            "Look around",
+            "Villain",
+            "I've been expecting you.",
+            vec![function_spec(0)],
+        );
+
+        let mut machine = StoryMachine::with_compiled_executor(program, BTreeMap::new(), host)
+            .expect("build story machine");
+
+        machine.start().expect("start compiled story");
+        machine.advance().expect("reach selection");
+        machine.choose(0).expect("resolve compiled choice");
+        assert_eq!(machine.context_value("visits"), Some(Value::Number(1.0)));
+        assert_eq!(machine.context_value("seen"), Some(Value::Boolean(false)));
+        assert!(machine.has_seen(1));
+        assert_eq!(machine.visits("part_1"), 1);
+    }
+
+    #[test]
+    fn linked_host_rolls_match_interpreted_rolls_for_the_same_seed() {
+        let host = Rc::new(LinkedCompiledFunctionHost::new(&[
+            LinkedFunctionDescriptor {
+                id: 0,
+                symbol: "fabc_fn_0",
+                params: NO_PARAMS,
+                function: compiled_roll,
+            },
+        ]));
+        let program = story_program_with_selection(
+            "Hero",
+            "Hello there!",
+            "Roll",
+            "Villain",
+            "I've been expecting you.",
+            vec![function_spec(0)],
+        );
+        let mut interpreted_program = program.clone();
+        interpreted_program.functions[0].body = Block {
+            statements: vec![Stmt::Expr(Expr::Assignment {
+                target: Box::new(Expr::MemberAccess {
+                    base: Box::new(Expr::Context),
+                    members: vec![MemberSegment::Key("roll".to_string())],
+                }),
+                value: Box::new(Expr::Call {
+                    callee: Box::new(Expr::Identifier("random_int".to_string())),
+                    arguments: vec![
+                        Expr::Literal(Literal::Number(1.0)),
+                        Expr::Literal(Literal::Number(20.0)),
+                    ],
+                }),
+            })],
+        };
+
+        let mut native = StoryMachine::with_compiled_executor(program, BTreeMap::new(), host)
+            .expect("build story machine")
+            .seeded(42);
+        let mut interpreted = StoryMachine::with_seed(interpreted_program, BTreeMap::new(), 42)
+            .expect("build interpreted machine");
+
+        for machine in [&mut native, &mut interpreted] {
+            machine.start().expect("start story");
+            machine.advance().expect("reach selection");
+            machine.choose(0).expect("roll");
+        }
+
+        assert!(native.context_value("roll").is_some());
+        assert_eq!(
+            native.context_value("roll"),
+            interpreted.context_value("roll")
+        );
+        assert_eq!(
+            native.snapshot().expect("native snapshot").rng_state,
+            interpreted
+                .snapshot()
+                .expect("interpreted snapshot")
+                .rng_state
+        );
+    }
+
+    #[test]
+    fn linked_host_evaluates_quote_interpolations() {
+        let host = Rc::new(LinkedCompiledFunctionHost::new(&[
+            LinkedFunctionDescriptor {
+                id: 1,
+                symbol: "fabc_fn_1",
+                params: NO_PARAMS,
+                function: compiled_gold,
+            },
+        ]));
+        let mut program = story_program_with_selection(
+            "Hero",
+            "Gold: {context.gold}",
+            "Continue",
+            "Villain",
+            "I've been expecting you.",
+            vec![function_spec(0), function_spec(1)],
+        );
+        let StepSpec::Dialogue(dialogue) = &mut program.parts[0].steps[0] else {
+            panic!("expected a dialogue step");
+        };
+        dialogue.quote.segments = vec![
+            TextSegment::Text("Gold: ".to_string()),
+            TextSegment::Interpolation(1),
+        ];
+
+        let mut context = BTreeMap::new();
+        context.insert("gold".to_string(), Value::Number(7.0));
+        let mut machine = StoryMachine::with_compiled_executor(program, context, host)
+            .expect("build story machine");
+
+        assert_eq!(
+            machine.start().expect("start story"),
+            StoryEvent::Dialogue(DialogueView {
+                speaker: "Hero".to_string(),
+                text: "Gold: 7".to_string(),
+                properties: BTreeMap::new(),
+            })
+        );
+    }
+
     fn story_program_with_selection(
         intro_speaker: &str,
         intro_text: &str,
@@ -946,35 +1230,53 @@ mod tests {
             parts: vec![
                 PartSpec {
                     id: "part_1".to_string(),
+                    properties: BTreeMap::new(),
+                    on_enter: None,
+                    on_exit: None,
                     steps: vec![
                         StepSpec::Dialogue(DialogueSpec {
                             speaker: intro_speaker.to_string(),
                             quote: QuoteSpec {
                                 node_id: 0,
                                 text: intro_text.to_string(),
+                                segments: Vec::new(),
                                 properties: BTreeMap::new(),
                                 next_action: None,
+                                guard: None,
+                                once: false,
+                                fallback: false,
                             },
                         }),
                         StepSpec::Selection(SelectionSpec {
                             choices: vec![QuoteSpec {
                                 node_id: 1,
                                 text: choice_text.to_string(),
+                                segments: Vec::new(),
                                 properties: BTreeMap::new(),
                                 next_action: Some(0),
+                                guard: None,
+                                once: false,
+                                fallback: false,
                             }],
                         }),
                     ],
                 },
                 PartSpec {
                     id: "part_2".to_string(),
+                    properties: BTreeMap::new(),
+                    on_enter: None,
+                    on_exit: None,
                     steps: vec![StepSpec::Dialogue(DialogueSpec {
                         speaker: outro_speaker.to_string(),
                         quote: QuoteSpec {
                             node_id: 2,
                             text: outro_text.to_string(),
+                            segments: Vec::new(),
                             properties: BTreeMap::new(),
                             next_action: None,
+                            guard: None,
+                            once: false,
+                            fallback: false,
                         },
                     })],
                 },
@@ -1043,6 +1345,86 @@ mod tests {
         unsafe { fabc_rt_outcome_goto(target) }
     }
 
+    unsafe extern "C" fn compiled_builtin_len(frame: RawPtr, context: RawPtr) -> RawPtr {
+        let namespace = unsafe { fabc_rt_env_load(frame, "str".as_ptr().cast(), 3) };
+        let callee = unsafe { fabc_rt_member_get(namespace, string_value("len")) };
+        let mut args = [unsafe { string_value("hello") }];
+        let outcome = unsafe { fabc_rt_call(frame, context, callee, args.as_mut_ptr(), 1) };
+        let len = unsafe { fabc_rt_outcome_into_value(outcome) };
+
+        let context_value = unsafe { fabc_rt_context_value(context) };
+        unsafe { fabc_rt_member_assign(context_value, string_value("len"), len) };
+
+        // SAFETY: `context_value` originated from `fabc_rt_context_value` in this function.
+        unsafe {
+            drop(Box::from_raw(context_value as *mut Value));
+        }
+
+        fabc_rt_outcome_continue()
+    }
+
+    unsafe extern "C" fn compiled_give_item(frame: RawPtr, context: RawPtr) -> RawPtr {
+        let callee = unsafe { fabc_rt_env_load(frame, "give_item".as_ptr().cast(), 9) };
+        let mut args = [unsafe { string_value("sword") }];
+        let outcome = unsafe { fabc_rt_call(frame, context, callee, args.as_mut_ptr(), 1) };
+        let count = unsafe { fabc_rt_outcome_into_value(outcome) };
+
+        let context_value = unsafe { fabc_rt_context_value(context) };
+        unsafe { fabc_rt_member_assign(context_value, string_value("items"), count) };
+
+        // SAFETY: `context_value` originated from `fabc_rt_context_value` in this function.
+        unsafe {
+            drop(Box::from_raw(context_value as *mut Value));
+        }
+
+        fabc_rt_outcome_continue()
+    }
+
+    unsafe extern "C" fn compiled_visits(frame: RawPtr, context: RawPtr) -> RawPtr {
+        let callee = unsafe { fabc_rt_env_load(frame, "visits".as_ptr().cast(), 6) };
+        let mut args = [unsafe { string_value("part_1") }];
+        let outcome = unsafe { fabc_rt_call(frame, context, callee, args.as_mut_ptr(), 1) };
+        let visits = unsafe { fabc_rt_outcome_into_value(outcome) };
+
+        let callee = unsafe { fabc_rt_env_load(frame, "seen".as_ptr().cast(), 4) };
+        let outcome = unsafe { fabc_rt_call(frame, context, callee, [].as_mut_ptr(), 0) };
+        let seen = unsafe { fabc_rt_outcome_into_value(outcome) };
+
+        let context_value = unsafe { fabc_rt_context_value(context) };
+        unsafe { fabc_rt_member_assign(context_value, string_value("visits"), visits) };
+        unsafe { fabc_rt_member_assign(context_value, string_value("seen"), seen) };
+
+        // SAFETY: `context_value` originated from `fabc_rt_context_value` in this function.
+        unsafe {
+            drop(Box::from_raw(context_value as *mut Value));
+        }
+
+        fabc_rt_outcome_continue()
+    }
+
+    unsafe extern "C" fn compiled_gold(_frame: RawPtr, context: RawPtr) -> RawPtr {
+        let context_value = unsafe { fabc_rt_context_value(context) };
+        let gold = unsafe { fabc_rt_member_get(context_value, string_value("gold")) };
+        unsafe { fabc_rt_outcome_return(gold) }
+    }
+
+    unsafe extern "C" fn compiled_roll(frame: RawPtr, context: RawPtr) -> RawPtr {
+        let callee = unsafe { fabc_rt_env_load(frame, "random_int".as_ptr().cast(), 10) };
+        let mut args = [fabc_rt_value_number(1.0), fabc_rt_value_number(20.0)];
+        let outcome = unsafe { fabc_rt_call(frame, context, callee, args.as_mut_ptr(), 2) };
+        let roll = unsafe { fabc_rt_outcome_into_value(outcome) };
+
+        let context_value = unsafe { fabc_rt_context_value(context) };
+        unsafe { fabc_rt_member_assign(context_value, string_value("roll"), roll) };
+
+        // SAFETY: `context_value` originated from `fabc_rt_context_value` in this function.
+        unsafe {
+            drop(Box::from_raw(context_value as *mut Value));
+        }
+
+        fabc_rt_outcome_continue()
+    }
+
     unsafe fn string_value(text: &str) -> RawPtr {
         unsafe { fabc_rt_value_string(text.as_ptr().cast(), text.len() as u64) }
     }
@@ -1,14 +1,26 @@
-use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashSet, VecDeque},
+    rc::Rc,
+};
 
 use fabc_ir::{
-    BinaryOperator, Block, Expr, Literal, MemberSegment, QuoteSpec, SelectionSpec, StepSpec, Stmt,
-    StoryProgram, UnaryOperator,
+    BinaryOperator, Block, ChoiceGuard, Expr, Literal, MemberSegment, QuoteSpec, SelectionSpec,
+    StepSpec, Stmt, StoryProgram, TextSegment, UnaryOperator,
 };
 
 use super::{
+    builtins::define_builtins,
+    compiled::{with_active_rng, with_active_visits},
     error::{Result, RuntimeError},
-    scope::Scope,
-    value::{ClosureValue, ObjectRef, Value},
+    rng::StoryRng,
+    scope::{Scope, ScopeCopies},
+    snapshot::{
+        program_fingerprint, restore_map, snapshot_map, SnapshotCursor, StorySnapshot,
+        STORY_SNAPSHOT_FORMAT_VERSION, SUPPORTED_SNAPSHOT_FORMAT_VERSIONS,
+    },
+    value::{ClosureValue, HostFunction, ObjectRef, Value, ValueKey},
+    visits::{VisitLog, VisitView},
     CompiledFunctionHost,
 };
 
@@ -29,6 +41,8 @@ pub struct DialogueView {
 pub struct ChoiceView {
     pub text: String,
     pub properties: BTreeMap<String, Value>,
+    /// `false` for a guarded choice whose condition failed but which is still shown.
+    pub enabled: bool,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -41,13 +55,46 @@ pub enum StoryEvent {
     Narration(NarrationView),
     Dialogue(DialogueView),
     Selection(SelectionView),
+    /// An engine cue for the host, such as a music or background change.
+    Directive {
+        name: String,
+        args: Vec<Value>,
+    },
+    /// The cursor moved into a part that declares `# id => { ... }` properties. Shown before
+    /// the part's first step, after its `on_enter` hook has run. Parts without properties are
+    /// the exception: entering them emits no event and goes straight to their first step,
+    /// though their `on_enter` hook still runs and `visits()` still counts the entry.
+    PartEntered {
+        id: String,
+        properties: BTreeMap<String, Value>,
+    },
     Finished,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 struct Cursor {
     part_index: usize,
     step_index: usize,
+    /// The part's [`StoryEvent::PartEntered`] is showing and its first step is still ahead.
+    entering: bool,
+}
+
+/// What the explorer compares to tell whether two machines are in the same state.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct StateKey {
+    cursor: Option<Cursor>,
+    globals: BTreeMap<String, ValueKey>,
+    context: BTreeMap<String, ValueKey>,
+    rng: u64,
+    visits: Option<VisitLog>,
+}
+
+#[derive(Debug, Clone)]
+struct HistoryEntry {
+    cursor: Option<Cursor>,
+    context: BTreeMap<String, Value>,
+    rng: StoryRng,
+    visits: VisitLog,
 }
 
 #[derive(Debug, Clone)]
@@ -67,6 +114,8 @@ enum ExecSignal {
     Continue,
     Return(Value),
     Goto(String),
+    LoopBreak,
+    LoopContinue,
 }
 
 #[derive(Debug, Clone)]
@@ -75,10 +124,23 @@ pub struct StoryMachine {
     globals: Scope,
     context: ObjectRef,
     cursor: Option<Cursor>,
+    history: VecDeque<HistoryEntry>,
+    history_limit: usize,
+    loop_limit: usize,
+    rng: StoryRng,
+    visits: VisitLog,
+    /// Node id of the quote being rendered or run, which `seen()` asks about.
+    current_quote: Option<usize>,
+    /// Visible choices of the selection under the cursor, worked out once per step so that
+    /// rendering and `choose` agree with each other without running the guards again.
+    shown_choices: Option<(Cursor, Vec<(usize, bool)>)>,
     compiled_executor: Option<Rc<dyn CompiledFunctionHost>>,
 }
 
 impl StoryMachine {
+    pub const DEFAULT_HISTORY_LIMIT: usize = 64;
+    pub const DEFAULT_LOOP_LIMIT: usize = 1_000_000;
+
     pub fn new(program: StoryProgram) -> Result<Self> {
         Self::with_context(program, BTreeMap::new())
     }
@@ -87,6 +149,14 @@ impl StoryMachine {
         Self::build(program, context, None)
     }
 
+    pub fn with_seed(
+        program: StoryProgram,
+        context: BTreeMap<String, Value>,
+        seed: u64,
+    ) -> Result<Self> {
+        Ok(Self::with_context(program, context)?.seeded(seed))
+    }
+
     pub fn with_compiled_executor(
         program: StoryProgram,
         context: BTreeMap<String, Value>,
@@ -103,6 +173,22 @@ impl StoryMachine {
         Self::with_compiled_executor(program, context, native_executor)
     }
 
+    pub fn restore(program: StoryProgram, snapshot: &StorySnapshot) -> Result<Self> {
+        let mut machine = Self::build(program, BTreeMap::new(), None)?;
+        machine.load_snapshot(snapshot)?;
+        Ok(machine)
+    }
+
+    pub fn restore_with_compiled_executor(
+        program: StoryProgram,
+        snapshot: &StorySnapshot,
+        compiled_executor: Rc<dyn CompiledFunctionHost>,
+    ) -> Result<Self> {
+        let mut machine = Self::build(program, BTreeMap::new(), Some(compiled_executor))?;
+        machine.load_snapshot(snapshot)?;
+        Ok(machine)
+    }
+
     fn build(
         program: StoryProgram,
         context: BTreeMap<String, Value>,
@@ -112,19 +198,59 @@ impl StoryMachine {
             return Err(RuntimeError::UnknownPart(program.start_part.clone()));
         }
 
+        let globals = Scope::new();
+        define_builtins(&globals);
+
         Ok(Self {
             program,
-            globals: Scope::new(),
+            globals,
             context: Rc::new(RefCell::new(context)),
             cursor: None,
+            history: VecDeque::new(),
+            history_limit: Self::DEFAULT_HISTORY_LIMIT,
+            loop_limit: Self::DEFAULT_LOOP_LIMIT,
+            rng: StoryRng::from_entropy(),
+            visits: VisitLog::default(),
+            current_quote: None,
+            shown_choices: None,
             compiled_executor,
         })
     }
 
+    /// Reseeds `random`, `random_int` and `pick`; the same seed replays the same rolls.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = StoryRng::from_seed(seed);
+    }
+
+    /// [`Self::set_seed`] for chaining onto any constructor, as in
+    /// `StoryMachine::with_compiled_executor(program, context, host)?.seeded(7)`. On a
+    /// restored machine it replaces the saved random state.
+    pub fn seeded(mut self, seed: u64) -> Self {
+        self.set_seed(seed);
+        self
+    }
+
     pub fn program(&self) -> &StoryProgram {
         &self.program
     }
 
+    /// Id of the part the cursor is in, or `None` before the start and after the story ends.
+    pub fn current_part(&self) -> Option<&str> {
+        self.cursor
+            .map(|cursor| self.program.parts[cursor.part_index].id.as_str())
+    }
+
+    /// How many times the cursor has moved into `part` since the story started.
+    pub fn visits(&self, part: &str) -> usize {
+        self.visits.visits(part)
+    }
+
+    /// Whether the reader has moved past the quote with this `QuoteSpec::node_id`: read a
+    /// narration or dialogue line, or picked a choice.
+    pub fn has_seen(&self, node_id: usize) -> bool {
+        self.visits.seen.contains(&node_id)
+    }
+
     pub fn context_snapshot(&self) -> BTreeMap<String, Value> {
         self.context.borrow().clone()
     }
@@ -133,17 +259,201 @@ impl StoryMachine {
         self.context.borrow().get(key).cloned()
     }
 
+    pub fn set_context_value(&mut self, key: impl Into<String>, value: Value) {
+        self.context.borrow_mut().insert(key.into(), value);
+        self.shown_choices = None;
+    }
+
+    /// Exposes `function` to scripts as a global called `name`, shadowing any builtin of that
+    /// name. The analyzer only accepts calls to it when the story is compiled with a matching
+    /// declaration.
+    pub fn register_function(
+        &mut self,
+        name: impl Into<String>,
+        function: impl Fn(&[Value]) -> Result<Value> + 'static,
+    ) {
+        let name = name.into();
+        self.globals.define(
+            name.clone(),
+            Value::HostFunction(HostFunction::new(name, function)),
+        );
+    }
+
+    /// A copy that shares no objects, globals or captured scopes with this machine, so the
+    /// two can diverge.
+    pub(crate) fn fork(&self) -> Self {
+        let mut scopes = ScopeCopies::default();
+        let mut fork = self.clone();
+        fork.globals = self.globals.deep_clone(&mut scopes);
+        fork.context = Rc::new(RefCell::new(
+            self.context
+                .borrow()
+                .iter()
+                .map(|(key, value)| (key.clone(), value.deep_clone_with_scopes(&mut scopes)))
+                .collect(),
+        ));
+        fork.history.clear();
+        fork
+    }
+
+    /// Everything that decides what happens next: cursor, globals, context and random state,
+    /// plus visit counts and seen quotes when `with_visits` says the story can read them.
+    pub(crate) fn state_key(&self, with_visits: bool) -> StateKey {
+        StateKey {
+            cursor: self.cursor,
+            globals: self.globals.binding_keys(),
+            context: self
+                .context
+                .borrow()
+                .iter()
+                .map(|(key, value)| (key.clone(), value.key()))
+                .collect(),
+            rng: self.rng.state(),
+            visits: with_visits.then(|| self.visits.clone()),
+        }
+    }
+
+    pub fn snapshot(&self) -> Result<StorySnapshot> {
+        Ok(StorySnapshot {
+            format_version: STORY_SNAPSHOT_FORMAT_VERSION,
+            fingerprint: program_fingerprint(&self.program),
+            cursor: self.cursor.map(|cursor| SnapshotCursor {
+                part: self.program.parts[cursor.part_index].id.clone(),
+                step_index: cursor.step_index,
+                entering: cursor.entering,
+            }),
+            context: snapshot_map(&self.context.borrow())?,
+            rng_state: Some(self.rng.state()),
+            visits: self.visits.parts.clone(),
+            seen: self.visits.seen.clone(),
+        })
+    }
+
+    pub fn load_snapshot(&mut self, snapshot: &StorySnapshot) -> Result<()> {
+        if !SUPPORTED_SNAPSHOT_FORMAT_VERSIONS.contains(&snapshot.format_version) {
+            return Err(RuntimeError::UnsupportedSnapshotVersion(
+                snapshot.format_version,
+            ));
+        }
+
+        let fingerprint = program_fingerprint(&self.program);
+        if snapshot.fingerprint != fingerprint {
+            return Err(RuntimeError::IncompatibleSnapshot {
+                expected: fingerprint,
+                found: snapshot.fingerprint,
+            });
+        }
+
+        let cursor = match &snapshot.cursor {
+            Some(cursor) => {
+                let part_index = self
+                    .program
+                    .find_part_index(&cursor.part)
+                    .filter(|index| {
+                        let part = &self.program.parts[*index];
+                        if cursor.entering {
+                            cursor.step_index == 0 && !part.properties.is_empty()
+                        } else {
+                            cursor.step_index < part.steps.len()
+                        }
+                    })
+                    .ok_or_else(|| RuntimeError::InvalidSnapshotCursor {
+                        part: cursor.part.clone(),
+                        step_index: cursor.step_index,
+                    })?;
+
+                Some(Cursor {
+                    part_index,
+                    step_index: cursor.step_index,
+                    entering: cursor.entering,
+                })
+            }
+            None => None,
+        };
+
+        self.cursor = cursor;
+        *self.context.borrow_mut() = restore_map(snapshot.context.clone());
+        if let Some(state) = snapshot.rng_state {
+            self.rng = StoryRng::from_seed(state);
+        }
+        self.visits = VisitLog {
+            parts: snapshot.visits.clone(),
+            seen: snapshot.seen.clone(),
+        };
+        self.history.clear();
+        self.shown_choices = None;
+        Ok(())
+    }
+
+    pub fn history_len(&self) -> usize {
+        self.history.len()
+    }
+
+    pub fn history_limit(&self) -> usize {
+        self.history_limit
+    }
+
+    pub fn set_history_limit(&mut self, limit: usize) {
+        self.history_limit = limit;
+        while self.history.len() > limit {
+            self.history.pop_front();
+        }
+    }
+
+    pub fn loop_limit(&self) -> usize {
+        self.loop_limit
+    }
+
+    /// Caps the iterations of any single `while`/`for` loop in an interpreted closure, so a
+    /// runaway loop fails with [`RuntimeError::LoopLimitExceeded`] instead of hanging the
+    /// story. Closures run by a compiled executor are not bounded.
+    pub fn set_loop_limit(&mut self, limit: usize) {
+        self.loop_limit = limit;
+    }
+
+    pub fn rewind(&mut self, steps: usize) -> Result<StoryEvent> {
+        if steps == 0 || steps > self.history.len() {
+            return Err(RuntimeError::HistoryExhausted {
+                requested: steps,
+                available: self.history.len(),
+            });
+        }
+
+        let entry = self
+            .history
+            .drain(self.history.len() - steps..)
+            .next()
+            .expect("history holds at least one entry");
+
+        self.cursor = entry.cursor;
+        *self.context.borrow_mut() = deep_clone_map(&entry.context);
+        self.rng = entry.rng;
+        self.visits = entry.visits;
+        self.shown_choices = None;
+        self.render_current()
+    }
+
+    pub fn current(&mut self) -> Result<StoryEvent> {
+        self.render_current()
+    }
+
     pub fn start(&mut self) -> Result<StoryEvent> {
-        let Some(start_index) = self.program.find_part_index(&self.program.start_part) else {
-            return Err(RuntimeError::UnknownPart(self.program.start_part.clone()));
+        let start_part = self.program.start_part.clone();
+        self.start_at(&start_part)
+    }
+
+    /// Like [`StoryMachine::start`], but begins at `part` instead of the story's start part.
+    pub fn start_at(&mut self, part: &str) -> Result<StoryEvent> {
+        let Some(start_index) = self.program.find_part_index(part) else {
+            return Err(RuntimeError::UnknownPart(part.to_string()));
         };
 
-        self.cursor = Some(Cursor {
-            part_index: start_index,
-            step_index: 0,
-        });
+        self.cursor = None;
+        self.history.clear();
+        self.visits = VisitLog::default();
 
-        self.normalize_cursor()?;
+        self.enter_part(start_index)?;
+        self.take_fallbacks()?;
         self.render_current()
     }
 
@@ -152,15 +462,36 @@ impl StoryMachine {
             return Err(RuntimeError::EndOfStory);
         };
 
+        if cursor.entering {
+            let entry = self.history_entry();
+            self.move_after_current(None)?;
+            self.take_fallbacks()?;
+            self.push_history(entry);
+            return self.render_current();
+        }
+
         let step = self.program.parts[cursor.part_index].steps[cursor.step_index].clone();
 
-        let goto_target = match step {
-            StepSpec::Narration(quote) => self.execute_quote(&quote)?.goto,
-            StepSpec::Dialogue(dialogue) => self.execute_quote(&dialogue.quote)?.goto,
+        let quote = match step {
+            StepSpec::Narration(quote) => Some(quote),
+            StepSpec::Dialogue(dialogue) => Some(dialogue.quote),
+            StepSpec::Directive(_) => None,
             StepSpec::Selection(_) => return Err(RuntimeError::ChoiceExpected),
         };
 
+        let entry = self.history_entry();
+        let goto_target = match quote {
+            Some(quote) => {
+                let goto_target = self.execute_quote(&quote)?.goto;
+                self.visits.seen.insert(quote.node_id);
+                goto_target
+            }
+            None => None,
+        };
+
         self.move_after_current(goto_target.as_deref())?;
+        self.take_fallbacks()?;
+        self.push_history(entry);
         self.render_current()
     }
 
@@ -169,56 +500,283 @@ impl StoryMachine {
             return Err(RuntimeError::EndOfStory);
         };
 
+        if cursor.entering {
+            return Err(RuntimeError::NotInSelection);
+        }
+
         let selection = match self.program.parts[cursor.part_index].steps[cursor.step_index].clone()
         {
             StepSpec::Selection(selection) => selection,
             _ => return Err(RuntimeError::NotInSelection),
         };
 
-        let Some(choice) = selection.choices.get(choice_index).cloned() else {
+        let visible = self.shown_choices(cursor, &selection)?;
+        let Some(&(original_index, enabled)) = visible.get(choice_index) else {
             return Err(RuntimeError::InvalidChoice {
                 index: choice_index,
-                len: selection.choices.len(),
+                len: visible.len(),
             });
         };
+        if !enabled {
+            return Err(RuntimeError::DisabledChoice {
+                index: choice_index,
+            });
+        }
+        let choice = selection.choices[original_index].clone();
 
-        let goto_target = self.execute_quote(&choice)?.goto;
-
-        self.move_after_current(goto_target.as_deref())?;
+        let entry = self.history_entry();
+        self.take_choice(&choice)?;
+        self.take_fallbacks()?;
+        self.push_history(entry);
         self.render_current()
     }
 
+    /// Runs `choice`'s `next` handler and moves on. Picked choices count as seen, which is
+    /// also what retires `once` choices.
+    fn take_choice(&mut self, choice: &QuoteSpec) -> Result<()> {
+        let goto_target = self.execute_quote(choice)?.goto;
+        self.visits.seen.insert(choice.node_id);
+
+        self.move_after_current(goto_target.as_deref())
+    }
+
+    /// The state before a step, taken up front but only recorded by [`Self::push_history`]
+    /// once the step went through, so a failing step leaves no entry to rewind onto.
+    fn history_entry(&self) -> Option<HistoryEntry> {
+        if self.history_limit == 0 {
+            return None;
+        }
+
+        // `next` closures mutate nested context objects in place, so the entry must not
+        // share any `ObjectRef` with the live context.
+        Some(HistoryEntry {
+            cursor: self.cursor,
+            context: deep_clone_map(&self.context.borrow()),
+            rng: self.rng,
+            visits: self.visits.clone(),
+        })
+    }
+
+    fn push_history(&mut self, entry: Option<HistoryEntry>) {
+        let Some(entry) = entry else {
+            return;
+        };
+
+        if self.history.len() == self.history_limit {
+            self.history.pop_front();
+        }
+        self.history.push_back(entry);
+    }
+
     fn render_current(&mut self) -> Result<StoryEvent> {
         let Some(cursor) = self.cursor else {
             return Ok(StoryEvent::Finished);
         };
 
+        if cursor.entering {
+            let part = &self.program.parts[cursor.part_index];
+            let (id, properties) = (part.id.clone(), part.properties.clone());
+            return Ok(StoryEvent::PartEntered {
+                id,
+                properties: self.evaluate_properties(&properties)?,
+            });
+        }
+
         let step = self.program.parts[cursor.part_index].steps[cursor.step_index].clone();
         match step {
-            StepSpec::Narration(quote) => Ok(StoryEvent::Narration(NarrationView {
-                text: quote.text.clone(),
-                properties: self.evaluate_properties(&quote.properties)?,
-            })),
-            StepSpec::Dialogue(dialogue) => Ok(StoryEvent::Dialogue(DialogueView {
-                speaker: dialogue.speaker.clone(),
-                text: dialogue.quote.text.clone(),
-                properties: self.evaluate_properties(&dialogue.quote.properties)?,
-            })),
-            StepSpec::Selection(selection) => {
-                Ok(StoryEvent::Selection(self.render_selection(&selection)?))
+            StepSpec::Narration(quote) => {
+                let (text, properties) = self.render_quote(&quote)?;
+                Ok(StoryEvent::Narration(NarrationView { text, properties }))
+            }
+            StepSpec::Dialogue(dialogue) => {
+                let (text, properties) = self.render_quote(&dialogue.quote)?;
+                Ok(StoryEvent::Dialogue(DialogueView {
+                    speaker: dialogue.speaker.clone(),
+                    text,
+                    properties,
+                }))
+            }
+            StepSpec::Selection(selection) => self.render_selection(cursor, &selection),
+            StepSpec::Directive(directive) => {
+                let globals = self.globals.clone();
+                let mut args = Vec::with_capacity(directive.args.len());
+                for arg in &directive.args {
+                    match self.eval_expr(arg, &globals)? {
+                        EvalSignal::Value(value) => args.push(value),
+                        EvalSignal::Goto(_) => return Err(RuntimeError::UnexpectedControlFlow),
+                    }
+                }
+                Ok(StoryEvent::Directive {
+                    name: directive.name,
+                    args,
+                })
+            }
+        }
+    }
+
+    /// Takes the first available `fallback` choice for as long as the cursor rests on a
+    /// selection where none of the listed choices can be picked. Fallbacks are taken within
+    /// the step that led into the selection, so a rewind lands before that step. Coming back
+    /// to a selection whose fallback was already taken fails instead of looping forever.
+    fn take_fallbacks(&mut self) -> Result<()> {
+        let mut taken = HashSet::new();
+        while let Some(cursor) = self.cursor.filter(|cursor| !cursor.entering) {
+            let StepSpec::Selection(selection) =
+                &self.program.parts[cursor.part_index].steps[cursor.step_index]
+            else {
+                return Ok(());
+            };
+            let selection = selection.clone();
+
+            let visible = self.visible_choices(&selection)?;
+            let exhausted = !visible.iter().any(|&(_, enabled)| enabled);
+            self.shown_choices = Some((cursor, visible));
+            if !exhausted {
+                return Ok(());
+            }
+            let Some(index) = self.fallback_choice(&selection)? else {
+                return Ok(());
+            };
+            if !taken.insert(cursor) {
+                return Err(RuntimeError::FallbackLoop {
+                    part: self.program.parts[cursor.part_index].id.clone(),
+                });
             }
+            self.take_choice(&selection.choices[index])?;
         }
+        Ok(())
     }
 
-    fn render_selection(&mut self, selection: &SelectionSpec) -> Result<SelectionView> {
-        let mut choices = Vec::with_capacity(selection.choices.len());
-        for choice in &selection.choices {
+    /// Lists the choices the reader can see. Fallbacks are never listed; they were already
+    /// taken by [`Self::take_fallbacks`] if nothing else could be picked.
+    fn render_selection(
+        &mut self,
+        cursor: Cursor,
+        selection: &SelectionSpec,
+    ) -> Result<StoryEvent> {
+        let visible = self.shown_choices(cursor, selection)?;
+        let mut choices = Vec::with_capacity(visible.len());
+        for &(index, enabled) in &visible {
+            let (text, properties) = self.render_quote(&selection.choices[index])?;
             choices.push(ChoiceView {
-                text: choice.text.clone(),
-                properties: self.evaluate_properties(&choice.properties)?,
+                text,
+                properties,
+                enabled,
             });
         }
-        Ok(SelectionView { choices })
+        Ok(StoryEvent::Selection(SelectionView { choices }))
+    }
+
+    /// The visible choices worked out for the selection at `cursor` during this step, or
+    /// freshly evaluated ones when there are none, e.g. right after a restore or rewind.
+    fn shown_choices(
+        &mut self,
+        cursor: Cursor,
+        selection: &SelectionSpec,
+    ) -> Result<Vec<(usize, bool)>> {
+        if let Some((shown_at, visible)) = &self.shown_choices {
+            if *shown_at == cursor {
+                return Ok(visible.clone());
+            }
+        }
+
+        let visible = self.visible_choices(selection)?;
+        self.shown_choices = Some((cursor, visible.clone()));
+        Ok(visible)
+    }
+
+    /// Original indices of the choices the reader sees, paired with whether each can be taken.
+    /// Fallbacks and `once` choices that were already picked are never listed.
+    fn visible_choices(&mut self, selection: &SelectionSpec) -> Result<Vec<(usize, bool)>> {
+        let mut visible = Vec::with_capacity(selection.choices.len());
+        for (index, choice) in selection.choices.iter().enumerate() {
+            if choice.fallback || self.used_up(choice) {
+                continue;
+            }
+
+            let Some(guard) = choice.guard else {
+                visible.push((index, true));
+                continue;
+            };
+
+            let enabled = self.guard_passes(choice.node_id, guard)?;
+            if enabled || guard.show_disabled {
+                visible.push((index, enabled));
+            }
+        }
+        Ok(visible)
+    }
+
+    /// Original index of the first fallback choice whose guard passes and which is not used up.
+    fn fallback_choice(&mut self, selection: &SelectionSpec) -> Result<Option<usize>> {
+        for (index, choice) in selection.choices.iter().enumerate() {
+            if !choice.fallback || self.used_up(choice) {
+                continue;
+            }
+
+            match choice.guard {
+                Some(guard) if !self.guard_passes(choice.node_id, guard)? => {}
+                _ => return Ok(Some(index)),
+            }
+        }
+        Ok(None)
+    }
+
+    fn used_up(&self, choice: &QuoteSpec) -> bool {
+        choice.once && self.visits.seen.contains(&choice.node_id)
+    }
+
+    fn guard_passes(&mut self, node_id: usize, guard: ChoiceGuard) -> Result<bool> {
+        let result = self.with_quote(node_id, |machine| {
+            machine.invoke_function(guard.condition, machine.globals.clone(), Vec::new())
+        })?;
+        if result.goto.is_some() {
+            return Err(RuntimeError::UnexpectedControlFlow);
+        }
+        result.value.to_bool()
+    }
+
+    fn render_quote(&mut self, quote: &QuoteSpec) -> Result<(String, BTreeMap<String, Value>)> {
+        self.with_quote(quote.node_id, |machine| {
+            Ok((
+                machine.render_text(quote)?,
+                machine.evaluate_properties(&quote.properties)?,
+            ))
+        })
+    }
+
+    /// Runs `f` with `node_id` as the quote `seen()` asks about.
+    fn with_quote<T>(
+        &mut self,
+        node_id: usize,
+        f: impl FnOnce(&mut Self) -> Result<T>,
+    ) -> Result<T> {
+        let previous = self.current_quote.replace(node_id);
+        let result = f(self);
+        self.current_quote = previous;
+        result
+    }
+
+    fn render_text(&mut self, quote: &QuoteSpec) -> Result<String> {
+        if quote.segments.is_empty() {
+            return Ok(quote.text.clone());
+        }
+
+        let mut text = String::new();
+        for segment in &quote.segments {
+            match segment {
+                TextSegment::Text(literal) => text.push_str(literal),
+                TextSegment::Interpolation(function_id) => {
+                    let result =
+                        self.invoke_function(*function_id, self.globals.clone(), Vec::new())?;
+                    if result.goto.is_some() {
+                        return Err(RuntimeError::UnexpectedControlFlow);
+                    }
+                    text.push_str(&result.value.to_string());
+                }
+            }
+        }
+        Ok(text)
     }
 
     fn evaluate_properties(
@@ -240,9 +798,9 @@ impl StoryMachine {
 
     fn execute_quote(&mut self, quote: &QuoteSpec) -> Result<InvocationResult> {
         match quote.next_action {
-            Some(function_id) => {
-                self.invoke_function(function_id, self.globals.clone(), Vec::new())
-            }
+            Some(function_id) => self.with_quote(quote.node_id, |machine| {
+                machine.invoke_function(function_id, machine.globals.clone(), Vec::new())
+            }),
             None => Ok(InvocationResult {
                 value: Value::None,
                 goto: None,
@@ -256,10 +814,15 @@ impl StoryMachine {
         captured: Scope,
         args: Vec<Value>,
     ) -> Result<InvocationResult> {
-        if let Some(compiled_executor) = self.compiled_executor.as_ref() {
-            let result = compiled_executor
-                .invoke_function(function_id, captured, self.context.clone(), args)
-                .map_err(RuntimeError::NativeExecution);
+        if let Some(compiled_executor) = self.compiled_executor.clone() {
+            let context = self.context.clone();
+            let quote = self.current_quote;
+            let result = with_active_rng(&mut self.rng, || {
+                with_active_visits(&mut self.visits, quote, || {
+                    compiled_executor.invoke_function(function_id, captured, context, args)
+                })
+            })
+            .map_err(RuntimeError::NativeExecution);
 
             return result.map(|result| InvocationResult {
                 value: result.value,
@@ -304,6 +867,9 @@ impl StoryMachine {
                 value: Value::None,
                 goto: Some(target),
             }),
+            ExecSignal::LoopBreak | ExecSignal::LoopContinue => {
+                Err(RuntimeError::UnexpectedControlFlow)
+            }
         }
     }
 
@@ -368,6 +934,66 @@ impl StoryMachine {
 
                 Ok(ExecSignal::Return(value))
             }
+            Stmt::While { condition, body } => self.exec_loop(Some(condition), None, body, scope),
+            Stmt::For {
+                initializer,
+                condition,
+                increment,
+                body,
+            } => {
+                let loop_scope = scope.child();
+                if let Some(initializer) = initializer {
+                    match self.exec_stmt(initializer, &loop_scope)? {
+                        ExecSignal::Continue => {}
+                        signal => return Ok(signal),
+                    }
+                }
+
+                self.exec_loop(condition.as_ref(), increment.as_ref(), body, &loop_scope)
+            }
+            Stmt::Break => Ok(ExecSignal::LoopBreak),
+            Stmt::Continue => Ok(ExecSignal::LoopContinue),
+        }
+    }
+
+    fn exec_loop(
+        &mut self,
+        condition: Option<&Expr>,
+        increment: Option<&Expr>,
+        body: &Block,
+        scope: &Scope,
+    ) -> Result<ExecSignal> {
+        let mut iterations = 0;
+        loop {
+            if let Some(condition) = condition {
+                let condition = match self.eval_expr(condition, scope)? {
+                    EvalSignal::Value(value) => value,
+                    EvalSignal::Goto(target) => return Ok(ExecSignal::Goto(target)),
+                };
+
+                if !condition.to_bool()? {
+                    return Ok(ExecSignal::Continue);
+                }
+            }
+
+            if iterations == self.loop_limit {
+                return Err(RuntimeError::LoopLimitExceeded {
+                    limit: self.loop_limit,
+                });
+            }
+            iterations += 1;
+
+            match self.exec_block(body, &scope.child())? {
+                ExecSignal::Continue | ExecSignal::LoopContinue => {}
+                ExecSignal::LoopBreak => return Ok(ExecSignal::Continue),
+                signal => return Ok(signal),
+            }
+
+            if let Some(increment) = increment {
+                if let EvalSignal::Goto(target) = self.eval_expr(increment, scope)? {
+                    return Ok(ExecSignal::Goto(target));
+                }
+            }
         }
     }
 
@@ -398,6 +1024,17 @@ impl StoryMachine {
 
                 EvalSignal::Value(Value::object(object))
             }
+            Expr::List(elements) => {
+                let mut list = Vec::with_capacity(elements.len());
+                for element in elements {
+                    match self.eval_expr(element, scope)? {
+                        EvalSignal::Value(value) => list.push(value),
+                        EvalSignal::Goto(target) => return Ok(EvalSignal::Goto(target)),
+                    }
+                }
+
+                EvalSignal::Value(Value::list(list))
+            }
             Expr::Closure(function_id) => EvalSignal::Value(Value::Closure(ClosureValue {
                 function_id: *function_id,
                 captured: scope.clone(),
@@ -426,6 +1063,14 @@ impl StoryMachine {
                             None => EvalSignal::Value(result.value),
                         }
                     }
+                    Value::Builtin(builtin) => {
+                        let visits = VisitView {
+                            log: &self.visits,
+                            quote: self.current_quote,
+                        };
+                        EvalSignal::Value(builtin.call(&args, &mut self.rng, visits)?)
+                    }
+                    Value::HostFunction(function) => EvalSignal::Value(function.call(&args)?),
                     other => {
                         return Err(RuntimeError::InvalidCallee(other.kind_name().to_string()));
                     }
@@ -439,22 +1084,7 @@ impl StoryMachine {
 
                 for member in members {
                     let key = self.resolve_member_key(member, scope)?;
-                    current = match current {
-                        Value::Object(object) => {
-                            object.borrow().get(&key).cloned().ok_or_else(|| {
-                                RuntimeError::InvalidMemberAccess {
-                                    target: "Object".to_string(),
-                                    member: key.clone(),
-                                }
-                            })?
-                        }
-                        other => {
-                            return Err(RuntimeError::InvalidMemberAccess {
-                                target: other.kind_name().to_string(),
-                                member: key,
-                            });
-                        }
-                    };
+                    current = current.get_member(&key)?;
                 }
 
                 EvalSignal::Value(current)
@@ -520,17 +1150,10 @@ impl StoryMachine {
 
     fn assign_target(&mut self, target: &Expr, scope: &Scope, value: Value) -> Result<()> {
         match target {
-            Expr::Identifier(name) => {
-                if scope.assign(name, value) {
-                    Ok(())
-                } else {
-                    Err(RuntimeError::UndefinedVariable(name.clone()))
-                }
-            }
+            Expr::Identifier(name) => scope.assign(name, value),
             Expr::MemberAccess { base, members } => {
-                let (object, key) = self.resolve_member_place(base, members, scope)?;
-                object.borrow_mut().insert(key, value);
-                Ok(())
+                let (container, key) = self.resolve_member_place(base, members, scope)?;
+                container.set_member(&key, value)
             }
             _ => Err(RuntimeError::InvalidAssignmentTarget),
         }
@@ -541,7 +1164,7 @@ impl StoryMachine {
         base: &Expr,
         members: &[MemberSegment],
         scope: &Scope,
-    ) -> Result<(ObjectRef, String)> {
+    ) -> Result<(Value, Value)> {
         let mut current = match self.eval_expr(base, scope)? {
             EvalSignal::Value(value) => value,
             EvalSignal::Goto(target) => return Err(RuntimeError::InvalidStoryTarget(target)),
@@ -553,40 +1176,17 @@ impl StoryMachine {
 
         for member in rest {
             let key = self.resolve_member_key(member, scope)?;
-            current = match current {
-                Value::Object(object) => object.borrow().get(&key).cloned().ok_or_else(|| {
-                    RuntimeError::InvalidMemberAccess {
-                        target: "Object".to_string(),
-                        member: key.clone(),
-                    }
-                })?,
-                other => {
-                    return Err(RuntimeError::InvalidMemberAccess {
-                        target: other.kind_name().to_string(),
-                        member: key,
-                    });
-                }
-            };
+            current = current.get_member(&key)?;
         }
 
-        let object = match current {
-            Value::Object(object) => object,
-            other => {
-                return Err(RuntimeError::InvalidMemberAccess {
-                    target: other.kind_name().to_string(),
-                    member: self.resolve_member_key(last, scope)?,
-                });
-            }
-        };
-
-        Ok((object, self.resolve_member_key(last, scope)?))
+        Ok((current, self.resolve_member_key(last, scope)?))
     }
 
-    fn resolve_member_key(&mut self, member: &MemberSegment, scope: &Scope) -> Result<String> {
+    fn resolve_member_key(&mut self, member: &MemberSegment, scope: &Scope) -> Result<Value> {
         match member {
-            MemberSegment::Key(value) => Ok(value.clone()),
+            MemberSegment::Key(value) => Ok(Value::String(value.clone())),
             MemberSegment::Expr(expr) => match self.eval_expr(expr, scope)? {
-                EvalSignal::Value(value) => value.to_member_key(),
+                EvalSignal::Value(value) => Ok(value),
                 EvalSignal::Goto(target) => Err(RuntimeError::InvalidStoryTarget(target)),
             },
         }
@@ -598,42 +1198,98 @@ impl StoryMachine {
                 return Err(RuntimeError::UnknownPart(target.to_string()));
             };
 
-            self.cursor = Some(Cursor {
-                part_index,
-                step_index: 0,
-            });
-        } else if let Some(cursor) = self.cursor.as_mut() {
-            cursor.step_index += 1;
+            self.leave_part()?;
+            return self.enter_part(part_index);
+        }
+
+        if let Some(cursor) = self.cursor.as_mut() {
+            if cursor.entering {
+                cursor.entering = false;
+            } else {
+                cursor.step_index += 1;
+            }
         }
 
         self.normalize_cursor()
     }
 
-    fn normalize_cursor(&mut self) -> Result<()> {
-        while let Some(cursor) = self.cursor {
-            let part = &self.program.parts[cursor.part_index];
-            if cursor.step_index < part.steps.len() {
-                return Ok(());
-            }
+    /// Puts the cursor at the top of the part and runs its `on_enter` hook. A `goto` to the part
+    /// the cursor is already in re-enters it like any other: `on_exit` runs, then `on_enter`,
+    /// and the visit counts.
+    fn enter_part(&mut self, part_index: usize) -> Result<()> {
+        let part = &self.program.parts[part_index];
+        let on_enter = part.on_enter;
+        *self.visits.parts.entry(part.id.clone()).or_default() += 1;
+        self.cursor = Some(Cursor {
+            part_index,
+            step_index: 0,
+            entering: !part.properties.is_empty(),
+        });
 
-            self.cursor = None;
+        if let Some(function_id) = on_enter {
+            self.run_part_hook(function_id)?;
         }
+        self.normalize_cursor()
+    }
+
+    /// Runs the `on_exit` hook of the part the cursor is still in.
+    fn leave_part(&mut self) -> Result<()> {
+        let Some(cursor) = self.cursor else {
+            return Ok(());
+        };
+
+        match self.program.parts[cursor.part_index].on_exit {
+            Some(function_id) => self.run_part_hook(function_id),
+            None => Ok(()),
+        }
+    }
 
+    fn run_part_hook(&mut self, function_id: usize) -> Result<()> {
+        let result = self.invoke_function(function_id, self.globals.clone(), Vec::new())?;
+        if result.goto.is_some() {
+            return Err(RuntimeError::UnexpectedControlFlow);
+        }
         Ok(())
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use std::collections::BTreeMap;
+    /// Ends the story once the cursor has run past the last step of its part.
+    fn normalize_cursor(&mut self) -> Result<()> {
+        let Some(cursor) = self.cursor else {
+            return Ok(());
+        };
+
+        if cursor.entering || cursor.step_index < self.program.parts[cursor.part_index].steps.len()
+        {
+            return Ok(());
+        }
+
+        self.leave_part()?;
+        self.cursor = None;
+        Ok(())
+    }
+}
+
+fn deep_clone_map(values: &BTreeMap<String, Value>) -> BTreeMap<String, Value> {
+    values
+        .iter()
+        .map(|(key, value)| (key.clone(), value.deep_clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
 
     use fabc_ir::{
-        BinaryOperator, Block, DialogueSpec, Expr, FunctionSpec, Literal, MemberSegment, PartSpec,
-        QuoteSpec, SelectionSpec, StepSpec, Stmt, StoryProgram,
+        BinaryOperator, Block, DialogueSpec, DirectiveSpec, Expr, FunctionSpec, Literal,
+        MemberSegment, PartSpec, QuoteSpec, SelectionSpec, StepSpec, Stmt, StoryProgram,
     };
 
     use super::{DialogueView, NarrationView, StoryEvent, StoryMachine};
-    use crate::{RuntimeError, Value};
+    use crate::{
+        builtins::Builtin, value::ClosureValue, RuntimeError, StorySnapshot, Value,
+        STORY_SNAPSHOT_FORMAT_VERSION,
+    };
 
     #[test]
     fn interpreted_machine_updates_context_and_goto_targets() {
@@ -761,11 +1417,585 @@ mod tests {
                 properties: Default::default(),
             })
         );
+        assert_eq!(machine.current_part(), Some("connected"));
 
         let event = machine
             .advance()
             .expect("connected ending should terminate story");
         assert_eq!(event, StoryEvent::Finished);
+        assert_eq!(machine.current_part(), None);
+    }
+
+    #[test]
+    fn machine_starts_at_a_chosen_part() {
+        let mut machine = StoryMachine::new(program_with_dangling_part())
+            .expect("build story with dangling part");
+        machine.set_context_value("mood", Value::String("calm".to_string()));
+
+        let event = machine
+            .start_at("dangling")
+            .expect("start at dangling part");
+        assert_eq!(
+            event,
+            StoryEvent::Narration(NarrationView {
+                text: "This dangling part should never render.".to_string(),
+                properties: Default::default(),
+            })
+        );
+        assert_eq!(machine.current_part(), Some("dangling"));
+        assert_eq!(
+            machine.context_value("mood"),
+            Some(Value::String("calm".to_string()))
+        );
+
+        assert_eq!(
+            machine.start_at("missing"),
+            Err(RuntimeError::UnknownPart("missing".to_string()))
+        );
+    }
+
+    #[test]
+    fn interpreted_machine_calls_registered_host_functions() {
+        let mut machine =
+            StoryMachine::new(program_with_host_call()).expect("build interpreted machine");
+        machine.register_function("give_item", |args| match args {
+            [Value::String(item)] => Ok(Value::String(format!("got {item}"))),
+            _ => Err(RuntimeError::Host(
+                "give_item expects an item name".to_string(),
+            )),
+        });
+
+        machine.start().expect("start story");
+        machine.advance().expect("call host function");
+        assert_eq!(
+            machine.context_value("result"),
+            Some(Value::String("got sword".to_string()))
+        );
+
+        machine.register_function("give_item", |_| {
+            Err(RuntimeError::Host("inventory is full".to_string()))
+        });
+        machine.start().expect("restart story");
+        assert_eq!(
+            machine.advance(),
+            Err(RuntimeError::Host("inventory is full".to_string()))
+        );
+    }
+
+    #[test]
+    fn runaway_loops_fail_at_the_loop_limit() {
+        let mut machine =
+            StoryMachine::new(program_with_endless_loop()).expect("build interpreted machine");
+        assert_eq!(machine.loop_limit(), StoryMachine::DEFAULT_LOOP_LIMIT);
+        machine.set_loop_limit(100);
+
+        machine.start().expect("start story");
+        assert_eq!(
+            machine.advance(),
+            Err(RuntimeError::LoopLimitExceeded { limit: 100 })
+        );
+        assert_eq!(machine.history_len(), 0);
+    }
+
+    #[test]
+    fn builtin_namespaces_are_read_only() {
+        let overwrite_member = Expr::Assignment {
+            target: Box::new(Expr::MemberAccess {
+                base: Box::new(Expr::Identifier("str".to_string())),
+                members: vec![MemberSegment::Key("len".to_string())],
+            }),
+            value: Box::new(Expr::Literal(Literal::Number(5.0))),
+        };
+        let rebind = Expr::Assignment {
+            target: Box::new(Expr::Identifier("num".to_string())),
+            value: Box::new(Expr::Literal(Literal::Number(1.0))),
+        };
+
+        for (statement, namespace) in [(overwrite_member, "str"), (rebind, "num")] {
+            let mut machine = StoryMachine::new(program_with_next(vec![Stmt::Expr(statement)]))
+                .expect("build interpreted machine");
+            let mut fork = machine.fork();
+
+            machine.start().expect("start story");
+            assert_eq!(
+                machine.advance(),
+                Err(RuntimeError::ReadOnlyNamespace(namespace.to_string()))
+            );
+            assert_eq!(
+                fork.globals.get(namespace),
+                Some(Value::Namespace(namespace))
+            );
+            fork.start().expect("start fork");
+        }
+    }
+
+    #[test]
+    fn forks_share_no_globals_or_captured_scopes() {
+        let rebind = Expr::Assignment {
+            target: Box::new(Expr::Identifier("random".to_string())),
+            value: Box::new(Expr::Literal(Literal::Number(1.0))),
+        };
+        let mut machine = StoryMachine::new(program_with_next(vec![Stmt::Expr(rebind)]))
+            .expect("build interpreted machine");
+        let captured = machine.globals.child();
+        captured.define("count", Value::Number(0.0));
+        machine.set_context_value(
+            "counter",
+            Value::Closure(ClosureValue {
+                function_id: 0,
+                captured: captured.clone(),
+            }),
+        );
+        machine.start().expect("start story");
+
+        let fork = machine.fork();
+        machine.advance().expect("rebind a global");
+        captured
+            .assign("count", Value::Number(1.0))
+            .expect("bump captured count");
+
+        assert_eq!(machine.globals.get("random"), Some(Value::Number(1.0)));
+        assert_eq!(
+            fork.globals.get("random"),
+            Some(Value::Builtin(Builtin::Random))
+        );
+        let Some(Value::Closure(forked)) = fork.context_value("counter") else {
+            panic!("expected the forked counter closure");
+        };
+        assert_eq!(forked.captured.get("count"), Some(Value::Number(0.0)));
+        fork.globals.define("random", Value::Number(2.0));
+        assert_eq!(forked.captured.get("random"), Some(Value::Number(2.0)));
+
+        let rebound = fork.fork();
+        rebound.globals.define("random", Value::Number(3.0));
+        assert_ne!(fork.state_key(false), rebound.state_key(false));
+    }
+
+    #[test]
+    fn part_entries_show_properties_and_run_exit_hooks() {
+        let mut machine = StoryMachine::new(program_with_part_properties()).expect("build machine");
+        let entered = StoryEvent::PartEntered {
+            id: "intro".to_string(),
+            properties: BTreeMap::from([(
+                "background".to_string(),
+                Value::String("bg_alley".to_string()),
+            )]),
+        };
+
+        assert_eq!(machine.start(), Ok(entered.clone()));
+        assert_eq!(machine.choose(0), Err(RuntimeError::NotInSelection));
+
+        let snapshot = machine.snapshot().expect("snapshot part entry");
+        let mut restored =
+            StoryMachine::restore(program_with_part_properties(), &snapshot).expect("restore");
+        assert_eq!(restored.current(), Ok(entered.clone()));
+
+        assert!(matches!(machine.advance(), Ok(StoryEvent::Narration(_))));
+        assert_eq!(machine.rewind(1), Ok(entered));
+        assert!(matches!(machine.advance(), Ok(StoryEvent::Narration(_))));
+        assert_eq!(machine.context_value("left"), None);
+
+        assert_eq!(machine.advance(), Ok(StoryEvent::Finished));
+        assert_eq!(machine.context_value("left"), Some(Value::Boolean(true)));
+    }
+
+    #[test]
+    fn directives_are_emitted_in_story_order() {
+        let context = BTreeMap::from([("weather".to_string(), Value::String("rain".to_string()))]);
+        let mut machine =
+            StoryMachine::with_context(program_with_directive(), context).expect("build machine");
+
+        assert_eq!(
+            machine.start(),
+            Ok(StoryEvent::Directive {
+                name: "play_music".to_string(),
+                args: vec![Value::String("rain".to_string()), Value::Number(0.5)],
+            })
+        );
+        assert_eq!(
+            machine.advance(),
+            Ok(StoryEvent::Narration(NarrationView {
+                text: "It starts to pour.".to_string(),
+                properties: BTreeMap::new(),
+            }))
+        );
+        assert!(matches!(
+            machine.rewind(1),
+            Ok(StoryEvent::Directive { .. })
+        ));
+        assert!(matches!(machine.advance(), Ok(StoryEvent::Narration(_))));
+        assert_eq!(machine.advance(), Ok(StoryEvent::Finished));
+    }
+
+    #[test]
+    fn snapshot_round_trips_cursor_and_nested_context() {
+        let mut context = BTreeMap::new();
+        context.insert(
+            "inventory".to_string(),
+            Value::object(BTreeMap::from([(
+                "lamp".to_string(),
+                Value::object(BTreeMap::from([("lit".to_string(), Value::Boolean(true))])),
+            )])),
+        );
+        let mut machine = StoryMachine::with_context(program_with_context_mutation(), context)
+            .expect("build interpreted machine");
+        machine.start().expect("start story");
+        machine.advance().expect("reach selection");
+
+        let snapshot = machine.snapshot().expect("take snapshot");
+        let encoded = serde_json::to_string(&snapshot).expect("encode snapshot");
+        let decoded: StorySnapshot = serde_json::from_str(&encoded).expect("decode snapshot");
+
+        let mut restored = StoryMachine::restore(program_with_context_mutation(), &decoded)
+            .expect("restore snapshot");
+        assert_eq!(restored.context_snapshot(), machine.context_snapshot());
+
+        let StoryEvent::Selection(selection) = restored.current().expect("render current step")
+        else {
+            panic!("expected selection event");
+        };
+        assert_eq!(selection.choices[0].text, "Hi!");
+
+        restored.choose(0).expect("resume from restored selection");
+        assert_eq!(restored.context_value("total"), Some(Value::Number(30.0)));
+        assert_eq!(machine.context_value("total"), None);
+    }
+
+    #[test]
+    fn snapshot_round_trips_lists_and_rejects_bad_indexes() {
+        let items = Value::list(vec![
+            Value::String("torch".to_string()),
+            Value::list(vec![Value::Number(1.0)]),
+        ]);
+        let mut machine = StoryMachine::with_context(
+            program_with_context_mutation(),
+            BTreeMap::from([("items".to_string(), items.clone())]),
+        )
+        .expect("build interpreted machine");
+        machine.start().expect("start story");
+
+        let encoded = serde_json::to_string(&machine.snapshot().expect("take snapshot"))
+            .expect("encode snapshot");
+        let decoded: StorySnapshot = serde_json::from_str(&encoded).expect("decode snapshot");
+        let restored = StoryMachine::restore(program_with_context_mutation(), &decoded)
+            .expect("restore snapshot");
+        assert_eq!(restored.context_value("items"), Some(items.clone()));
+
+        assert_eq!(
+            items.get_member(&Value::Number(2.0)),
+            Err(RuntimeError::ListIndexOutOfBounds {
+                index: "2".to_string(),
+                len: 2,
+            })
+        );
+        assert_eq!(
+            items.get_member(&Value::String("0".to_string())),
+            Err(RuntimeError::InvalidMemberKey("String".to_string()))
+        );
+    }
+
+    #[test]
+    fn restore_rejects_snapshots_from_other_programs() {
+        let mut machine =
+            StoryMachine::new(program_with_context_mutation()).expect("build interpreted machine");
+        machine.start().expect("start story");
+        let snapshot = machine.snapshot().expect("take snapshot");
+
+        let error = StoryMachine::restore(program_with_nested_goto(), &snapshot)
+            .expect_err("restoring into another program should fail");
+        assert!(matches!(error, RuntimeError::IncompatibleSnapshot { .. }));
+
+        let mut retitled = program_with_context_mutation();
+        let StepSpec::Dialogue(dialogue) = &mut retitled.parts[0].steps[0] else {
+            panic!("expected dialogue step");
+        };
+        dialogue.quote.text = "Hello again!".to_string();
+        StoryMachine::restore(retitled, &snapshot).expect("text edits keep snapshots compatible");
+    }
+
+    #[test]
+    fn snapshot_rejects_closures_in_context() {
+        let mut machine = StoryMachine::new(program_with_nested_goto()).expect("build machine");
+        machine.start().expect("start story");
+        machine.context.borrow_mut().insert(
+            "callback".to_string(),
+            Value::Closure(crate::ClosureValue {
+                function_id: 1,
+                captured: machine.globals.clone(),
+            }),
+        );
+
+        assert_eq!(
+            machine.snapshot(),
+            Err(RuntimeError::UnserializableValue("Closure".to_string()))
+        );
+    }
+
+    #[test]
+    fn failing_steps_leave_no_history_entry_to_rewind_onto() {
+        let context = BTreeMap::from([(
+            "stats".to_string(),
+            Value::object(BTreeMap::from([("hp".to_string(), Value::Number(10.0))])),
+        )]);
+        let mut machine = StoryMachine::with_context(program_with_nested_mutation_loop(), context)
+            .expect("build interpreted machine");
+
+        machine.start().expect("start story");
+        machine.advance().expect("reach selection");
+        machine.choose(0).expect("first hit");
+        machine.advance().expect("reach selection again");
+        assert_eq!(machine.history_len(), 3);
+
+        machine.set_context_value("stats", Value::String("broken".to_string()));
+        assert!(machine.choose(0).is_err());
+        assert_eq!(machine.history_len(), 3);
+
+        let event = machine.rewind(1).expect("undo reaching the selection");
+        assert!(matches!(event, StoryEvent::Narration(_)));
+        let Some(Value::Object(stats)) = machine.context_value("stats") else {
+            panic!("expected stats object");
+        };
+        assert_eq!(stats.borrow().get("hp"), Some(&Value::Number(7.0)));
+    }
+
+    #[test]
+    fn rewind_restores_nested_context_mutated_in_place() {
+        let context = BTreeMap::from([(
+            "stats".to_string(),
+            Value::object(BTreeMap::from([("hp".to_string(), Value::Number(10.0))])),
+        )]);
+        let mut machine = StoryMachine::with_context(program_with_nested_mutation_loop(), context)
+            .expect("build interpreted machine");
+        let hp = |machine: &StoryMachine| {
+            let Some(Value::Object(stats)) = machine.context_value("stats") else {
+                panic!("expected stats object");
+            };
+            let hp = stats.borrow().get("hp").cloned();
+            hp
+        };
+
+        machine.start().expect("start story");
+        machine.advance().expect("reach selection");
+        machine.choose(0).expect("first hit");
+        machine.advance().expect("reach selection again");
+        machine.choose(0).expect("second hit");
+        assert_eq!(hp(&machine), Some(Value::Number(4.0)));
+        assert_eq!(machine.history_len(), 4);
+
+        let event = machine.rewind(1).expect("undo second hit");
+        assert!(matches!(event, StoryEvent::Selection(_)));
+        assert_eq!(hp(&machine), Some(Value::Number(7.0)));
+
+        machine.rewind(2).expect("undo first hit");
+        assert_eq!(hp(&machine), Some(Value::Number(10.0)));
+        assert_eq!(machine.history_len(), 1);
+
+        assert_eq!(
+            machine.rewind(2),
+            Err(RuntimeError::HistoryExhausted {
+                requested: 2,
+                available: 1,
+            })
+        );
+
+        machine.set_history_limit(1);
+        machine.choose(0).expect("hit after rewind");
+        machine.advance().expect("reach selection after rewind");
+        assert_eq!(machine.history_len(), 1);
+    }
+
+    #[test]
+    fn seeded_rolls_replay_through_snapshots_and_rewind() {
+        let roll = |machine: &mut StoryMachine| {
+            machine.advance().expect("reach selection");
+            machine.choose(0).expect("roll");
+            machine.context_value("roll")
+        };
+
+        let mut machine = StoryMachine::with_seed(program_with_dice_loop(), BTreeMap::new(), 7)
+            .expect("build seeded machine");
+        machine.start().expect("start story");
+        let first = roll(&mut machine);
+        let snapshot = machine.snapshot().expect("take snapshot");
+        let second = roll(&mut machine);
+
+        let Some(Value::Number(value)) = first else {
+            panic!("expected a numeric roll");
+        };
+        assert!((1.0..=100.0).contains(&value) && value.fract() == 0.0);
+
+        let mut replay = StoryMachine::with_seed(program_with_dice_loop(), BTreeMap::new(), 7)
+            .expect("build seeded machine");
+        replay.start().expect("start story");
+        assert_eq!(roll(&mut replay), first);
+        assert_eq!(roll(&mut replay), second);
+
+        let mut restored =
+            StoryMachine::restore(program_with_dice_loop(), &snapshot).expect("restore snapshot");
+        assert_eq!(roll(&mut restored), second);
+
+        machine.rewind(2).expect("rewind second roll");
+        assert_eq!(roll(&mut machine), second);
+    }
+
+    #[test]
+    fn restored_machines_can_be_seeded_and_older_snapshot_versions_load() {
+        let roll = |machine: &mut StoryMachine| {
+            machine.advance().expect("reach selection");
+            machine.choose(0).expect("roll");
+            machine.context_value("roll")
+        };
+
+        let mut machine = StoryMachine::new(program_with_dice_loop()).expect("build machine");
+        let mut snapshot = machine.snapshot().expect("take snapshot");
+        assert_eq!(snapshot.format_version, STORY_SNAPSHOT_FORMAT_VERSION);
+
+        let mut seeded = StoryMachine::with_seed(program_with_dice_loop(), BTreeMap::new(), 7)
+            .expect("build seeded machine");
+        let mut restored = StoryMachine::restore(program_with_dice_loop(), &snapshot)
+            .expect("restore snapshot")
+            .seeded(7);
+        for machine in [&mut seeded, &mut restored] {
+            machine.start().expect("start story");
+        }
+        assert_eq!(roll(&mut restored), roll(&mut seeded));
+
+        snapshot.format_version = 1;
+        snapshot.rng_state = None;
+        machine
+            .load_snapshot(&snapshot)
+            .expect("version 1 snapshots still load");
+
+        snapshot.format_version = STORY_SNAPSHOT_FORMAT_VERSION + 1;
+        assert_eq!(
+            machine.load_snapshot(&snapshot),
+            Err(RuntimeError::UnsupportedSnapshotVersion(
+                STORY_SNAPSHOT_FORMAT_VERSION + 1
+            ))
+        );
+    }
+
+    fn program_with_dice_loop() -> StoryProgram {
+        StoryProgram {
+            start_part: "table".to_string(),
+            metadata: BTreeMap::new(),
+            parts: vec![PartSpec {
+                id: "table".to_string(),
+                properties: BTreeMap::new(),
+                on_enter: None,
+                on_exit: None,
+                steps: vec![
+                    StepSpec::Narration(QuoteSpec {
+                        node_id: 0,
+                        text: "The dice are cold.".to_string(),
+                        segments: Vec::new(),
+                        properties: BTreeMap::new(),
+                        next_action: None,
+                        guard: None,
+                        once: false,
+                        fallback: false,
+                    }),
+                    StepSpec::Selection(SelectionSpec {
+                        choices: vec![QuoteSpec {
+                            node_id: 1,
+                            text: "Roll".to_string(),
+                            segments: Vec::new(),
+                            properties: BTreeMap::new(),
+                            next_action: Some(0),
+                            guard: None,
+                            once: false,
+                            fallback: false,
+                        }],
+                    }),
+                ],
+            }],
+            functions: vec![FunctionSpec {
+                id: 0,
+                node_id: 1,
+                params: Vec::new(),
+                body: Block {
+                    statements: vec![
+                        Stmt::Expr(Expr::Assignment {
+                            target: Box::new(Expr::MemberAccess {
+                                base: Box::new(Expr::Context),
+                                members: vec![MemberSegment::Key("roll".to_string())],
+                            }),
+                            value: Box::new(Expr::Call {
+                                callee: Box::new(Expr::Identifier("random_int".to_string())),
+                                arguments: vec![
+                                    Expr::Literal(Literal::Number(1.0)),
+                                    Expr::Literal(Literal::Number(100.0)),
+                                ],
+                            }),
+                        }),
+                        Stmt::Goto(Expr::StoryReference("table".to_string())),
+                    ],
+                },
+            }],
+        }
+    }
+
+    fn program_with_nested_mutation_loop() -> StoryProgram {
+        let hp = || Expr::MemberAccess {
+            base: Box::new(Expr::Context),
+            members: vec![
+                MemberSegment::Key("stats".to_string()),
+                MemberSegment::Key("hp".to_string()),
+            ],
+        };
+
+        StoryProgram {
+            start_part: "arena".to_string(),
+            metadata: BTreeMap::new(),
+            parts: vec![PartSpec {
+                id: "arena".to_string(),
+                properties: BTreeMap::new(),
+                on_enter: None,
+                on_exit: None,
+                steps: vec![
+                    StepSpec::Narration(QuoteSpec {
+                        node_id: 0,
+                        text: "The goblin snarls.".to_string(),
+                        segments: Vec::new(),
+                        properties: BTreeMap::new(),
+                        next_action: None,
+                        guard: None,
+                        once: false,
+                        fallback: false,
+                    }),
+                    StepSpec::Selection(SelectionSpec {
+                        choices: vec![QuoteSpec {
+                            node_id: 1,
+                            text: "Take a hit".to_string(),
+                            segments: Vec::new(),
+                            properties: BTreeMap::new(),
+                            next_action: Some(0),
+                            guard: None,
+                            once: false,
+                            fallback: false,
+                        }],
+                    }),
+                ],
+            }],
+            functions: vec![FunctionSpec {
+                id: 0,
+                node_id: 1,
+                params: Vec::new(),
+                body: Block {
+                    statements: vec![
+                        Stmt::Expr(Expr::Assignment {
+                            target: Box::new(hp()),
+                            value: Box::new(Expr::Binary {
+                                left: Box::new(hp()),
+                                operator: BinaryOperator::Subtract,
+                                right: Box::new(Expr::Literal(Literal::Number(3.0))),
+                            }),
+                        }),
+                        Stmt::Goto(Expr::StoryReference("arena".to_string())),
+                    ],
+                },
+            }],
+        }
     }
 
     fn program_with_context_mutation() -> StoryProgram {
@@ -775,35 +2005,53 @@ mod tests {
             parts: vec![
                 PartSpec {
                     id: "part_1".to_string(),
+                    properties: BTreeMap::new(),
+                    on_enter: None,
+                    on_exit: None,
                     steps: vec![
                         StepSpec::Dialogue(DialogueSpec {
                             speaker: "Hero".to_string(),
                             quote: QuoteSpec {
                                 node_id: 0,
                                 text: "Hello there!".to_string(),
+                                segments: Vec::new(),
                                 properties: BTreeMap::new(),
                                 next_action: None,
+                                guard: None,
+                                once: false,
+                                fallback: false,
                             },
                         }),
                         StepSpec::Selection(SelectionSpec {
                             choices: vec![QuoteSpec {
                                 node_id: 1,
                                 text: "Hi!".to_string(),
+                                segments: Vec::new(),
                                 properties: BTreeMap::new(),
                                 next_action: Some(0),
+                                guard: None,
+                                once: false,
+                                fallback: false,
                             }],
                         }),
                     ],
                 },
                 PartSpec {
                     id: "part_2".to_string(),
+                    properties: BTreeMap::new(),
+                    on_enter: None,
+                    on_exit: None,
                     steps: vec![StepSpec::Dialogue(DialogueSpec {
                         speaker: "Villain".to_string(),
                         quote: QuoteSpec {
                             node_id: 2,
                             text: "I've been expecting you.".to_string(),
+                            segments: Vec::new(),
                             properties: BTreeMap::new(),
                             next_action: None,
+                            guard: None,
+                            once: false,
+                            fallback: false,
                         },
                     })],
                 },
@@ -847,35 +2095,53 @@ mod tests {
             parts: vec![
                 PartSpec {
                     id: "part_1".to_string(),
+                    properties: BTreeMap::new(),
+                    on_enter: None,
+                    on_exit: None,
                     steps: vec![
                         StepSpec::Dialogue(DialogueSpec {
                             speaker: "Guide".to_string(),
                             quote: QuoteSpec {
                                 node_id: 0,
                                 text: "Choose carefully.".to_string(),
+                                segments: Vec::new(),
                                 properties: BTreeMap::new(),
                                 next_action: None,
+                                guard: None,
+                                once: false,
+                                fallback: false,
                             },
                         }),
                         StepSpec::Selection(SelectionSpec {
                             choices: vec![QuoteSpec {
                                 node_id: 1,
                                 text: "Jump".to_string(),
+                                segments: Vec::new(),
                                 properties: BTreeMap::new(),
                                 next_action: Some(0),
+                                guard: None,
+                                once: false,
+                                fallback: false,
                             }],
                         }),
                     ],
                 },
                 PartSpec {
                     id: "part_2".to_string(),
+                    properties: BTreeMap::new(),
+                    on_enter: None,
+                    on_exit: None,
                     steps: vec![StepSpec::Dialogue(DialogueSpec {
                         speaker: "Guide".to_string(),
                         quote: QuoteSpec {
                             node_id: 2,
                             text: "Nested goto worked.".to_string(),
+                            segments: Vec::new(),
                             properties: BTreeMap::new(),
                             next_action: None,
+                            guard: None,
+                            once: false,
+                            fallback: false,
                         },
                     })],
                 },
@@ -917,18 +2183,209 @@ mod tests {
         }
     }
 
+    fn program_with_host_call() -> StoryProgram {
+        StoryProgram {
+            start_part: "intro".to_string(),
+            metadata: BTreeMap::new(),
+            parts: vec![PartSpec {
+                id: "intro".to_string(),
+                properties: BTreeMap::new(),
+                on_enter: None,
+                on_exit: None,
+                steps: vec![StepSpec::Narration(QuoteSpec {
+                    node_id: 0,
+                    text: "You find a sword.".to_string(),
+                    segments: Vec::new(),
+                    properties: BTreeMap::new(),
+                    next_action: Some(0),
+                    guard: None,
+                    once: false,
+                    fallback: false,
+                })],
+            }],
+            functions: vec![FunctionSpec {
+                id: 0,
+                node_id: 0,
+                params: Vec::new(),
+                body: Block {
+                    statements: vec![Stmt::Expr(Expr::Assignment {
+                        target: Box::new(Expr::MemberAccess {
+                            base: Box::new(Expr::Context),
+                            members: vec![MemberSegment::Key("result".to_string())],
+                        }),
+                        value: Box::new(Expr::Call {
+                            callee: Box::new(Expr::Identifier("give_item".to_string())),
+                            arguments: vec![Expr::Literal(Literal::String("sword".to_string()))],
+                        }),
+                    })],
+                },
+            }],
+        }
+    }
+
+    /// A single narration whose `next` runs `statements`.
+    fn program_with_next(statements: Vec<Stmt>) -> StoryProgram {
+        StoryProgram {
+            start_part: "intro".to_string(),
+            metadata: BTreeMap::new(),
+            parts: vec![PartSpec {
+                id: "intro".to_string(),
+                properties: BTreeMap::new(),
+                on_enter: None,
+                on_exit: None,
+                steps: vec![StepSpec::Narration(QuoteSpec {
+                    node_id: 0,
+                    text: "Hello.".to_string(),
+                    segments: Vec::new(),
+                    properties: BTreeMap::new(),
+                    next_action: Some(0),
+                    guard: None,
+                    once: false,
+                    fallback: false,
+                })],
+            }],
+            functions: vec![FunctionSpec {
+                id: 0,
+                node_id: 0,
+                params: Vec::new(),
+                body: Block { statements },
+            }],
+        }
+    }
+
+    /// A narration whose `next` runs `while (true) {}`.
+    fn program_with_endless_loop() -> StoryProgram {
+        StoryProgram {
+            start_part: "intro".to_string(),
+            metadata: BTreeMap::new(),
+            parts: vec![PartSpec {
+                id: "intro".to_string(),
+                properties: BTreeMap::new(),
+                on_enter: None,
+                on_exit: None,
+                steps: vec![StepSpec::Narration(QuoteSpec {
+                    node_id: 0,
+                    text: "Time stands still.".to_string(),
+                    segments: Vec::new(),
+                    properties: BTreeMap::new(),
+                    next_action: Some(0),
+                    guard: None,
+                    once: false,
+                    fallback: false,
+                })],
+            }],
+            functions: vec![FunctionSpec {
+                id: 0,
+                node_id: 0,
+                params: Vec::new(),
+                body: Block {
+                    statements: vec![Stmt::While {
+                        condition: Expr::Literal(Literal::Boolean(true)),
+                        body: Block {
+                            statements: Vec::new(),
+                        },
+                    }],
+                },
+            }],
+        }
+    }
+
+    fn program_with_part_properties() -> StoryProgram {
+        StoryProgram {
+            start_part: "intro".to_string(),
+            metadata: BTreeMap::new(),
+            parts: vec![PartSpec {
+                id: "intro".to_string(),
+                properties: BTreeMap::from([(
+                    "background".to_string(),
+                    Expr::Literal(Literal::String("bg_alley".to_string())),
+                )]),
+                on_enter: None,
+                on_exit: Some(0),
+                steps: vec![StepSpec::Narration(QuoteSpec {
+                    node_id: 0,
+                    text: "Rain drips from the gutters.".to_string(),
+                    segments: Vec::new(),
+                    properties: BTreeMap::new(),
+                    next_action: None,
+                    guard: None,
+                    once: false,
+                    fallback: false,
+                })],
+            }],
+            functions: vec![FunctionSpec {
+                id: 0,
+                node_id: 1,
+                params: Vec::new(),
+                body: Block {
+                    statements: vec![Stmt::Expr(Expr::Assignment {
+                        target: Box::new(Expr::MemberAccess {
+                            base: Box::new(Expr::Context),
+                            members: vec![MemberSegment::Key("left".to_string())],
+                        }),
+                        value: Box::new(Expr::Literal(Literal::Boolean(true))),
+                    })],
+                },
+            }],
+        }
+    }
+
+    fn program_with_directive() -> StoryProgram {
+        StoryProgram {
+            start_part: "intro".to_string(),
+            metadata: BTreeMap::new(),
+            parts: vec![PartSpec {
+                id: "intro".to_string(),
+                properties: BTreeMap::new(),
+                on_enter: None,
+                on_exit: None,
+                steps: vec![
+                    StepSpec::Directive(DirectiveSpec {
+                        node_id: 0,
+                        name: "play_music".to_string(),
+                        args: vec![
+                            Expr::MemberAccess {
+                                base: Box::new(Expr::Context),
+                                members: vec![MemberSegment::Key("weather".to_string())],
+                            },
+                            Expr::Literal(Literal::Number(0.5)),
+                        ],
+                    }),
+                    StepSpec::Narration(QuoteSpec {
+                        node_id: 1,
+                        text: "It starts to pour.".to_string(),
+                        segments: Vec::new(),
+                        properties: BTreeMap::new(),
+                        next_action: None,
+                        guard: None,
+                        once: false,
+                        fallback: false,
+                    }),
+                ],
+            }],
+            functions: Vec::new(),
+        }
+    }
+
     fn program_with_selection_only() -> StoryProgram {
         StoryProgram {
             start_part: "intro".to_string(),
             metadata: BTreeMap::new(),
             parts: vec![PartSpec {
                 id: "intro".to_string(),
+                properties: BTreeMap::new(),
+                on_enter: None,
+                on_exit: None,
                 steps: vec![StepSpec::Selection(SelectionSpec {
                     choices: vec![QuoteSpec {
                         node_id: 0,
                         text: "Only option".to_string(),
+                        segments: Vec::new(),
                         properties: BTreeMap::new(),
                         next_action: None,
+                        guard: None,
+                        once: false,
+                        fallback: false,
                     }],
                 })],
             }],
@@ -942,11 +2399,18 @@ mod tests {
             metadata: BTreeMap::new(),
             parts: vec![PartSpec {
                 id: "intro".to_string(),
+                properties: BTreeMap::new(),
+                on_enter: None,
+                on_exit: None,
                 steps: vec![StepSpec::Narration(QuoteSpec {
                     node_id: 0,
                     text: "Done".to_string(),
+                    segments: Vec::new(),
                     properties: BTreeMap::new(),
                     next_action: None,
+                    guard: None,
+                    once: false,
+                    fallback: false,
                 })],
             }],
             functions: Vec::new(),
@@ -960,31 +2424,52 @@ mod tests {
             parts: vec![
                 PartSpec {
                     id: "intro".to_string(),
+                    properties: BTreeMap::new(),
+                    on_enter: None,
+                    on_exit: None,
                     steps: vec![StepSpec::Selection(SelectionSpec {
                         choices: vec![QuoteSpec {
                             node_id: 0,
                             text: "Go".to_string(),
+                            segments: Vec::new(),
                             properties: BTreeMap::new(),
                             next_action: Some(0),
+                            guard: None,
+                            once: false,
+                            fallback: false,
                         }],
                     })],
                 },
                 PartSpec {
                     id: "connected".to_string(),
+                    properties: BTreeMap::new(),
+                    on_enter: None,
+                    on_exit: None,
                     steps: vec![StepSpec::Narration(QuoteSpec {
                         node_id: 1,
                         text: "Reached the connected ending.".to_string(),
+                        segments: Vec::new(),
                         properties: BTreeMap::new(),
                         next_action: None,
+                        guard: None,
+                        once: false,
+                        fallback: false,
                     })],
                 },
                 PartSpec {
                     id: "dangling".to_string(),
+                    properties: BTreeMap::new(),
+                    on_enter: None,
+                    on_exit: None,
                     steps: vec![StepSpec::Narration(QuoteSpec {
                         node_id: 2,
                         text: "This dangling part should never render.".to_string(),
+                        segments: Vec::new(),
                         properties: BTreeMap::new(),
                         next_action: None,
+                        guard: None,
+                        once: false,
+                        fallback: false,
                     })],
                 },
             ],
@@ -12,6 +12,8 @@ pub enum RuntimeError {
     NotInSelection,
     #[error("choice index {index} is out of bounds for {len} choices")]
     InvalidChoice { index: usize, len: usize },
+    #[error("choice {index} is currently unavailable")]
+    DisabledChoice { index: usize },
     #[error("story part `{0}` does not exist")]
     UnknownPart(String),
     #[error("undefined variable `{0}`")]
@@ -20,12 +22,16 @@ pub enum RuntimeError {
     InvalidCallee(String),
     #[error("closure expected {expected} arguments but received {got}")]
     ArityMismatch { expected: usize, got: usize },
+    #[error("builtin namespace `{0}` is read-only")]
+    ReadOnlyNamespace(String),
     #[error("invalid assignment target")]
     InvalidAssignmentTarget,
     #[error("cannot read member `{member}` from `{target}`")]
     InvalidMemberAccess { target: String, member: String },
     #[error("invalid member key from `{0}`")]
     InvalidMemberKey(String),
+    #[error("list index {index} is out of bounds for {len} elements")]
+    ListIndexOutOfBounds { index: String, len: usize },
     #[error("cannot use `{0}` as a story target")]
     InvalidStoryTarget(String),
     #[error("cannot cast `{0}` to a number")]
@@ -38,10 +44,35 @@ pub enum RuntimeError {
         left: String,
         right: String,
     },
+    #[error("`{builtin}` expected a `{expected}` argument but received `{found}`")]
+    InvalidBuiltinArgument {
+        builtin: String,
+        expected: &'static str,
+        found: String,
+    },
+    #[error("`random_int` range {min}..={max} contains no integers")]
+    InvalidRandomRange { min: String, max: String },
+    /// Raised by a function registered with `StoryMachine::register_function`.
+    #[error("{0}")]
+    Host(String),
     #[error("native closure execution failed: {0}")]
     NativeExecution(String),
+    #[error("loop ran past the limit of {limit} iterations")]
+    LoopLimitExceeded { limit: usize },
+    #[error("fallback choice led back to the exhausted selection in part `{part}`")]
+    FallbackLoop { part: String },
     #[error("unexpected control flow while evaluating metadata")]
     UnexpectedControlFlow,
+    #[error("cannot save `{0}` values in a story snapshot")]
+    UnserializableValue(String),
+    #[error("unsupported story snapshot format version {0}")]
+    UnsupportedSnapshotVersion(u32),
+    #[error("story snapshot was taken from a different program (expected fingerprint {expected:016x}, found {found:016x})")]
+    IncompatibleSnapshot { expected: u64, found: u64 },
+    #[error("cannot rewind {requested} steps with only {available} recorded")]
+    HistoryExhausted { requested: usize, available: usize },
+    #[error("story snapshot points at step {step_index} of part `{part}`, which does not exist")]
+    InvalidSnapshotCursor { part: String, step_index: usize },
 }
 
 pub type Result<T> = StdResult<T, RuntimeError>;
@@ -0,0 +1,510 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+
+use super::{
+    engine::{StateKey, StoryEvent, StoryMachine},
+    error::RuntimeError,
+    visits::program_reads_visits,
+};
+
+/// Bounds for [`explore`]; exploration stops early and sets [`ExploreReport::truncated`]
+/// once either is hit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExploreOptions {
+    /// Most steps (advances and choices) followed along any single playthrough.
+    pub max_depth: usize,
+    /// Most distinct story states visited across all playthroughs.
+    pub max_states: usize,
+}
+
+impl Default for ExploreOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: 1_000,
+            max_states: 100_000,
+        }
+    }
+}
+
+/// A choice taken on the way to a finding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathChoice {
+    pub part: String,
+    pub index: usize,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExploredEnding {
+    /// The last part shown before the story finished.
+    pub part: String,
+    /// The shortest sequence of choices that reaches this ending.
+    pub path: Vec<PathChoice>,
+    /// How many distinct explored states finish here. Playthroughs that converge on the same
+    /// state are only followed once, so they count once.
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExploredError {
+    /// The part whose step raised the error.
+    pub part: Option<String>,
+    pub error: RuntimeError,
+    pub path: Vec<PathChoice>,
+}
+
+/// A cycle of gotos that revisits the same state without the reader making a choice.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExploredLoop {
+    /// The parts of the cycle in the order they are visited.
+    pub parts: Vec<String>,
+    pub path: Vec<PathChoice>,
+}
+
+/// A selection in which every choice is hidden or disabled, leaving the reader stuck.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExploredDeadEnd {
+    pub part: String,
+    pub path: Vec<PathChoice>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExploreReport {
+    pub endings: Vec<ExploredEnding>,
+    pub errors: Vec<ExploredError>,
+    pub loops: Vec<ExploredLoop>,
+    pub dead_ends: Vec<ExploredDeadEnd>,
+    /// Parts, in declaration order, that no explored playthrough entered.
+    pub unvisited_parts: Vec<String>,
+    /// Number of distinct states visited.
+    pub states: usize,
+    /// `true` when a limit cut exploration short, so the findings may be incomplete.
+    pub truncated: bool,
+}
+
+impl ExploreReport {
+    /// Whether exploration found anything a reader could get stuck on.
+    pub fn has_problems(&self) -> bool {
+        !self.errors.is_empty() || !self.loops.is_empty() || !self.dead_ends.is_empty()
+    }
+}
+
+struct Branch {
+    machine: StoryMachine,
+    event: StoryEvent,
+    path: Vec<PathChoice>,
+    depth: usize,
+}
+
+/// Walks every playthrough of `machine`'s story from its start part, forking at each enabled
+/// choice.
+///
+/// Playthroughs are explored breadth-first, so every reported path is a shortest one, and
+/// states are deduplicated by cursor, globals, context and random state, plus visit counts for
+/// stories that call `visits` or `seen`. The machine itself is left untouched; its context and
+/// seed are the starting point for every playthrough.
+pub fn explore(machine: &StoryMachine, options: ExploreOptions) -> ExploreReport {
+    let mut explorer = Explorer {
+        reads_visits: program_reads_visits(machine.program()),
+        ..Explorer::default()
+    };
+
+    let mut root = machine.fork();
+    root.set_history_limit(0);
+    let start_part = root.program().start_part.clone();
+    match root.start() {
+        Ok(event) => explorer.queue.push_back(Branch {
+            machine: root,
+            event,
+            path: Vec::new(),
+            depth: 0,
+        }),
+        Err(error) => explorer.error(Some(start_part), error, &[]),
+    }
+
+    while let Some(branch) = explorer.queue.pop_front() {
+        if !explorer.follow(branch, options) {
+            explorer.report.truncated = true;
+            break;
+        }
+    }
+
+    explorer.report.states = explorer.seen.len();
+    explorer.report.unvisited_parts = machine
+        .program()
+        .parts
+        .iter()
+        .filter(|part| !explorer.visited.contains(&part.id))
+        .map(|part| part.id.clone())
+        .collect();
+    explorer.report.endings = explorer.endings.into_values().collect();
+    explorer.report
+}
+
+#[derive(Default)]
+struct Explorer {
+    queue: VecDeque<Branch>,
+    seen: HashSet<StateKey>,
+    visited: HashSet<String>,
+    endings: BTreeMap<String, ExploredEnding>,
+    report: ExploreReport,
+    reads_visits: bool,
+}
+
+impl Explorer {
+    /// Plays one branch up to its next selection, queueing a branch per enabled choice.
+    /// Returns `false` once the state limit is reached.
+    fn follow(&mut self, branch: Branch, options: ExploreOptions) -> bool {
+        let Branch {
+            mut machine,
+            mut event,
+            path,
+            mut depth,
+        } = branch;
+        // States since the last choice; seeing one twice means gotos alone cycle forever.
+        let mut segment = HashMap::new();
+        let mut segment_parts = Vec::new();
+        let mut part = String::new();
+
+        loop {
+            if let Some(current) = machine.current_part() {
+                part = current.to_string();
+                self.visited.insert(part.clone());
+            }
+
+            if let StoryEvent::Finished = event {
+                self.ending(part, &path);
+                return true;
+            }
+
+            let key = machine.state_key(self.reads_visits);
+            if let Some(&start) = segment.get(&key) {
+                self.infinite_loop(&segment_parts[start..], &path);
+                return true;
+            }
+            if !self.seen.insert(key.clone()) {
+                return true;
+            }
+            if self.seen.len() > options.max_states {
+                return false;
+            }
+            segment.insert(key, segment_parts.len());
+            segment_parts.push(part.clone());
+
+            if depth >= options.max_depth {
+                self.report.truncated = true;
+                return true;
+            }
+            depth += 1;
+
+            let StoryEvent::Selection(selection) = &event else {
+                match machine.advance() {
+                    Ok(next) => event = next,
+                    Err(error) => {
+                        self.error(Some(part), error, &path);
+                        return true;
+                    }
+                }
+                continue;
+            };
+
+            let mut enabled = false;
+            for (index, choice) in selection.choices.iter().enumerate() {
+                if !choice.enabled {
+                    continue;
+                }
+                enabled = true;
+
+                let mut path = path.clone();
+                path.push(PathChoice {
+                    part: part.clone(),
+                    index,
+                    text: choice.text.clone(),
+                });
+                let mut fork = machine.fork();
+                match fork.choose(index) {
+                    Ok(event) => self.queue.push_back(Branch {
+                        machine: fork,
+                        event,
+                        path,
+                        depth,
+                    }),
+                    Err(error) => self.error(Some(part.clone()), error, &path),
+                }
+            }
+            if !enabled && !self.report.dead_ends.iter().any(|dead| dead.part == part) {
+                self.report.dead_ends.push(ExploredDeadEnd { part, path });
+            }
+            return true;
+        }
+    }
+
+    fn ending(&mut self, part: String, path: &[PathChoice]) {
+        self.endings
+            .entry(part.clone())
+            .or_insert_with(|| ExploredEnding {
+                part,
+                path: path.to_vec(),
+                count: 0,
+            })
+            .count += 1;
+    }
+
+    /// Records `error` unless the same error was already found in the same part.
+    fn error(&mut self, part: Option<String>, error: RuntimeError, path: &[PathChoice]) {
+        if self
+            .report
+            .errors
+            .iter()
+            .any(|found| found.part == part && found.error == error)
+        {
+            return;
+        }
+        self.report.errors.push(ExploredError {
+            part,
+            error,
+            path: path.to_vec(),
+        });
+    }
+
+    fn infinite_loop(&mut self, cycle: &[String], path: &[PathChoice]) {
+        let mut parts = cycle.to_vec();
+        parts.dedup();
+        let key = parts.iter().collect::<BTreeSet<_>>();
+        if self
+            .report
+            .loops
+            .iter()
+            .any(|found| found.parts.iter().collect::<BTreeSet<_>>() == key)
+        {
+            return;
+        }
+        self.report.loops.push(ExploredLoop {
+            parts,
+            path: path.to_vec(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use fabc_ir::{
+        Block, Expr, FunctionSpec, MemberSegment, PartSpec, QuoteSpec, SelectionSpec, StepSpec,
+        Stmt, StoryProgram,
+    };
+
+    use super::{explore, ExploreOptions};
+    use crate::{RuntimeError, StoryMachine, Value};
+
+    #[test]
+    fn explores_every_ending_and_unvisited_part() {
+        let machine = branching_machine(false);
+
+        let report = explore(&machine, ExploreOptions::default());
+
+        let endings = report
+            .endings
+            .iter()
+            .map(|ending| (ending.part.as_str(), ending.path.len()))
+            .collect::<Vec<_>>();
+        assert_eq!(endings, vec![("left", 1), ("right", 1)]);
+        assert_eq!(report.endings[0].path[0].text, "Left");
+        assert_eq!(report.unvisited_parts, vec!["orphan".to_string()]);
+        assert!(report.errors.is_empty());
+        assert!(!report.has_problems());
+        assert!(!report.truncated);
+    }
+
+    #[test]
+    fn reports_runtime_errors_with_the_path_that_raises_them() {
+        let machine = branching_machine(true);
+
+        let report = explore(&machine, ExploreOptions::default());
+
+        assert_eq!(report.errors.len(), 1);
+        let error = &report.errors[0];
+        assert_eq!(error.part.as_deref(), Some("intro"));
+        assert_eq!(
+            error.error,
+            RuntimeError::UnknownPart("missing".to_string())
+        );
+        assert_eq!(error.path.len(), 1);
+        assert_eq!(error.path[0].text, "Right");
+        assert!(report.has_problems());
+    }
+
+    #[test]
+    fn detects_goto_cycles_without_choices() {
+        let machine = StoryMachine::new(looping_program()).expect("build machine");
+
+        let report = explore(&machine, ExploreOptions::default());
+
+        assert_eq!(report.loops.len(), 1);
+        assert_eq!(report.loops[0].parts, vec!["ping", "pong"]);
+        assert!(report.endings.is_empty());
+    }
+
+    #[test]
+    fn detects_goto_cycles_while_the_context_holds_nan() {
+        let context = BTreeMap::from([("ratio".to_string(), Value::Number(f64::NAN))]);
+        let machine =
+            StoryMachine::with_context(looping_program(), context).expect("build machine");
+
+        let report = explore(&machine, ExploreOptions::default());
+
+        assert_eq!(report.loops.len(), 1);
+        assert!(!report.truncated);
+    }
+
+    #[test]
+    fn stops_at_the_state_limit() {
+        let machine = branching_machine(false);
+
+        let report = explore(
+            &machine,
+            ExploreOptions {
+                max_states: 1,
+                ..ExploreOptions::default()
+            },
+        );
+
+        assert!(report.truncated);
+        assert!(report.states <= 2);
+    }
+
+    #[test]
+    fn exploring_leaves_the_machine_untouched() {
+        let mut machine = branching_machine(false);
+
+        explore(&machine, ExploreOptions::default());
+
+        assert_eq!(machine.current_part(), None);
+        assert_eq!(machine.context_value("broken"), Some(Value::Boolean(false)));
+        assert!(machine.start().is_ok());
+    }
+
+    fn quote(node_id: usize, text: &str, next_action: Option<usize>) -> QuoteSpec {
+        QuoteSpec {
+            node_id,
+            text: text.to_string(),
+            segments: Vec::new(),
+            properties: BTreeMap::new(),
+            next_action,
+            guard: None,
+            once: false,
+            fallback: false,
+        }
+    }
+
+    fn goto(id: usize, target: &str) -> FunctionSpec {
+        FunctionSpec {
+            id,
+            node_id: id,
+            params: Vec::new(),
+            body: Block {
+                statements: vec![Stmt::Goto(Expr::StoryReference(target.to_string()))],
+            },
+        }
+    }
+
+    fn branching_machine(broken: bool) -> StoryMachine {
+        let mut context = BTreeMap::new();
+        context.insert("broken".to_string(), Value::Boolean(broken));
+        StoryMachine::with_context(branching_program(), context).expect("build machine")
+    }
+
+    /// `intro` offers "Left" and "Right"; "Right" jumps to a missing part when
+    /// `context.broken` is set.
+    fn branching_program() -> StoryProgram {
+        let context_broken = Expr::MemberAccess {
+            base: Box::new(Expr::Context),
+            members: vec![MemberSegment::Key("broken".to_string())],
+        };
+        StoryProgram {
+            start_part: "intro".to_string(),
+            metadata: BTreeMap::new(),
+            parts: vec![
+                PartSpec {
+                    id: "intro".to_string(),
+                    properties: BTreeMap::new(),
+                    on_enter: None,
+                    on_exit: None,
+                    steps: vec![
+                        StepSpec::Narration(quote(0, "Two doors.", None)),
+                        StepSpec::Selection(SelectionSpec {
+                            choices: vec![quote(1, "Left", Some(0)), quote(2, "Right", Some(1))],
+                        }),
+                    ],
+                },
+                PartSpec {
+                    id: "left".to_string(),
+                    properties: BTreeMap::new(),
+                    on_enter: None,
+                    on_exit: None,
+                    steps: vec![StepSpec::Narration(quote(3, "Left room.", None))],
+                },
+                PartSpec {
+                    id: "right".to_string(),
+                    properties: BTreeMap::new(),
+                    on_enter: None,
+                    on_exit: None,
+                    steps: vec![StepSpec::Narration(quote(4, "Right room.", None))],
+                },
+                PartSpec {
+                    id: "orphan".to_string(),
+                    properties: BTreeMap::new(),
+                    on_enter: None,
+                    on_exit: None,
+                    steps: vec![StepSpec::Narration(quote(5, "Never shown.", None))],
+                },
+            ],
+            functions: vec![
+                goto(0, "left"),
+                FunctionSpec {
+                    id: 1,
+                    node_id: 2,
+                    params: Vec::new(),
+                    body: Block {
+                        statements: vec![
+                            Stmt::If {
+                                condition: context_broken,
+                                then_branch: Block {
+                                    statements: vec![Stmt::Goto(Expr::StoryReference(
+                                        "missing".to_string(),
+                                    ))],
+                                },
+                                else_branch: None,
+                            },
+                            Stmt::Goto(Expr::StoryReference("right".to_string())),
+                        ],
+                    },
+                },
+            ],
+        }
+    }
+
+    fn looping_program() -> StoryProgram {
+        StoryProgram {
+            start_part: "ping".to_string(),
+            metadata: BTreeMap::new(),
+            parts: vec![
+                PartSpec {
+                    id: "ping".to_string(),
+                    properties: BTreeMap::new(),
+                    on_enter: None,
+                    on_exit: None,
+                    steps: vec![StepSpec::Narration(quote(0, "Ping.", Some(0)))],
+                },
+                PartSpec {
+                    id: "pong".to_string(),
+                    properties: BTreeMap::new(),
+                    on_enter: None,
+                    on_exit: None,
+                    steps: vec![StepSpec::Narration(quote(1, "Pong.", Some(1)))],
+                },
+            ],
+            functions: vec![goto(0, "pong"), goto(1, "ping")],
+        }
+    }
+}
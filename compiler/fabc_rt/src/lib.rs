@@ -1,10 +1,17 @@
+mod builtins;
 mod compiled;
 mod engine;
 mod error;
+mod explore;
 mod host;
+mod rng;
 mod scope;
+mod simulate;
+mod snapshot;
 mod value;
+mod visits;
 
+pub use builtins::Builtin;
 pub use compiled::{
     invoke_compiled_with_active_host, runtime_symbols, CompiledClosureFn,
     LinkedCompiledFunctionHost, LinkedFunctionDescriptor, RuntimeSymbol,
@@ -13,6 +20,18 @@ pub use engine::{
     ChoiceView, DialogueView, NarrationView, SelectionView, StoryEvent, StoryMachine,
 };
 pub use error::{Result, RuntimeError};
+pub use explore::{
+    explore, ExploreOptions, ExploreReport, ExploredDeadEnd, ExploredEnding, ExploredError,
+    ExploredLoop, PathChoice,
+};
 pub use host::{CompiledFunctionHost, CompiledInvocationResult};
 pub use scope::Scope;
-pub use value::{ClosureValue, ObjectRef, Value};
+pub use simulate::{
+    simulate, ChoiceStrategy, NumericSummary, SimulateOptions, SimulationReport,
+    CHOICE_WEIGHT_PROPERTY,
+};
+pub use snapshot::{
+    program_fingerprint, SnapshotCursor, SnapshotValue, StorySnapshot,
+    STORY_SNAPSHOT_FORMAT_VERSION,
+};
+pub use value::{ClosureValue, HostFunction, ListRef, ObjectRef, Value};
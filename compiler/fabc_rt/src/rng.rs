@@ -0,0 +1,34 @@
+use std::hash::{BuildHasher, RandomState};
+
+/// SplitMix64, small enough to snapshot as a single `u64` and identical on every backend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct StoryRng {
+    state: u64,
+}
+
+impl StoryRng {
+    pub(crate) fn from_seed(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub(crate) fn from_entropy() -> Self {
+        Self::from_seed(RandomState::new().hash_one(0_u64))
+    }
+
+    pub(crate) fn state(&self) -> u64 {
+        self.state
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut value = self.state;
+        value = (value ^ (value >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        value = (value ^ (value >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        value ^ (value >> 31)
+    }
+
+    /// Uniform float in `[0, 1)`.
+    pub(crate) fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1_u64 << 53) as f64
+    }
+}
@@ -1,6 +1,13 @@
-use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    rc::Rc,
+};
 
-use super::value::Value;
+use super::{
+    error::{Result, RuntimeError},
+    value::{Value, ValueKey},
+};
 
 #[derive(Debug, Default)]
 struct Frame {
@@ -11,6 +18,10 @@ struct Frame {
 #[derive(Clone, Debug, Default)]
 pub struct Scope(Rc<RefCell<Frame>>);
 
+/// Copies already made by [`Scope::deep_clone`], keyed by the frame they copy.
+#[derive(Default)]
+pub(crate) struct ScopeCopies(HashMap<*const RefCell<Frame>, Scope>);
+
 impl Scope {
     pub fn new() -> Self {
         Self::default()
@@ -36,15 +47,57 @@ impl Scope {
         }
     }
 
-    pub fn assign(&self, name: &str, value: Value) -> bool {
+    /// A copy of this scope and its parents whose bindings change independently of the
+    /// original. Frames already copied into `copies` are reused, which keeps closures that
+    /// capture their own frame from recursing forever.
+    pub(crate) fn deep_clone(&self, copies: &mut ScopeCopies) -> Scope {
+        if let Some(copy) = copies.0.get(&Rc::as_ptr(&self.0)) {
+            return copy.clone();
+        }
+
+        let copy = Scope::default();
+        copies.0.insert(Rc::as_ptr(&self.0), copy.clone());
+
+        let frame = self.0.borrow();
+        let parent = frame
+            .parent
+            .as_ref()
+            .map(|parent| parent.deep_clone(copies));
+        let values = frame
+            .values
+            .iter()
+            .map(|(name, value)| (name.clone(), value.deep_clone_with_scopes(copies)))
+            .collect();
+        *copy.0.borrow_mut() = Frame { values, parent };
+        copy
+    }
+
+    /// Keys of the bindings of this frame alone, not its parents'.
+    pub(crate) fn binding_keys(&self) -> BTreeMap<String, ValueKey> {
+        self.0
+            .borrow()
+            .values
+            .iter()
+            .map(|(name, value)| (name.clone(), value.key()))
+            .collect()
+    }
+
+    /// Rebinds the innermost `name`. Builtin namespaces in the global frame cannot be
+    /// rebound, though a local binding may shadow them.
+    pub fn assign(&self, name: &str, value: Value) -> Result<()> {
         let mut frame = self.0.borrow_mut();
-        if frame.values.contains_key(name) {
-            frame.values.insert(name.to_string(), value);
-            true
-        } else if let Some(parent) = frame.parent.as_ref() {
-            parent.assign(name, value)
-        } else {
-            false
+        match frame.values.get(name) {
+            Some(Value::Namespace(namespace)) if frame.parent.is_none() => {
+                Err(RuntimeError::ReadOnlyNamespace(namespace.to_string()))
+            }
+            Some(_) => {
+                frame.values.insert(name.to_string(), value);
+                Ok(())
+            }
+            None => match frame.parent.as_ref() {
+                Some(parent) => parent.assign(name, value),
+                None => Err(RuntimeError::UndefinedVariable(name.to_string())),
+            },
         }
     }
 }
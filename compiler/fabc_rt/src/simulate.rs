@@ -0,0 +1,414 @@
+use std::collections::BTreeMap;
+
+use super::{
+    engine::{ChoiceView, StoryEvent, StoryMachine},
+    rng::StoryRng,
+    value::Value,
+};
+
+/// The choice property [`ChoiceStrategy::Weighted`] reads; choices without it weigh `1`.
+pub const CHOICE_WEIGHT_PROPERTY: &str = "weight";
+
+/// How a simulated reader picks among the enabled choices of a selection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChoiceStrategy {
+    /// Uniformly at random.
+    Random,
+    /// At random, in proportion to each choice's `weight` property.
+    Weighted,
+    /// Always the first enabled choice.
+    First,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SimulateOptions {
+    pub runs: usize,
+    pub strategy: ChoiceStrategy,
+    /// Seeds both the choices and every run's story rolls, so equal seeds give equal reports.
+    pub seed: u64,
+    /// Steps after which a run is abandoned as unfinished.
+    pub max_steps: usize,
+}
+
+impl Default for SimulateOptions {
+    fn default() -> Self {
+        Self {
+            runs: 1_000,
+            strategy: ChoiceStrategy::Random,
+            seed: 0,
+            max_steps: 10_000,
+        }
+    }
+}
+
+/// Spread of a numeric context value across the runs that finished with it set.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NumericSummary {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    /// Number of runs the value was numeric in.
+    pub samples: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SimulationReport {
+    pub runs: usize,
+    /// Finished runs per ending part.
+    pub endings: BTreeMap<String, usize>,
+    /// Runs that entered each part at least once, as counted by `visits()`, so parts that
+    /// only pass the reader on count too.
+    pub visits: BTreeMap<String, usize>,
+    /// Numeric context values at the end of finished runs, keyed by dotted path.
+    pub context: BTreeMap<String, NumericSummary>,
+    /// Runs stopped by a runtime error, keyed by the error message.
+    pub errors: BTreeMap<String, usize>,
+    /// Runs abandoned after `max_steps`, or stuck in a selection with no enabled choice.
+    pub unfinished: usize,
+}
+
+/// Plays `options.runs` headless playthroughs of `machine`'s story from its start part.
+///
+/// Each run starts from a copy of the machine's context; the machine itself is left untouched.
+pub fn simulate(machine: &StoryMachine, options: SimulateOptions) -> SimulationReport {
+    let mut report = SimulationReport {
+        runs: options.runs,
+        ..SimulationReport::default()
+    };
+    let mut totals = BTreeMap::<String, f64>::new();
+    let mut rng = StoryRng::from_seed(options.seed);
+
+    for _ in 0..options.runs {
+        let mut run = machine.fork();
+        run.set_history_limit(0);
+        run.set_seed(rng.next_u64());
+
+        let mut part = None;
+        let mut event = run.start();
+        let mut steps = 0;
+
+        let finished = loop {
+            if let Some(current) = run.current_part() {
+                part = Some(current.to_string());
+            }
+
+            let next = match event {
+                Ok(StoryEvent::Finished) => break true,
+                Err(error) => {
+                    *report.errors.entry(error.to_string()).or_default() += 1;
+                    break false;
+                }
+                _ if steps == options.max_steps => {
+                    report.unfinished += 1;
+                    break false;
+                }
+                Ok(StoryEvent::Selection(selection)) => {
+                    let Some(index) = pick(&selection.choices, options.strategy, &mut rng) else {
+                        report.unfinished += 1;
+                        break false;
+                    };
+                    run.choose(index)
+                }
+                Ok(_) => run.advance(),
+            };
+            event = next;
+            steps += 1;
+        };
+
+        for spec in &run.program().parts {
+            if run.visits(&spec.id) > 0 {
+                *report.visits.entry(spec.id.clone()).or_default() += 1;
+            }
+        }
+        if !finished {
+            continue;
+        }
+
+        if let Some(part) = part {
+            *report.endings.entry(part).or_default() += 1;
+        }
+        let mut numbers = Vec::new();
+        numeric_values("", &run.context_snapshot(), &mut numbers);
+        for (path, value) in numbers {
+            *totals.entry(path.clone()).or_default() += value;
+            report
+                .context
+                .entry(path)
+                .and_modify(|summary| {
+                    summary.min = summary.min.min(value);
+                    summary.max = summary.max.max(value);
+                    summary.samples += 1;
+                })
+                .or_insert(NumericSummary {
+                    min: value,
+                    max: value,
+                    mean: 0.0,
+                    samples: 1,
+                });
+        }
+    }
+
+    for (path, summary) in &mut report.context {
+        summary.mean = totals[path] / summary.samples as f64;
+    }
+    report
+}
+
+/// Index of the choice a reader following `strategy` takes, or `None` if none is enabled.
+fn pick(choices: &[ChoiceView], strategy: ChoiceStrategy, rng: &mut StoryRng) -> Option<usize> {
+    let enabled = choices
+        .iter()
+        .enumerate()
+        .filter(|(_, choice)| choice.enabled)
+        .map(|(index, choice)| (index, choice_weight(choice)))
+        .collect::<Vec<_>>();
+    if enabled.is_empty() {
+        return None;
+    }
+
+    let total = enabled.iter().map(|(_, weight)| weight).sum::<f64>();
+    match strategy {
+        ChoiceStrategy::First => Some(enabled[0].0),
+        ChoiceStrategy::Weighted if total > 0.0 => {
+            let mut target = rng.next_f64() * total;
+            for &(index, weight) in &enabled {
+                if target < weight {
+                    return Some(index);
+                }
+                target -= weight;
+            }
+            enabled
+                .iter()
+                .rev()
+                .find(|(_, weight)| *weight > 0.0)
+                .map(|(index, _)| *index)
+        }
+        // Uniform, also when every weight is zero.
+        _ => Some(enabled[(rng.next_f64() * enabled.len() as f64) as usize].0),
+    }
+}
+
+/// The choice's `weight` property. Missing weights count as `1`, unusable ones as `0`.
+fn choice_weight(choice: &ChoiceView) -> f64 {
+    match choice.properties.get(CHOICE_WEIGHT_PROPERTY) {
+        None => 1.0,
+        Some(Value::Number(weight)) if weight.is_finite() => weight.max(0.0),
+        Some(_) => 0.0,
+    }
+}
+
+/// Collects every number in `values`, descending into objects with dotted paths.
+fn numeric_values(prefix: &str, values: &BTreeMap<String, Value>, out: &mut Vec<(String, f64)>) {
+    for (key, value) in values {
+        let path = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{prefix}.{key}")
+        };
+        match value {
+            Value::Number(number) => out.push((path, *number)),
+            Value::Object(object) => numeric_values(&path, &object.borrow(), out),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use fabc_ir::{
+        BinaryOperator, Block, Expr, FunctionSpec, Literal, MemberSegment, PartSpec, QuoteSpec,
+        SelectionSpec, StepSpec, Stmt, StoryProgram,
+    };
+
+    use super::{simulate, ChoiceStrategy, SimulateOptions};
+    use crate::{StoryMachine, Value};
+
+    #[test]
+    fn first_strategy_always_takes_the_first_choice() {
+        let machine = machine();
+
+        let report = simulate(
+            &machine,
+            SimulateOptions {
+                runs: 10,
+                strategy: ChoiceStrategy::First,
+                ..SimulateOptions::default()
+            },
+        );
+
+        assert_eq!(report.endings, BTreeMap::from([("left".to_string(), 10)]));
+        assert_eq!(report.visits["intro"], 10);
+        assert!(!report.visits.contains_key("right"));
+        let summary = report.context["stats.trust"];
+        assert_eq!((summary.min, summary.max, summary.mean), (1.0, 1.0, 1.0));
+        assert_eq!(summary.samples, 10);
+    }
+
+    #[test]
+    fn counts_parts_that_only_pass_the_reader_on() {
+        let machine = machine();
+
+        let report = simulate(
+            &machine,
+            SimulateOptions {
+                runs: 10,
+                strategy: ChoiceStrategy::First,
+                ..SimulateOptions::default()
+            },
+        );
+
+        assert_eq!(report.visits["hall"], 10);
+        assert_eq!(report.visits["left"], 10);
+    }
+
+    #[test]
+    fn weighted_strategy_follows_weights_and_is_seeded() {
+        let machine = machine();
+        let options = SimulateOptions {
+            runs: 400,
+            strategy: ChoiceStrategy::Weighted,
+            seed: 7,
+            ..SimulateOptions::default()
+        };
+
+        let report = simulate(&machine, options);
+
+        let (left, right) = (report.endings["left"], report.endings["right"]);
+        assert_eq!(left + right, 400);
+        assert!(right > left * 2, "left={left} right={right}");
+        let summary = report.context["stats.trust"];
+        assert_eq!((summary.min, summary.max), (-1.0, 1.0));
+        assert_eq!(simulate(&machine, options), report);
+    }
+
+    #[test]
+    fn random_strategy_reaches_every_ending() {
+        let report = simulate(
+            &machine(),
+            SimulateOptions {
+                runs: 100,
+                ..SimulateOptions::default()
+            },
+        );
+
+        assert_eq!(report.endings.len(), 2);
+        assert_eq!(report.unfinished, 0);
+        assert!(report.errors.is_empty());
+    }
+
+    fn choice(node_id: usize, text: &str, weight: f64, next_action: usize) -> QuoteSpec {
+        QuoteSpec {
+            node_id,
+            text: text.to_string(),
+            segments: Vec::new(),
+            properties: BTreeMap::from([(
+                "weight".to_string(),
+                Expr::Literal(Literal::Number(weight)),
+            )]),
+            next_action: Some(next_action),
+            guard: None,
+            once: false,
+            fallback: false,
+        }
+    }
+
+    fn narration(node_id: usize, text: &str) -> StepSpec {
+        StepSpec::Narration(QuoteSpec {
+            node_id,
+            text: text.to_string(),
+            segments: Vec::new(),
+            properties: BTreeMap::new(),
+            next_action: None,
+            guard: None,
+            once: false,
+            fallback: false,
+        })
+    }
+
+    /// Sets `context.stats.trust += delta` and jumps to `target`.
+    fn adjust_trust(id: usize, delta: f64, target: &str) -> FunctionSpec {
+        let trust = Expr::MemberAccess {
+            base: Box::new(Expr::Context),
+            members: vec![
+                MemberSegment::Key("stats".to_string()),
+                MemberSegment::Key("trust".to_string()),
+            ],
+        };
+        FunctionSpec {
+            id,
+            node_id: id,
+            params: Vec::new(),
+            body: Block {
+                statements: vec![
+                    Stmt::Expr(Expr::Assignment {
+                        target: Box::new(trust.clone()),
+                        value: Box::new(Expr::Binary {
+                            left: Box::new(trust),
+                            operator: BinaryOperator::Add,
+                            right: Box::new(Expr::Literal(Literal::Number(delta))),
+                        }),
+                    }),
+                    Stmt::Goto(Expr::StoryReference(target.to_string())),
+                ],
+            },
+        }
+    }
+
+    /// "Left" (weight 1) raises trust and passes through `hall`, whose only choice is a
+    /// fallback; "Right" (weight 9) lowers trust.
+    fn machine() -> StoryMachine {
+        let program = StoryProgram {
+            start_part: "intro".to_string(),
+            metadata: BTreeMap::new(),
+            parts: vec![
+                PartSpec {
+                    id: "intro".to_string(),
+                    properties: BTreeMap::new(),
+                    on_enter: None,
+                    on_exit: None,
+                    steps: vec![StepSpec::Selection(SelectionSpec {
+                        choices: vec![choice(0, "Left", 1.0, 0), choice(1, "Right", 9.0, 1)],
+                    })],
+                },
+                PartSpec {
+                    id: "hall".to_string(),
+                    properties: BTreeMap::new(),
+                    on_enter: None,
+                    on_exit: None,
+                    steps: vec![StepSpec::Selection(SelectionSpec {
+                        choices: vec![QuoteSpec {
+                            fallback: true,
+                            ..choice(4, "Onwards", 1.0, 2)
+                        }],
+                    })],
+                },
+                PartSpec {
+                    id: "left".to_string(),
+                    properties: BTreeMap::new(),
+                    on_enter: None,
+                    on_exit: None,
+                    steps: vec![narration(2, "Left room.")],
+                },
+                PartSpec {
+                    id: "right".to_string(),
+                    properties: BTreeMap::new(),
+                    on_enter: None,
+                    on_exit: None,
+                    steps: vec![narration(3, "Right room.")],
+                },
+            ],
+            functions: vec![
+                adjust_trust(0, 1.0, "hall"),
+                adjust_trust(1, -1.0, "right"),
+                adjust_trust(2, 0.0, "left"),
+            ],
+        };
+        let context = BTreeMap::from([(
+            "stats".to_string(),
+            Value::object(BTreeMap::from([("trust".to_string(), Value::Number(0.0))])),
+        )]);
+        StoryMachine::with_context(program, context).expect("build machine")
+    }
+}
@@ -0,0 +1,206 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::RangeInclusive,
+};
+
+use fabc_ir::{QuoteSpec, StepSpec, StoryProgram};
+
+use super::{
+    error::{Result, RuntimeError},
+    value::Value,
+};
+
+/// Version 2 added the random state, visit counts, seen quotes and `SnapshotCursor::entering`.
+/// Version 1 saves, written before any of them existed, still load.
+pub const STORY_SNAPSHOT_FORMAT_VERSION: u32 = 2;
+
+pub(crate) const SUPPORTED_SNAPSHOT_FORMAT_VERSIONS: RangeInclusive<u32> =
+    1..=STORY_SNAPSHOT_FORMAT_VERSION;
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct StorySnapshot {
+    pub format_version: u32,
+    pub fingerprint: u64,
+    pub cursor: Option<SnapshotCursor>,
+    pub context: BTreeMap<String, SnapshotValue>,
+    /// Generator state for `random` and friends; version 1 saves omit it.
+    #[serde(default)]
+    pub rng_state: Option<u64>,
+    /// Times each part was entered; version 1 saves omit it.
+    #[serde(default)]
+    pub visits: BTreeMap<String, usize>,
+    /// Node ids of the quotes the reader has moved past.
+    #[serde(default)]
+    pub seen: BTreeSet<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SnapshotCursor {
+    pub part: String,
+    pub step_index: usize,
+    /// Saved while the part's `PartEntered` event was showing.
+    #[serde(default)]
+    pub entering: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum SnapshotValue {
+    Number(f64),
+    Boolean(bool),
+    String(String),
+    None,
+    Object(BTreeMap<String, SnapshotValue>),
+    List(Vec<SnapshotValue>),
+    StoryRef(String),
+}
+
+impl SnapshotValue {
+    pub fn from_value(value: &Value) -> Result<Self> {
+        Ok(match value {
+            Value::Number(value) => SnapshotValue::Number(*value),
+            Value::Boolean(value) => SnapshotValue::Boolean(*value),
+            Value::String(value) => SnapshotValue::String(value.clone()),
+            Value::None => SnapshotValue::None,
+            Value::Object(object) => SnapshotValue::Object(snapshot_map(&object.borrow())?),
+            Value::List(list) => SnapshotValue::List(
+                list.borrow()
+                    .iter()
+                    .map(SnapshotValue::from_value)
+                    .collect::<Result<_>>()?,
+            ),
+            Value::StoryRef(value) => SnapshotValue::StoryRef(value.clone()),
+            other => {
+                return Err(RuntimeError::UnserializableValue(
+                    other.kind_name().to_string(),
+                ));
+            }
+        })
+    }
+
+    pub fn into_value(self) -> Value {
+        match self {
+            SnapshotValue::Number(value) => Value::Number(value),
+            SnapshotValue::Boolean(value) => Value::Boolean(value),
+            SnapshotValue::String(value) => Value::String(value),
+            SnapshotValue::None => Value::None,
+            SnapshotValue::Object(object) => Value::object(restore_map(object)),
+            SnapshotValue::List(list) => {
+                Value::list(list.into_iter().map(SnapshotValue::into_value).collect())
+            }
+            SnapshotValue::StoryRef(value) => Value::StoryRef(value),
+        }
+    }
+}
+
+pub(crate) fn snapshot_map(
+    values: &BTreeMap<String, Value>,
+) -> Result<BTreeMap<String, SnapshotValue>> {
+    values
+        .iter()
+        .map(|(key, value)| Ok((key.clone(), SnapshotValue::from_value(value)?)))
+        .collect()
+}
+
+pub(crate) fn restore_map(values: BTreeMap<String, SnapshotValue>) -> BTreeMap<String, Value> {
+    values
+        .into_iter()
+        .map(|(key, value)| (key, value.into_value()))
+        .collect()
+}
+
+// Only the shape of the program feeds the fingerprint, so text edits keep saves loadable
+// while anything that could move the cursor or change closure ids invalidates them.
+pub fn program_fingerprint(program: &StoryProgram) -> u64 {
+    let mut hasher = Fnv1a::default();
+
+    hasher.write_str(&program.start_part);
+    hasher.write_usize(program.parts.len());
+    for part in &program.parts {
+        hasher.write_str(&part.id);
+        // Skipped for parts without properties so their saves keep their old fingerprint.
+        if !part.properties.is_empty() || part.on_enter.is_some() || part.on_exit.is_some() {
+            hasher.write_usize(part.properties.len());
+            for hook in [part.on_enter, part.on_exit] {
+                hasher.write_usize(hook.map_or(0, |function_id| function_id + 1));
+            }
+        }
+        hasher.write_usize(part.steps.len());
+        for step in &part.steps {
+            match step {
+                StepSpec::Narration(quote) => {
+                    hasher.write_usize(0);
+                    hasher.write_quote(quote);
+                }
+                StepSpec::Dialogue(dialogue) => {
+                    hasher.write_usize(1);
+                    hasher.write_str(&dialogue.speaker);
+                    hasher.write_quote(&dialogue.quote);
+                }
+                StepSpec::Selection(selection) => {
+                    hasher.write_usize(2);
+                    hasher.write_usize(selection.choices.len());
+                    for choice in &selection.choices {
+                        hasher.write_quote(choice);
+                    }
+                }
+                StepSpec::Directive(directive) => {
+                    hasher.write_usize(3);
+                    hasher.write_usize(directive.node_id);
+                    hasher.write_str(&directive.name);
+                }
+            }
+        }
+    }
+
+    hasher.write_usize(program.functions.len());
+    for function in &program.functions {
+        hasher.write_usize(function.id);
+        hasher.write_usize(function.params.len());
+        for param in &function.params {
+            hasher.write_str(param);
+        }
+    }
+
+    hasher.finish()
+}
+
+struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Fnv1a {
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn write_usize(&mut self, value: usize) {
+        self.write_bytes(&(value as u64).to_le_bytes());
+    }
+
+    fn write_str(&mut self, value: &str) {
+        self.write_usize(value.len());
+        self.write_bytes(value.as_bytes());
+    }
+
+    fn write_quote(&mut self, quote: &QuoteSpec) {
+        self.write_usize(quote.node_id);
+        match quote.next_action {
+            Some(function_id) => {
+                self.write_usize(1);
+                self.write_usize(function_id);
+            }
+            None => self.write_usize(0),
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
@@ -2,9 +2,14 @@ use std::{cell::RefCell, collections::BTreeMap, fmt, rc::Rc};
 
 use fabc_ir::FunctionId;
 
-use super::{error::RuntimeError, scope::Scope};
+use super::{
+    builtins::Builtin,
+    error::{Result as RuntimeResult, RuntimeError},
+    scope::{Scope, ScopeCopies},
+};
 
 pub type ObjectRef = Rc<RefCell<BTreeMap<String, Value>>>;
+pub type ListRef = Rc<RefCell<Vec<Value>>>;
 
 #[derive(Clone, Debug)]
 pub struct ClosureValue {
@@ -12,6 +17,41 @@ pub struct ClosureValue {
     pub captured: Scope,
 }
 
+type HostFn = dyn Fn(&[Value]) -> RuntimeResult<Value>;
+
+/// A Rust function exposed to scripts with `StoryMachine::register_function`.
+#[derive(Clone)]
+pub struct HostFunction {
+    name: Rc<str>,
+    function: Rc<HostFn>,
+}
+
+impl HostFunction {
+    pub fn new(
+        name: impl Into<String>,
+        function: impl Fn(&[Value]) -> RuntimeResult<Value> + 'static,
+    ) -> Self {
+        Self {
+            name: name.into().into(),
+            function: Rc::new(function),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn call(&self, args: &[Value]) -> RuntimeResult<Value> {
+        (self.function)(args)
+    }
+}
+
+impl fmt::Debug for HostFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("HostFunction").field(&self.name).finish()
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum Value {
     Number(f64),
@@ -19,7 +59,12 @@ pub enum Value {
     String(String),
     None,
     Object(ObjectRef),
+    List(ListRef),
     Closure(ClosureValue),
+    Builtin(Builtin),
+    /// A read-only builtin namespace such as `str`, whose members are its builtins.
+    Namespace(&'static str),
+    HostFunction(HostFunction),
     StoryRef(String),
 }
 
@@ -28,6 +73,48 @@ impl Value {
         Self::Object(Rc::new(RefCell::new(properties)))
     }
 
+    pub fn list(elements: Vec<Value>) -> Self {
+        Self::List(Rc::new(RefCell::new(elements)))
+    }
+
+    /// Copies objects and lists all the way down. Closures keep sharing the scopes they
+    /// captured.
+    pub fn deep_clone(&self) -> Self {
+        self.deep_clone_in(None)
+    }
+
+    /// [`Self::deep_clone`] that also copies the scopes closures captured, each one once, so
+    /// closures that shared a scope still share its copy.
+    pub(crate) fn deep_clone_with_scopes(&self, scopes: &mut ScopeCopies) -> Self {
+        self.deep_clone_in(Some(scopes))
+    }
+
+    fn deep_clone_in(&self, mut scopes: Option<&mut ScopeCopies>) -> Self {
+        match self {
+            Value::Object(object) => Value::object(
+                object
+                    .borrow()
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.deep_clone_in(scopes.as_deref_mut())))
+                    .collect(),
+            ),
+            Value::List(list) => Value::list(
+                list.borrow()
+                    .iter()
+                    .map(|value| value.deep_clone_in(scopes.as_deref_mut()))
+                    .collect(),
+            ),
+            Value::Closure(closure) => match scopes {
+                Some(scopes) => Value::Closure(ClosureValue {
+                    function_id: closure.function_id,
+                    captured: closure.captured.deep_clone(scopes),
+                }),
+                None => self.clone(),
+            },
+            other => other.clone(),
+        }
+    }
+
     pub fn kind_name(&self) -> &'static str {
         match self {
             Value::Number(_) => "Number",
@@ -35,7 +122,11 @@ impl Value {
             Value::String(_) => "String",
             Value::None => "None",
             Value::Object(_) => "Object",
+            Value::List(_) => "List",
             Value::Closure(_) => "Closure",
+            Value::Builtin(_) => "Builtin",
+            Value::Namespace(_) => "Namespace",
+            Value::HostFunction(_) => "HostFunction",
             Value::StoryRef(_) => "StoryRef",
         }
     }
@@ -76,6 +167,74 @@ impl Value {
         }
     }
 
+    pub fn get_member(&self, key: &Value) -> Result<Value, RuntimeError> {
+        match self {
+            Value::Object(object) => {
+                let key = key.to_member_key()?;
+                object.borrow().get(&key).cloned().ok_or_else(|| {
+                    RuntimeError::InvalidMemberAccess {
+                        target: self.kind_name().to_string(),
+                        member: key,
+                    }
+                })
+            }
+            Value::List(list) => {
+                let list = list.borrow();
+                Ok(list[key.to_list_index(list.len())?].clone())
+            }
+            Value::Namespace(namespace) => {
+                let key = key.to_member_key()?;
+                Builtin::in_namespace(namespace, &key)
+                    .map(Value::Builtin)
+                    .ok_or_else(|| RuntimeError::InvalidMemberAccess {
+                        target: self.kind_name().to_string(),
+                        member: key,
+                    })
+            }
+            other => Err(RuntimeError::InvalidMemberAccess {
+                target: other.kind_name().to_string(),
+                member: key.display_value(),
+            }),
+        }
+    }
+
+    pub fn set_member(&self, key: &Value, value: Value) -> Result<(), RuntimeError> {
+        match self {
+            Value::Object(object) => {
+                object.borrow_mut().insert(key.to_member_key()?, value);
+                Ok(())
+            }
+            Value::List(list) => {
+                let mut list = list.borrow_mut();
+                let index = key.to_list_index(list.len())?;
+                list[index] = value;
+                Ok(())
+            }
+            Value::Namespace(namespace) => {
+                Err(RuntimeError::ReadOnlyNamespace(namespace.to_string()))
+            }
+            other => Err(RuntimeError::InvalidMemberAccess {
+                target: other.kind_name().to_string(),
+                member: key.display_value(),
+            }),
+        }
+    }
+
+    fn to_list_index(&self, len: usize) -> Result<usize, RuntimeError> {
+        let Value::Number(index) = self else {
+            return Err(RuntimeError::InvalidMemberKey(self.kind_name().to_string()));
+        };
+
+        if index.fract() != 0.0 || *index < 0.0 || *index >= len as f64 {
+            return Err(RuntimeError::ListIndexOutOfBounds {
+                index: self.display_value(),
+                len,
+            });
+        }
+
+        Ok(*index as usize)
+    }
+
     pub fn to_story_target(&self) -> Result<String, RuntimeError> {
         match self {
             Value::String(value) => Ok(value.clone()),
@@ -112,7 +271,18 @@ impl Value {
             Value::String(value) => value.clone(),
             Value::None => "none".to_string(),
             Value::Object(_) => "[object]".to_string(),
+            Value::List(list) => format!(
+                "[{}]",
+                list.borrow()
+                    .iter()
+                    .map(Value::display_value)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
             Value::Closure(_) => "[closure]".to_string(),
+            Value::Builtin(builtin) => format!("[builtin {}]", builtin.path()),
+            Value::Namespace(namespace) => format!("[namespace {namespace}]"),
+            Value::HostFunction(function) => format!("[host function {}]", function.name()),
             Value::StoryRef(value) => value.clone(),
         }
     }
@@ -127,13 +297,60 @@ impl PartialEq for Value {
             (Value::None, Value::None) => true,
             (Value::StoryRef(left), Value::StoryRef(right)) => left == right,
             (Value::Object(left), Value::Object(right)) => *left.borrow() == *right.borrow(),
+            (Value::List(left), Value::List(right)) => *left.borrow() == *right.borrow(),
             (Value::Closure(left), Value::Closure(right)) => left.function_id == right.function_id,
+            (Value::Builtin(left), Value::Builtin(right)) => left == right,
+            (Value::Namespace(left), Value::Namespace(right)) => left == right,
+            (Value::HostFunction(left), Value::HostFunction(right)) => left.name == right.name,
             _ => false,
         }
     }
 }
 
-impl Eq for Value {}
+/// A [`Value`] as explored states are compared: numbers by their bits, so that a NaN equals
+/// itself and the key can be `Eq`, which `Value` cannot be. Both zeroes give the same key, and
+/// closures and host functions are keyed by function, as `PartialEq` compares them.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum ValueKey {
+    Number(u64),
+    Boolean(bool),
+    String(String),
+    None,
+    Object(BTreeMap<String, ValueKey>),
+    List(Vec<ValueKey>),
+    Closure(FunctionId),
+    Builtin(Builtin),
+    Namespace(&'static str),
+    HostFunction(Rc<str>),
+    StoryRef(String),
+}
+
+impl Value {
+    pub(crate) fn key(&self) -> ValueKey {
+        match self {
+            Value::Number(value) => {
+                let value = if *value == 0.0 { 0.0 } else { *value };
+                ValueKey::Number(value.to_bits())
+            }
+            Value::Boolean(value) => ValueKey::Boolean(*value),
+            Value::String(value) => ValueKey::String(value.clone()),
+            Value::None => ValueKey::None,
+            Value::Object(object) => ValueKey::Object(
+                object
+                    .borrow()
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.key()))
+                    .collect(),
+            ),
+            Value::List(list) => ValueKey::List(list.borrow().iter().map(Value::key).collect()),
+            Value::Closure(closure) => ValueKey::Closure(closure.function_id),
+            Value::Builtin(builtin) => ValueKey::Builtin(*builtin),
+            Value::Namespace(namespace) => ValueKey::Namespace(namespace),
+            Value::HostFunction(function) => ValueKey::HostFunction(function.name.clone()),
+            Value::StoryRef(value) => ValueKey::StoryRef(value.clone()),
+        }
+    }
+}
 
 impl fmt::Display for Value {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -0,0 +1,137 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use fabc_ir::{Block, Expr, MemberSegment, StepSpec, Stmt, StoryProgram};
+
+use super::value::Value;
+
+/// What the reader has been through: how often each part was entered and which quotes, by
+/// `QuoteSpec::node_id`, they have moved past.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub(crate) struct VisitLog {
+    pub(crate) parts: BTreeMap<String, usize>,
+    pub(crate) seen: BTreeSet<usize>,
+}
+
+impl VisitLog {
+    pub(crate) fn visits(&self, part: &str) -> usize {
+        self.parts.get(part).copied().unwrap_or(0)
+    }
+}
+
+/// What the `visits` and `seen` builtins read.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct VisitView<'a> {
+    pub(crate) log: &'a VisitLog,
+    /// The quote whose guard, text, properties or `next` handler is being evaluated.
+    pub(crate) quote: Option<usize>,
+}
+
+impl VisitView<'_> {
+    /// `visits(@part)`; parts can also be named by string.
+    pub(crate) fn visits(&self, part: &Value) -> Option<Value> {
+        match part {
+            Value::StoryRef(part) | Value::String(part) => {
+                Some(Value::Number(self.log.visits(part) as f64))
+            }
+            _ => None,
+        }
+    }
+
+    /// `seen()`: whether the reader already moved past the current quote. Outside a quote
+    /// there is nothing to have seen.
+    pub(crate) fn seen(&self) -> Value {
+        Value::Boolean(
+            self.quote
+                .is_some_and(|node_id| self.log.seen.contains(&node_id)),
+        )
+    }
+}
+
+/// Whether any script in `program` mentions `visits` or `seen`, or any choice is `once`.
+/// Visit counts grow on every loop, so exploration only tells states apart by them when the
+/// story can read them.
+pub(crate) fn program_reads_visits(program: &StoryProgram) -> bool {
+    let step_reads_visits = |step: &StepSpec| match step {
+        StepSpec::Narration(quote) => any_reads_visits(quote.properties.values()),
+        StepSpec::Dialogue(dialogue) => any_reads_visits(dialogue.quote.properties.values()),
+        StepSpec::Selection(selection) => selection
+            .choices
+            .iter()
+            .any(|choice| choice.once || any_reads_visits(choice.properties.values())),
+        StepSpec::Directive(directive) => any_reads_visits(&directive.args),
+    };
+
+    any_reads_visits(program.metadata.values())
+        || program.parts.iter().any(|part| {
+            any_reads_visits(part.properties.values()) || part.steps.iter().any(step_reads_visits)
+        })
+        || program
+            .functions
+            .iter()
+            .any(|function| block_reads_visits(&function.body))
+}
+
+fn any_reads_visits<'a>(exprs: impl IntoIterator<Item = &'a Expr>) -> bool {
+    exprs.into_iter().any(expr_reads_visits)
+}
+
+fn block_reads_visits(block: &Block) -> bool {
+    block.statements.iter().any(stmt_reads_visits)
+}
+
+fn stmt_reads_visits(stmt: &Stmt) -> bool {
+    match stmt {
+        Stmt::Expr(expr)
+        | Stmt::Goto(expr)
+        | Stmt::Let {
+            initializer: expr, ..
+        } => expr_reads_visits(expr),
+        Stmt::Return(expr) => expr.as_ref().is_some_and(expr_reads_visits),
+        Stmt::Block(block) => block_reads_visits(block),
+        Stmt::If {
+            condition,
+            then_branch,
+            else_branch,
+        } => {
+            expr_reads_visits(condition)
+                || block_reads_visits(then_branch)
+                || else_branch.as_deref().is_some_and(stmt_reads_visits)
+        }
+        Stmt::While { condition, body } => expr_reads_visits(condition) || block_reads_visits(body),
+        Stmt::For {
+            initializer,
+            condition,
+            increment,
+            body,
+        } => {
+            initializer.as_deref().is_some_and(stmt_reads_visits)
+                || condition.as_ref().is_some_and(expr_reads_visits)
+                || increment.as_ref().is_some_and(expr_reads_visits)
+                || block_reads_visits(body)
+        }
+        Stmt::Break | Stmt::Continue => false,
+    }
+}
+
+fn expr_reads_visits(expr: &Expr) -> bool {
+    match expr {
+        Expr::Identifier(name) => name == "visits" || name == "seen",
+        Expr::Literal(_) | Expr::StoryReference(_) | Expr::Context | Expr::Closure(_) => false,
+        Expr::Object(entries) => any_reads_visits(entries.values()),
+        Expr::List(elements) => any_reads_visits(elements),
+        Expr::Call { callee, arguments } => {
+            expr_reads_visits(callee) || any_reads_visits(arguments)
+        }
+        Expr::MemberAccess { base, members } => {
+            expr_reads_visits(base)
+                || members.iter().any(|member| match member {
+                    MemberSegment::Key(_) => false,
+                    MemberSegment::Expr(expr) => expr_reads_visits(expr),
+                })
+        }
+        Expr::Assignment { target, value } => expr_reads_visits(target) || expr_reads_visits(value),
+        Expr::Unary { right, .. } => expr_reads_visits(right),
+        Expr::Binary { left, right, .. } => expr_reads_visits(left) || expr_reads_visits(right),
+        Expr::Grouping(expr) => expr_reads_visits(expr),
+    }
+}
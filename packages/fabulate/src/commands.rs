@@ -1,25 +1,99 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use fabc::{Error as CompilerError, HostFunctions};
+
 use crate::commands::build::Build;
+use crate::commands::check::Check;
 use crate::commands::compile::Compile;
+use crate::commands::explore::Explore;
+use crate::commands::fmt::Fmt;
+use crate::commands::graph::Graph;
 use crate::commands::play::Play;
+use crate::commands::simulate::Simulate;
+use crate::commands::test::Test;
 use crate::error::Result;
+use crate::report::Reporter;
 
 pub mod build;
+pub mod check;
 pub mod compile;
+pub mod explore;
+pub mod fmt;
+pub mod graph;
 pub mod play;
+pub mod simulate;
+pub mod test;
 
 #[derive(clap::Subcommand)]
 pub enum Commands {
     Build(Build),
+    Check(Check),
     Compile(Compile),
+    Explore(Explore),
+    Fmt(Fmt),
+    Graph(Graph),
     Play(Play),
+    Simulate(Simulate),
+    Test(Test),
 }
 
 impl Commands {
-    pub fn exec(&self) -> Result<()> {
+    pub fn exec(&self, reporter: &Reporter) -> Result<()> {
         match self {
-            Commands::Build(cmd) => cmd.exec(),
-            Commands::Compile(cmd) => cmd.exec(),
-            Commands::Play(cmd) => cmd.exec(),
+            Commands::Build(cmd) => cmd.exec(reporter),
+            Commands::Check(cmd) => cmd.exec(reporter),
+            Commands::Compile(cmd) => cmd.exec(reporter),
+            Commands::Explore(cmd) => cmd.exec(reporter),
+            Commands::Fmt(cmd) => cmd.exec(),
+            Commands::Graph(cmd) => cmd.exec(reporter),
+            Commands::Play(cmd) => cmd.exec(reporter),
+            Commands::Simulate(cmd) => cmd.exec(reporter),
+            Commands::Test(cmd) => cmd.exec(reporter),
         }
     }
 }
+
+/// The host functions declared by a `--host-functions` manifest, or none without one.
+pub(crate) fn host_functions(manifest: Option<&Path>) -> Result<HostFunctions> {
+    Ok(match manifest {
+        Some(path) => HostFunctions::load(path)?,
+        None => HostFunctions::default(),
+    })
+}
+
+/// Adds `input` if it is a file, or every file with `extension` beneath it if it is a directory.
+pub(crate) fn collect_sources(
+    input: &Path,
+    extension: &str,
+    files: &mut Vec<PathBuf>,
+) -> Result<()> {
+    if !input.is_dir() {
+        files.push(input.to_path_buf());
+        return Ok(());
+    }
+
+    let read_dir = |path: &Path| {
+        fs::read_dir(path).map_err(|source| CompilerError::Io {
+            path: path.to_path_buf(),
+            source,
+        })
+    };
+
+    let mut entries = read_dir(input)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort();
+
+    for path in entries {
+        if path.is_dir() {
+            collect_sources(&path, extension, files)?;
+        } else if path.extension().is_some_and(|found| found == extension) {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
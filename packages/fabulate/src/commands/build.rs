@@ -2,7 +2,9 @@ use std::path::PathBuf;
 
 use fabc::{Compiler, ExecutableOptions};
 
+use crate::commands::host_functions;
 use crate::error::Result;
+use crate::report::Reporter;
 
 #[derive(clap::Args)]
 pub struct Build {
@@ -20,16 +22,22 @@ pub struct Build {
     /// Build the generated launcher in release mode
     #[arg(long)]
     pub release: bool,
+
+    /// JSON manifest declaring the host functions the story may call
+    #[arg(long)]
+    pub host_functions: Option<PathBuf>,
 }
 
 impl Build {
-    pub fn exec(&self) -> Result<()> {
+    pub fn exec(&self, reporter: &Reporter) -> Result<()> {
         let artifact = Compiler::build_executable_with_options(ExecutableOptions {
             entry: self.input.clone(),
             output: self.output.clone(),
             module_name: self.module_name.clone(),
             release: self.release,
+            host_functions: host_functions(self.host_functions.as_deref())?.declarations,
         })?;
+        reporter.diagnostics(&artifact.warnings);
 
         println!(
             "Wrote standalone executable for {} to {}",
@@ -0,0 +1,96 @@
+use std::path::PathBuf;
+
+use fabc::{Compiler, Diagnostic, Severity};
+use serde_json::{json, Value};
+
+use crate::commands::host_functions;
+use crate::error::{Error, Result};
+use crate::report::Reporter;
+
+#[derive(clap::Args)]
+pub struct Check {
+    /// The input fab source file
+    pub input: PathBuf,
+
+    /// Fail when the story has warnings, not just errors
+    #[arg(long)]
+    pub deny_warnings: bool,
+
+    /// How to print diagnostics
+    #[arg(long, value_enum, default_value_t = CheckFormat::Human)]
+    pub format: CheckFormat,
+
+    /// JSON manifest declaring the host functions the story may call
+    #[arg(long)]
+    pub host_functions: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, Debug, clap::ValueEnum)]
+pub enum CheckFormat {
+    Human,
+    Json,
+}
+
+impl Check {
+    pub fn exec(&self, reporter: &Reporter) -> Result<()> {
+        let host_functions = host_functions(self.host_functions.as_deref())?;
+        let diagnostics =
+            match Compiler.check_with_host_functions(&self.input, &host_functions.declarations) {
+                Ok(diagnostics) => diagnostics,
+                Err(error) => match error.diagnostics() {
+                    Some(diagnostics) => diagnostics.to_vec(),
+                    None => return Err(error.into()),
+                },
+            };
+
+        let errors = diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == Severity::Error)
+            .count();
+        let warnings = diagnostics.len() - errors;
+        let failed = errors > 0 || (self.deny_warnings && warnings > 0);
+
+        match self.format {
+            // A failed check is summarized once, by the error it returns.
+            CheckFormat::Human => {
+                reporter.diagnostics(&diagnostics);
+                if !failed {
+                    eprintln!(
+                        "Checked {}: {errors} error(s), {warnings} warning(s)",
+                        self.input.display()
+                    );
+                }
+            }
+            CheckFormat::Json => {
+                let report = json!({
+                    "entry": self.input.display().to_string(),
+                    "errors": errors,
+                    "warnings": warnings,
+                    "diagnostics": diagnostics.iter().map(diagnostic_json).collect::<Vec<_>>(),
+                });
+                println!("{report}");
+            }
+        }
+
+        if failed {
+            return Err(Error::CheckFailed { errors, warnings });
+        }
+
+        Ok(())
+    }
+}
+
+fn diagnostic_json(diagnostic: &Diagnostic) -> Value {
+    let span = &diagnostic.error.span;
+    json!({
+        "severity": match diagnostic.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        },
+        "name": diagnostic.error.kind.name(),
+        "message": diagnostic.error.kind.message(),
+        "path": diagnostic.path.as_ref().map(|path| path.display().to_string()),
+        "start": { "line": span.start().line(), "column": span.start().col() },
+        "end": { "line": span.end().line(), "column": span.end().col() },
+    })
+}
@@ -2,7 +2,9 @@ use std::path::PathBuf;
 
 use fabc::{CompileOptions, Compiler};
 
+use crate::commands::host_functions;
 use crate::error::Result;
+use crate::report::Reporter;
 
 #[derive(clap::Args)]
 pub struct Compile {
@@ -24,17 +26,23 @@ pub struct Compile {
     /// Override the emitted LLVM module name
     #[arg(long)]
     pub module_name: Option<String>,
+
+    /// JSON manifest declaring the host functions the story may call
+    #[arg(long)]
+    pub host_functions: Option<PathBuf>,
 }
 
 impl Compile {
-    pub fn exec(&self) -> Result<()> {
+    pub fn exec(&self, reporter: &Reporter) -> Result<()> {
         let artifact = Compiler::compile_with_options(CompileOptions {
             entry: self.input.clone(),
             output: self.output.clone(),
             object_output: self.object_output.clone(),
             module_name: self.module_name.clone(),
             bundle_output: self.bundle_output.clone(),
+            host_functions: host_functions(self.host_functions.as_deref())?.declarations,
         })?;
+        reporter.diagnostics(&artifact.warnings);
 
         println!(
             "Wrote LLVM IR for {} to {}",
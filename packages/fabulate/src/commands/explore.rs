@@ -0,0 +1,108 @@
+use std::path::PathBuf;
+
+use fabc::{explore, Compiler, ExploreOptions, PathChoice, StoryMachine};
+
+use crate::commands::host_functions;
+use crate::error::{Error, Result};
+use crate::report::Reporter;
+
+#[derive(clap::Args)]
+pub struct Explore {
+    /// The input fab source file
+    pub input: PathBuf,
+
+    /// Most steps followed along any single playthrough
+    #[arg(long, default_value_t = ExploreOptions::default().max_depth)]
+    pub max_depth: usize,
+
+    /// Most distinct story states to visit
+    #[arg(long, default_value_t = ExploreOptions::default().max_states)]
+    pub max_states: usize,
+
+    /// JSON manifest declaring the host functions the story may call
+    #[arg(long)]
+    pub host_functions: Option<PathBuf>,
+}
+
+impl Explore {
+    pub fn exec(&self, reporter: &Reporter) -> Result<()> {
+        let host_functions = host_functions(self.host_functions.as_deref())?;
+        let (program, warnings) = Compiler
+            .build_program_with_host_functions(&self.input, &host_functions.declarations)?;
+        reporter.diagnostics(&warnings);
+
+        let mut machine = StoryMachine::new(program)?;
+        host_functions.register(&mut machine);
+        let report = explore(
+            &machine,
+            ExploreOptions {
+                max_depth: self.max_depth,
+                max_states: self.max_states,
+            },
+        );
+
+        println!("explored {} state(s)", report.states);
+        if report.truncated {
+            println!("warning: exploration hit its limits; results may be incomplete");
+        }
+
+        println!("\nendings:");
+        for ending in &report.endings {
+            println!(
+                "  {} ({} playthrough(s)) via {}",
+                ending.part,
+                ending.count,
+                path(&ending.path)
+            );
+        }
+
+        if !report.errors.is_empty() {
+            println!("\nruntime errors:");
+            for error in &report.errors {
+                let part = error.part.as_deref().unwrap_or("<start>");
+                println!("  {part}: {} via {}", error.error, path(&error.path));
+            }
+        }
+        if !report.loops.is_empty() {
+            println!("\ninfinite loops:");
+            for found in &report.loops {
+                println!("  {} via {}", found.parts.join(" -> "), path(&found.path));
+            }
+        }
+        if !report.dead_ends.is_empty() {
+            println!("\ndead ends (no available choice):");
+            for dead_end in &report.dead_ends {
+                println!("  {} via {}", dead_end.part, path(&dead_end.path));
+            }
+        }
+        if !report.unvisited_parts.is_empty() {
+            println!("\nunvisited parts:");
+            for part in &report.unvisited_parts {
+                println!("  {part}");
+            }
+        }
+
+        if report.has_problems() {
+            return Err(Error::ExploreFailed {
+                errors: report.errors.len(),
+                loops: report.loops.len(),
+                dead_ends: report.dead_ends.len(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// The choices of a playthrough, e.g. `"Go left" > "Open the door"`.
+fn path(choices: &[PathChoice]) -> String {
+    if choices.is_empty() {
+        return "start".to_string();
+    }
+
+    choices
+        .iter()
+        .map(|choice| format!("{:?}", choice.text))
+        .collect::<Vec<_>>()
+        .join(" > ")
+}
@@ -0,0 +1,57 @@
+use std::{fs, path::PathBuf};
+
+use fabc::{Compiler, Error as CompilerError};
+
+use crate::commands::collect_sources;
+use crate::error::{Error, Result};
+
+#[derive(clap::Args)]
+pub struct Fmt {
+    /// The fab source files or directories to format
+    #[arg(required = true)]
+    pub inputs: Vec<PathBuf>,
+
+    /// Report files that are not formatted instead of rewriting them
+    #[arg(long)]
+    pub check: bool,
+}
+
+impl Fmt {
+    pub fn exec(&self) -> Result<()> {
+        let mut files = Vec::new();
+        for input in &self.inputs {
+            collect_sources(input, "fab", &mut files)?;
+        }
+
+        let mut unformatted = Vec::new();
+        for file in files {
+            let formatted = Compiler.format(&file)?;
+
+            let source = fs::read_to_string(&file).map_err(|source| CompilerError::Io {
+                path: file.clone(),
+                source,
+            })?;
+            if source == formatted {
+                continue;
+            }
+
+            if self.check {
+                println!("{}", file.display());
+                unformatted.push(file);
+            } else {
+                fs::write(&file, formatted).map_err(|source| CompilerError::Io {
+                    path: file.clone(),
+                    source,
+                })?;
+            }
+        }
+
+        if !unformatted.is_empty() {
+            return Err(Error::FormatCheckFailed {
+                files: unformatted.len(),
+            });
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,246 @@
+use std::{collections::BTreeMap, fmt::Write as _, path::PathBuf};
+
+use fabc::{Compiler, StoryGraph};
+use serde_json::json;
+
+use crate::commands::host_functions;
+use crate::error::Result;
+use crate::report::Reporter;
+
+#[derive(clap::Args)]
+pub struct Graph {
+    /// The input fab source file
+    pub input: PathBuf,
+
+    /// How to print the part graph
+    #[arg(long, value_enum, default_value_t = GraphFormat::Dot)]
+    pub format: GraphFormat,
+
+    /// JSON manifest declaring the host functions the story may call
+    #[arg(long)]
+    pub host_functions: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, Debug, clap::ValueEnum)]
+pub enum GraphFormat {
+    /// Graphviz DOT
+    Dot,
+    /// Mermaid flowchart
+    Mermaid,
+    Json,
+}
+
+impl Graph {
+    pub fn exec(&self, reporter: &Reporter) -> Result<()> {
+        let host_functions = host_functions(self.host_functions.as_deref())?;
+        let (graph, warnings) =
+            Compiler.graph_with_host_functions(&self.input, &host_functions.declarations)?;
+        reporter.diagnostics(&warnings);
+
+        let output = match self.format {
+            GraphFormat::Dot => dot(&graph),
+            GraphFormat::Mermaid => mermaid(&graph),
+            GraphFormat::Json => format!("{:#}\n", graph_json(&graph)),
+        };
+        print!("{output}");
+
+        Ok(())
+    }
+}
+
+/// Parts grouped by the namespace they were imported under; `None` holds the entry's own parts.
+fn clusters(graph: &StoryGraph) -> BTreeMap<Option<&str>, Vec<&str>> {
+    let mut clusters = BTreeMap::<_, Vec<_>>::new();
+    for part in &graph.parts {
+        clusters
+            .entry(StoryGraph::namespace(part))
+            .or_default()
+            .push(part.as_str());
+    }
+    clusters
+}
+
+/// The part's name within its namespace.
+fn local_name(part: &str) -> &str {
+    part.rsplit_once('.').map_or(part, |(_, name)| name)
+}
+
+fn dot(graph: &StoryGraph) -> String {
+    let mut out = String::from("digraph story {\n    rankdir=LR;\n    node [shape=box];\n");
+
+    for (namespace, parts) in clusters(graph) {
+        let indent = match namespace {
+            Some(namespace) => {
+                let _ = writeln!(out, "    subgraph \"cluster_{}\" {{", dot_escape(namespace));
+                let _ = writeln!(out, "        label=\"{}\";", dot_escape(namespace));
+                "        "
+            }
+            None => "    ",
+        };
+
+        for part in parts {
+            let mut attributes = vec![format!("label=\"{}\"", dot_escape(local_name(part)))];
+            if graph.start.as_deref() == Some(part) {
+                attributes.push("penwidth=2".to_string());
+            }
+            if graph.endings.contains(part) {
+                attributes.push("peripheries=2".to_string());
+            }
+            if graph.dead_ends.contains(part) {
+                attributes.push("shape=doubleoctagon, color=red".to_string());
+            }
+            if graph.unreachable.contains(part) {
+                attributes.push("style=dashed, color=gray, fontcolor=gray".to_string());
+            }
+            let _ = writeln!(
+                out,
+                "{indent}\"{}\" [{}];",
+                dot_escape(part),
+                attributes.join(", ")
+            );
+        }
+
+        if namespace.is_some() {
+            out.push_str("    }\n");
+        }
+    }
+
+    for edge in &graph.edges {
+        let _ = write!(
+            out,
+            "    \"{}\" -> \"{}\"",
+            dot_escape(&edge.from),
+            dot_escape(&edge.to)
+        );
+        if let Some(label) = &edge.label {
+            let _ = write!(out, " [label=\"{}\"]", dot_escape(label));
+        }
+        out.push_str(";\n");
+    }
+
+    if !graph.dynamic.is_empty() {
+        out.push_str("    \"?\" [label=\"runtime target\", shape=diamond, style=dashed];\n");
+        for part in &graph.dynamic {
+            let _ = writeln!(out, "    \"{}\" -> \"?\" [style=dashed];", dot_escape(part));
+        }
+    }
+
+    out.push_str("}\n");
+    out
+}
+
+fn mermaid(graph: &StoryGraph) -> String {
+    let ids = graph
+        .parts
+        .iter()
+        .enumerate()
+        .map(|(index, part)| (part.as_str(), format!("p{index}")))
+        .collect::<BTreeMap<_, _>>();
+    let mut out = String::from("flowchart LR\n");
+
+    for (index, (namespace, parts)) in clusters(graph).into_iter().enumerate() {
+        let indent = match namespace {
+            Some(namespace) => {
+                let _ = writeln!(
+                    out,
+                    "    subgraph ns{index}[\"{}\"]",
+                    mermaid_escape(namespace)
+                );
+                "        "
+            }
+            None => "    ",
+        };
+
+        for part in parts {
+            let label = mermaid_escape(local_name(part));
+            let node = if graph.dead_ends.contains(part) {
+                format!("{}[[\"{label}\"]]", ids[part])
+            } else if graph.endings.contains(part) {
+                format!("{}([\"{label}\"])", ids[part])
+            } else {
+                format!("{}[\"{label}\"]", ids[part])
+            };
+            let _ = writeln!(out, "{indent}{node}");
+        }
+
+        if namespace.is_some() {
+            out.push_str("    end\n");
+        }
+    }
+
+    for edge in &graph.edges {
+        let (from, to) = (&ids[edge.from.as_str()], &ids[edge.to.as_str()]);
+        match &edge.label {
+            Some(label) => {
+                let _ = writeln!(out, "    {from} -->|\"{}\"| {to}", mermaid_escape(label));
+            }
+            None => {
+                let _ = writeln!(out, "    {from} --> {to}");
+            }
+        }
+    }
+
+    if !graph.dynamic.is_empty() {
+        out.push_str("    dynamic{{\"runtime target\"}}\n");
+        for part in &graph.dynamic {
+            let _ = writeln!(out, "    {} -.-> dynamic", ids[part.as_str()]);
+        }
+    }
+
+    let mut classes = Vec::new();
+    if let Some(start) = graph.start.as_deref().and_then(|start| ids.get(start)) {
+        classes.push(("start", "stroke-width:3px", vec![start.as_str()]));
+    }
+    for (class, style, parts) in [
+        ("ending", "fill:#e3f4e1,stroke:#27ae60", &graph.endings),
+        ("deadEnd", "fill:#fde2e1,stroke:#c0392b", &graph.dead_ends),
+        (
+            "unreachable",
+            "stroke-dasharray:4 4,color:#888",
+            &graph.unreachable,
+        ),
+    ] {
+        let parts = parts
+            .iter()
+            .filter_map(|part| ids.get(part.as_str()).map(String::as_str))
+            .collect::<Vec<_>>();
+        if !parts.is_empty() {
+            classes.push((class, style, parts));
+        }
+    }
+    for (class, style, parts) in classes {
+        let _ = writeln!(out, "    classDef {class} {style}");
+        let _ = writeln!(out, "    class {} {class}", parts.join(","));
+    }
+
+    out
+}
+
+fn graph_json(graph: &StoryGraph) -> serde_json::Value {
+    json!({
+        "start": graph.start,
+        "parts": graph.parts.iter().map(|part| json!({
+            "name": part,
+            "namespace": StoryGraph::namespace(part),
+            "unreachable": graph.unreachable.contains(part),
+            "ending": graph.endings.contains(part),
+            "dead_end": graph.dead_ends.contains(part),
+            "dynamic": graph.dynamic.contains(part),
+        })).collect::<Vec<_>>(),
+        "edges": graph.edges.iter().map(|edge| json!({
+            "from": edge.from,
+            "to": edge.to,
+            "label": edge.label,
+        })).collect::<Vec<_>>(),
+    })
+}
+
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn mermaid_escape(text: &str) -> String {
+    text.replace('"', "#quot;").replace('\n', " ")
+}
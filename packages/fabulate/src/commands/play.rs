@@ -1,78 +1,257 @@
+use std::fs;
 use std::io::{self, IsTerminal, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use fabc::{CompiledBundle, StoryEvent};
+use fabc::{
+    parse_context, CompiledBundle, Compiler, StoryEvent, StoryMachine, StorySnapshot, StoryValue,
+};
 
-use crate::error::Result;
+use crate::commands::host_functions;
+use crate::error::{Error, Result};
+use crate::report::Reporter;
 
 #[derive(clap::Args)]
 pub struct Play {
-    /// Path to a compiled bundle directory or the story.json manifest itself
-    pub bundle: PathBuf,
+    /// A .fab entry file, a compiled bundle directory, or the bundle's story.json manifest
+    pub story: PathBuf,
+
+    /// Resume from a save file written with `:save`
+    #[arg(long, conflicts_with_all = ["start", "context"])]
+    pub resume: Option<PathBuf>,
+
+    /// Begin at this part instead of the story's start part
+    #[arg(long)]
+    pub start: Option<String>,
+
+    /// Initial context values as a JSON object, e.g. `{"trust": 2}`
+    #[arg(long)]
+    pub context: Option<String>,
+
+    /// Seed for `random`, `random_int` and `pick`, to replay the same rolls; with `--resume`
+    /// it replaces the saved random state
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// JSON manifest declaring the host functions the story may call
+    #[arg(long)]
+    pub host_functions: Option<PathBuf>,
+}
+
+enum PlayerInput {
+    Continue,
+    Choice(usize),
+    Save(String),
+    Load(String),
+    Quit,
 }
 
 impl Play {
-    pub fn exec(&self) -> Result<()> {
-        let bundle = CompiledBundle::load(&self.bundle)?;
-        let mut machine = bundle.story_machine_with_native_fallback()?;
-        let mut event = machine.start()?;
+    pub fn exec(&self, reporter: &Reporter) -> Result<()> {
+        let mut machine = self.story_machine(reporter)?;
+        if let Some(context) = &self.context {
+            for (key, value) in parse_context(context)? {
+                machine.set_context_value(key, value);
+            }
+        }
+        if let Some(path) = &self.resume {
+            machine.load_snapshot(&read_snapshot(path)?)?;
+        }
+        if let Some(seed) = self.seed {
+            machine.set_seed(seed);
+        }
+        let mut event = match (&self.resume, &self.start) {
+            (Some(_), _) => machine.current()?,
+            (None, Some(part)) => machine.start_at(part)?,
+            (None, None) => machine.start()?,
+        };
 
         loop {
-            match event {
+            let input = match &event {
                 StoryEvent::Narration(view) => {
                     println!("{}", view.text);
-                    prompt_continue()?;
-                    event = machine.advance()?;
+                    prompt_continue()?
                 }
                 StoryEvent::Dialogue(view) => {
                     println!("[{}] {}", view.speaker, view.text);
-                    prompt_continue()?;
-                    event = machine.advance()?;
+                    prompt_continue()?
                 }
                 StoryEvent::Selection(selection) => {
                     for (index, choice) in selection.choices.iter().enumerate() {
-                        println!("{}. {}", index + 1, choice.text);
+                        if choice.enabled {
+                            println!("{}. {}", index + 1, choice.text);
+                        } else {
+                            println!("{}. {} (unavailable)", index + 1, choice.text);
+                        }
                     }
 
-                    let choice = prompt_choice(selection.choices.len())?;
-                    event = machine.choose(choice)?;
+                    let enabled: Vec<bool> = selection
+                        .choices
+                        .iter()
+                        .map(|choice| choice.enabled)
+                        .collect();
+                    prompt_choice(&enabled)?
+                }
+                StoryEvent::Directive { name, args } => {
+                    let line = directive_line(name, args);
+                    if reporter.color() {
+                        println!("\x1b[2m{line}\x1b[0m");
+                    } else {
+                        println!("{line}");
+                    }
+                    PlayerInput::Continue
                 }
+                StoryEvent::PartEntered { .. } => PlayerInput::Continue,
                 StoryEvent::Finished => {
                     println!("Story finished.");
                     return Ok(());
                 }
-            }
+            };
+
+            event = match input {
+                PlayerInput::Continue => machine.advance()?,
+                PlayerInput::Choice(choice) => machine.choose(choice)?,
+                PlayerInput::Save(slot) => {
+                    match save_slot(&machine, &slot) {
+                        Ok(path) => println!("Saved to {}.", path.display()),
+                        Err(error) => eprintln!("{error}"),
+                    }
+                    continue;
+                }
+                PlayerInput::Load(slot) => {
+                    match read_snapshot(&slot_path(&slot))
+                        .and_then(|snapshot| Ok(machine.load_snapshot(&snapshot)?))
+                    {
+                        Ok(()) => machine.current()?,
+                        Err(error) => {
+                            eprintln!("{error}");
+                            continue;
+                        }
+                    }
+                }
+                PlayerInput::Quit => return Ok(()),
+            };
         }
     }
 }
 
-fn prompt_continue() -> Result<()> {
-    let stdin = io::stdin();
-    let mut stdout = io::stdout();
+impl Play {
+    /// Compiles `.fab` sources in memory; anything else is loaded as a compiled bundle.
+    fn story_machine(&self, reporter: &Reporter) -> Result<StoryMachine> {
+        let host_functions = host_functions(self.host_functions.as_deref())?;
+        let mut machine = if self
+            .story
+            .extension()
+            .is_some_and(|extension| extension == "fab")
+        {
+            let (program, warnings) = Compiler
+                .build_program_with_host_functions(&self.story, &host_functions.declarations)?;
+            reporter.diagnostics(&warnings);
+            StoryMachine::new(program)?
+        } else {
+            CompiledBundle::load(&self.story)?.story_machine_with_native_fallback()?
+        };
 
-    if !stdin.is_terminal() || !stdout.is_terminal() {
-        return Ok(());
+        host_functions.register(&mut machine);
+        Ok(machine)
     }
+}
 
-    let mut line = String::new();
+/// Renders a directive the way it is written in the source, e.g. `! play_music("rain")`.
+fn directive_line(name: &str, args: &[StoryValue]) -> String {
+    let args = args
+        .iter()
+        .map(|arg| match arg {
+            StoryValue::String(text) => format!("{text:?}"),
+            arg => arg.to_string(),
+        })
+        .collect::<Vec<_>>();
+    format!("! {name}({})", args.join(", "))
+}
 
-    write!(stdout, "> ")?;
+fn slot_path(slot: &str) -> PathBuf {
+    let path = PathBuf::from(slot);
+    if path.extension().is_some() {
+        path
+    } else {
+        path.with_extension("json")
+    }
+}
+
+fn save_slot(machine: &StoryMachine, slot: &str) -> Result<PathBuf> {
+    let path = slot_path(slot);
+    let snapshot = machine.snapshot()?;
+    let bytes = serde_json::to_vec_pretty(&snapshot).map_err(|source| Error::SaveFile {
+        path: path.clone(),
+        source,
+    })?;
+    fs::write(&path, bytes)?;
+    Ok(path)
+}
+
+fn read_snapshot(path: &Path) -> Result<StorySnapshot> {
+    let bytes = fs::read(path)?;
+    serde_json::from_slice(&bytes).map_err(|source| Error::SaveFile {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// Reads lines until one is not a `:` command or is a known one. `Ok(None)` leaves an ordinary
+/// line in `line`; `Ok(Some(PlayerInput::Quit))` also stands for the end of input, after which
+/// `line` is empty.
+fn read_input(line: &mut String) -> Result<Option<PlayerInput>> {
+    loop {
+        line.clear();
+        if io::stdin().read_line(line)? == 0 {
+            return Ok(Some(PlayerInput::Quit));
+        }
+
+        let Some(command) = line.trim().strip_prefix(':') else {
+            return Ok(None);
+        };
+
+        let mut words = command.split_whitespace();
+        match (words.next(), words.next(), words.next()) {
+            (Some("save"), Some(slot), None) => {
+                return Ok(Some(PlayerInput::Save(slot.to_string())))
+            }
+            (Some("load"), Some(slot), None) => {
+                return Ok(Some(PlayerInput::Load(slot.to_string())))
+            }
+            (Some("quit"), None, None) => return Ok(Some(PlayerInput::Quit)),
+            _ => eprintln!("Unknown command. Use `:save <slot>`, `:load <slot>` or `:quit`."),
+        }
+    }
+}
+
+/// Waits for Enter or a command after a line of text. The `> ` prompt is only shown on a
+/// terminal, and the end of piped input continues, so scripted sessions run on to the next
+/// selection.
+fn prompt_continue() -> Result<PlayerInput> {
+    let mut stdout = io::stdout();
+    if io::stdin().is_terminal() && stdout.is_terminal() {
+        write!(stdout, "> ")?;
+    }
     stdout.flush()?;
-    stdin.read_line(&mut line)?;
 
-    Ok(())
+    let mut line = String::new();
+    match read_input(&mut line)? {
+        Some(PlayerInput::Quit) if line.is_empty() => Ok(PlayerInput::Continue),
+        input => Ok(input.unwrap_or(PlayerInput::Continue)),
+    }
 }
 
-fn prompt_choice(choice_count: usize) -> Result<usize> {
-    let stdin = io::stdin();
+fn prompt_choice(enabled: &[bool]) -> Result<PlayerInput> {
+    let choice_count = enabled.len();
     let mut line = String::new();
 
     loop {
         print!("> ");
         io::stdout().flush()?;
 
-        line.clear();
-        stdin.read_line(&mut line)?;
+        if let Some(input) = read_input(&mut line)? {
+            return Ok(input);
+        }
 
         let trimmed = line.trim();
         let Ok(index) = trimmed.parse::<usize>() else {
@@ -81,7 +260,11 @@ fn prompt_choice(choice_count: usize) -> Result<usize> {
         };
 
         if (1..=choice_count).contains(&index) {
-            return Ok(index - 1);
+            if enabled[index - 1] {
+                return Ok(PlayerInput::Choice(index - 1));
+            }
+            eprintln!("That choice is unavailable.");
+            continue;
         }
 
         eprintln!("Enter a number between 1 and {choice_count}.");
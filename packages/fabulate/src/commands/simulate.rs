@@ -0,0 +1,107 @@
+use std::path::PathBuf;
+
+use fabc::{simulate, ChoiceStrategy, Compiler, SimulateOptions, StoryMachine};
+
+use crate::commands::host_functions;
+use crate::error::Result;
+use crate::report::Reporter;
+
+#[derive(clap::Args)]
+pub struct Simulate {
+    /// The input fab source file
+    pub input: PathBuf,
+
+    /// Number of playthroughs to simulate
+    #[arg(long, default_value_t = SimulateOptions::default().runs)]
+    pub runs: usize,
+
+    /// How the simulated reader picks a choice
+    #[arg(long, value_enum, default_value_t = Strategy::Random)]
+    pub strategy: Strategy,
+
+    /// Seed for choices and story rolls; equal seeds give equal results
+    #[arg(long, default_value_t = SimulateOptions::default().seed)]
+    pub seed: u64,
+
+    /// Steps after which a playthrough is abandoned
+    #[arg(long, default_value_t = SimulateOptions::default().max_steps)]
+    pub max_steps: usize,
+
+    /// JSON manifest declaring the host functions the story may call
+    #[arg(long)]
+    pub host_functions: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, Debug, clap::ValueEnum)]
+pub enum Strategy {
+    /// Uniformly random among enabled choices
+    Random,
+    /// Random in proportion to each choice's `weight` property
+    Weighted,
+    /// Always the first enabled choice
+    First,
+}
+
+impl Simulate {
+    pub fn exec(&self, reporter: &Reporter) -> Result<()> {
+        let host_functions = host_functions(self.host_functions.as_deref())?;
+        let (program, warnings) = Compiler
+            .build_program_with_host_functions(&self.input, &host_functions.declarations)?;
+        reporter.diagnostics(&warnings);
+
+        let mut machine = StoryMachine::new(program)?;
+        host_functions.register(&mut machine);
+        let report = simulate(
+            &machine,
+            SimulateOptions {
+                runs: self.runs,
+                strategy: match self.strategy {
+                    Strategy::Random => ChoiceStrategy::Random,
+                    Strategy::Weighted => ChoiceStrategy::Weighted,
+                    Strategy::First => ChoiceStrategy::First,
+                },
+                seed: self.seed,
+                max_steps: self.max_steps,
+            },
+        );
+
+        let share = |count: usize| 100.0 * count as f64 / report.runs.max(1) as f64;
+        println!("simulated {} run(s) with seed {}", report.runs, self.seed);
+
+        println!("\nendings:");
+        for (part, count) in &report.endings {
+            println!("  {part}: {count} ({:.1}%)", share(*count));
+        }
+
+        println!("\npart visits:");
+        for (part, count) in &report.visits {
+            println!("  {part}: {count} ({:.1}%)", share(*count));
+        }
+
+        if !report.context.is_empty() {
+            println!("\ncontext at the end:");
+            for (path, summary) in &report.context {
+                println!(
+                    "  {path}: min {} / mean {:.2} / max {}",
+                    summary.min, summary.mean, summary.max
+                );
+            }
+        }
+
+        if !report.errors.is_empty() {
+            println!("\nruntime errors:");
+            for (message, count) in &report.errors {
+                println!("  {message}: {count} ({:.1}%)", share(*count));
+            }
+        }
+        if report.unfinished > 0 {
+            println!(
+                "\nunfinished: {} ({:.1}%)",
+                report.unfinished,
+                share(report.unfinished)
+            );
+        }
+
+        Ok(())
+    }
+}
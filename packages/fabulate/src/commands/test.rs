@@ -0,0 +1,59 @@
+use std::path::PathBuf;
+
+use fabc::{playtest::Playtest, Compiler};
+
+use crate::commands::{collect_sources, host_functions};
+use crate::error::{Error, Result};
+use crate::report::Reporter;
+
+#[derive(clap::Args)]
+pub struct Test {
+    /// The .fabtest files to run, or directories to search for them
+    #[arg(required = true)]
+    pub inputs: Vec<PathBuf>,
+
+    /// JSON manifest declaring the host functions the stories may call
+    #[arg(long)]
+    pub host_functions: Option<PathBuf>,
+}
+
+impl Test {
+    pub fn exec(&self, reporter: &Reporter) -> Result<()> {
+        let mut files = Vec::new();
+        for input in &self.inputs {
+            collect_sources(input, "fabtest", &mut files)?;
+        }
+
+        let host_functions = host_functions(self.host_functions.as_deref())?;
+        let (mut passed, mut failed) = (0, 0);
+        for file in files {
+            let playtest = Playtest::load(&file)?;
+            let (program, warnings) = Compiler
+                .build_program_with_host_functions(&playtest.story, &host_functions.declarations)?;
+            reporter.diagnostics(&warnings);
+
+            for report in playtest.run_with_host_functions(&program, &host_functions) {
+                let Some(failure) = report.failure else {
+                    println!("test {} ... ok", report.name);
+                    passed += 1;
+                    continue;
+                };
+
+                println!("test {} ... FAILED", report.name);
+                println!("  --> {}:{}", file.display(), failure.line);
+                if !failure.expected.is_empty() {
+                    println!("  expected: {}", failure.expected);
+                }
+                println!("     found: {}", failure.found);
+                failed += 1;
+            }
+        }
+
+        println!("\ntest result: {passed} passed; {failed} failed");
+        if failed > 0 {
+            return Err(Error::TestsFailed { failed });
+        }
+
+        Ok(())
+    }
+}
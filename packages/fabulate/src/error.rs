@@ -1,6 +1,6 @@
-use std::{io, result::Result as StdResult};
+use std::{io, path::PathBuf, result::Result as StdResult};
 
-use fabc::{Error as CompilerError, StoryRuntimeError};
+use fabc::{Diagnostic, Error as CompilerError, StoryRuntimeError};
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
@@ -10,6 +10,59 @@ pub enum Error {
     Runtime(#[from] StoryRuntimeError),
     #[error(transparent)]
     Compiler(#[from] CompilerError),
+    #[error("check failed with {errors} error(s) and {warnings} warning(s)")]
+    CheckFailed { errors: usize, warnings: usize },
+    #[error("{files} file(s) are not formatted")]
+    FormatCheckFailed { files: usize },
+    #[error(
+        "exploration found {errors} runtime error(s), {loops} loop(s) and {dead_ends} dead end(s)"
+    )]
+    ExploreFailed {
+        errors: usize,
+        loops: usize,
+        dead_ends: usize,
+    },
+    #[error("{failed} playtest(s) failed")]
+    TestsFailed { failed: usize },
+    #[error("invalid save file `{path}`: {source}")]
+    SaveFile {
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
 }
 
 pub type Result<T> = StdResult<T, Error>;
+
+impl Error {
+    pub fn diagnostics(&self) -> Option<&[Diagnostic]> {
+        match self {
+            Error::Compiler(error) => error.diagnostics(),
+            _ => None,
+        }
+    }
+
+    /// Exit status for this failure, following the BSD `sysexits` codes.
+    pub fn exit_code(&self) -> u8 {
+        const DATA_ERROR: u8 = 65;
+        const SOFTWARE_ERROR: u8 = 70;
+        const IO_ERROR: u8 = 74;
+
+        if self.diagnostics().is_some() {
+            return DATA_ERROR;
+        }
+
+        match self {
+            Error::SaveFile { .. }
+            | Error::CheckFailed { .. }
+            | Error::FormatCheckFailed { .. }
+            | Error::ExploreFailed { .. }
+            | Error::TestsFailed { .. }
+            | Error::Compiler(CompilerError::InvalidPlaytest { .. })
+            | Error::Compiler(CompilerError::InvalidContext(_))
+            | Error::Compiler(CompilerError::InvalidHostFunctions { .. }) => DATA_ERROR,
+            Error::Io(_) | Error::Compiler(CompilerError::Io { .. }) => IO_ERROR,
+            _ => SOFTWARE_ERROR,
+        }
+    }
+}
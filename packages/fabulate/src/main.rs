@@ -2,9 +2,11 @@ use clap::Parser;
 use std::process::ExitCode;
 
 use crate::commands::Commands;
+use crate::report::{ColorChoice, Reporter};
 
 mod commands;
 mod error;
+mod report;
 
 /// Fabulist compiler cli
 #[derive(clap::Parser)]
@@ -13,16 +15,21 @@ struct Cli {
     /// The command to run
     #[clap(subcommand)]
     command: Commands,
+
+    /// When to color diagnostics
+    #[arg(long, value_enum, global = true, default_value_t = ColorChoice::Auto)]
+    color: ColorChoice,
 }
 
 fn main() -> ExitCode {
     let cli = Cli::parse();
+    let reporter = Reporter::new(cli.color);
 
-    match cli.command.exec() {
+    match cli.command.exec(&reporter) {
         Ok(()) => ExitCode::SUCCESS,
         Err(error) => {
-            eprintln!("{error}");
-            ExitCode::FAILURE
+            reporter.error(&error);
+            ExitCode::from(error.exit_code())
         }
     }
 }
@@ -0,0 +1,61 @@
+use std::{
+    env,
+    io::{self, IsTerminal},
+};
+
+use fabc::{Diagnostic, Severity};
+
+use crate::error::Error;
+
+#[derive(Clone, Copy, Debug, Default, clap::ValueEnum)]
+pub enum ColorChoice {
+    /// Color when stderr is a terminal and `NO_COLOR` is unset
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// Prints diagnostics and failures to stderr.
+#[derive(Clone, Copy, Debug)]
+pub struct Reporter {
+    color: bool,
+}
+
+impl Reporter {
+    pub fn new(choice: ColorChoice) -> Self {
+        let color = match choice {
+            ColorChoice::Auto => io::stderr().is_terminal() && env::var_os("NO_COLOR").is_none(),
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        };
+
+        Self { color }
+    }
+
+    /// Whether `--color` allows styled output.
+    pub fn color(&self) -> bool {
+        self.color
+    }
+
+    pub fn diagnostics(&self, diagnostics: &[Diagnostic]) {
+        for diagnostic in diagnostics {
+            eprintln!("{}\n", diagnostic.render(self.color));
+        }
+    }
+
+    pub fn error(&self, error: &Error) {
+        let Some(diagnostics) = error.diagnostics() else {
+            eprintln!("error: {error}");
+            return;
+        };
+
+        self.diagnostics(diagnostics);
+        let errors = diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == Severity::Error)
+            .count();
+        let warnings = diagnostics.len() - errors;
+        eprintln!("error: could not compile due to {errors} error(s) and {warnings} warning(s)");
+    }
+}
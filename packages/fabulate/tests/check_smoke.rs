@@ -0,0 +1,65 @@
+use std::{fs, process::Command};
+
+use fabc_reg_test::temp_case_dir;
+
+const WARNING_STORY: &str = "Story { start: \"intro\" }\n# intro\n* \"Hi\"\n# orphan\n* \"x\"\n";
+
+#[test]
+fn check_reports_warnings_as_json() {
+    let root = temp_case_dir("fabulate_check_json_smoke");
+    fs::create_dir_all(&root).expect("create temp dir");
+
+    let entry = root.join("story.fab");
+    fs::write(&entry, WARNING_STORY).expect("write entry");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_fabulate"))
+        .arg("check")
+        .arg(&entry)
+        .arg("--format")
+        .arg("json")
+        .output()
+        .expect("run fabulate check");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "stdout={stdout}");
+
+    let report: serde_json::Value = serde_json::from_str(&stdout).expect("valid json report");
+    assert_eq!(report["errors"], 0);
+    assert_eq!(report["warnings"], 1);
+    let diagnostic = &report["diagnostics"][0];
+    assert_eq!(diagnostic["severity"], "warning");
+    assert_eq!(diagnostic["name"], "Unreachable part");
+    assert_eq!(diagnostic["start"]["line"], 4);
+    assert_eq!(diagnostic["start"]["column"], 1);
+}
+
+#[test]
+fn check_denies_warnings_on_request() {
+    let root = temp_case_dir("fabulate_check_deny_smoke");
+    fs::create_dir_all(&root).expect("create temp dir");
+
+    let entry = root.join("story.fab");
+    fs::write(&entry, WARNING_STORY).expect("write entry");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_fabulate"))
+        .arg("check")
+        .arg(&entry)
+        .arg("--deny-warnings")
+        .arg("--color")
+        .arg("never")
+        .output()
+        .expect("run fabulate check");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(65), "stderr={stderr}");
+    assert!(
+        stderr.contains("warning: Unreachable part"),
+        "stderr={stderr}"
+    );
+    assert!(stderr.contains("story.fab:4:1"), "stderr={stderr}");
+    assert!(
+        stderr.contains("check failed with 0 error(s) and 1 warning(s)"),
+        "stderr={stderr}"
+    );
+    assert_eq!(stderr.matches("1 warning(s)").count(), 1, "stderr={stderr}");
+}
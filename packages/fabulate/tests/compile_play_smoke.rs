@@ -153,7 +153,7 @@ fn play_command_runs_compiled_bundle() {
         .stdin
         .as_mut()
         .expect("play stdin")
-        .write_all(b"1\n")
+        .write_all(b"\n1\n")
         .expect("write play choice");
 
     let output = child.wait_with_output().expect("wait for fabulate play");
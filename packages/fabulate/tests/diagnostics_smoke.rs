@@ -0,0 +1,37 @@
+use std::{fs, process::Command};
+
+use fabc_reg_test::temp_case_dir;
+
+#[test]
+fn failed_compiles_print_source_annotated_diagnostics() {
+    let root = temp_case_dir("fabulate_diagnostics_smoke");
+    fs::create_dir_all(&root).expect("create temp dir");
+
+    let entry = root.join("story.fab");
+    fs::write(
+        &entry,
+        "Story { start: \"intro\" }\n\n# intro\n- \"Go\" {\n    next: () => { let x = missing; }\n}\n",
+    )
+    .expect("write entry");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_fabulate"))
+        .arg("compile")
+        .arg(&entry)
+        .arg("--color")
+        .arg("never")
+        .output()
+        .expect("run fabulate compile");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(65), "stderr={stderr}");
+    assert!(
+        stderr.contains("error: Uninitialized variable"),
+        "stderr={stderr}"
+    );
+    assert!(stderr.contains("story.fab:5:27"), "stderr={stderr}");
+    assert!(
+        stderr.contains("    5 |    next: () => { let x = missing; }"),
+        "stderr={stderr}"
+    );
+    assert!(!stderr.contains('\u{1b}'), "stderr={stderr}");
+}
@@ -0,0 +1,57 @@
+use std::{fs, path::Path, process::Command};
+
+use fabc_reg_test::temp_case_dir;
+
+fn explore(root: &Path, source: &str) -> (bool, Option<i32>, String) {
+    fs::create_dir_all(root).expect("create temp dir");
+    let entry = root.join("story.fab");
+    fs::write(&entry, source).expect("write story");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_fabulate"))
+        .arg("explore")
+        .arg(&entry)
+        .output()
+        .expect("run fabulate explore");
+
+    (
+        output.status.success(),
+        output.status.code(),
+        String::from_utf8(output.stdout).expect("utf-8 output"),
+    )
+}
+
+#[test]
+fn explore_lists_endings_and_unvisited_parts() {
+    let (success, _, stdout) = explore(
+        &temp_case_dir("fabulate_explore_endings_smoke"),
+        "Story { start: \"intro\" }\n\n# intro\n- \"Left\" { next: () => { goto @left; } }\n- \"Right\" { next: () => { goto @right; } }\n\n# left\n* \"Left room\"\n\n# right\n* \"Right room\"\n\n# orphan\n* \"Nobody comes here\"\n",
+    );
+
+    assert!(success, "stdout={stdout}");
+    assert!(
+        stdout.contains("  left (1 playthrough(s)) via \"Left\""),
+        "stdout={stdout}"
+    );
+    assert!(stdout.contains("  right (1 playthrough(s)) via \"Right\""));
+    assert!(stdout.contains("unvisited parts:\n  orphan\n"));
+}
+
+#[test]
+fn explore_fails_on_runtime_errors_and_loops() {
+    let (success, code, stdout) = explore(
+        &temp_case_dir("fabulate_explore_problems_smoke"),
+        "Story { start: \"intro\" }\n\n# intro\n- \"Break\" { next: () => { goto context.missing; } }\n- \"Spin\" { next: () => { goto @ping; } }\n\n# ping\n* \"Ping\" { next: () => { goto @pong; } }\n\n# pong\n* \"Pong\" { next: () => { goto @ping; } }\n",
+    );
+
+    assert!(!success, "stdout={stdout}");
+    assert_eq!(code, Some(65));
+    assert!(
+        stdout.contains("runtime errors:\n  intro: "),
+        "stdout={stdout}"
+    );
+    assert!(stdout.contains("via \"Break\""), "stdout={stdout}");
+    assert!(
+        stdout.contains("infinite loops:\n  ping -> pong via \"Spin\""),
+        "stdout={stdout}"
+    );
+}
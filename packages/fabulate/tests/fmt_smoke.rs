@@ -0,0 +1,83 @@
+use std::{fs, process::Command};
+
+use fabc_reg_test::temp_case_dir;
+
+const MESSY_STORY: &str =
+    "Story {start:\"intro\"}\n#intro\n// Greeting.\n*\"Hi\" {next:()=>@intro}\n";
+const FORMATTED_STORY: &str =
+    "Story { start: \"intro\" }\n\n# intro\n// Greeting.\n* \"Hi\" { next: () => @intro }\n";
+
+#[test]
+fn fmt_rewrites_sources_in_directories() {
+    let root = temp_case_dir("fabulate_fmt_smoke");
+    fs::create_dir_all(root.join("chapters")).expect("create temp dir");
+
+    let entry = root.join("story.fab");
+    let chapter = root.join("chapters").join("one.fab");
+    let notes = root.join("chapters").join("notes.txt");
+    fs::write(&entry, MESSY_STORY).expect("write entry");
+    fs::write(&chapter, MESSY_STORY).expect("write chapter");
+    fs::write(&notes, "not a story").expect("write notes");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_fabulate"))
+        .arg("fmt")
+        .arg(&root)
+        .output()
+        .expect("run fabulate fmt");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "stderr={stderr}");
+    assert_eq!(fs::read_to_string(&entry).unwrap(), FORMATTED_STORY);
+    assert_eq!(fs::read_to_string(&chapter).unwrap(), FORMATTED_STORY);
+    assert_eq!(fs::read_to_string(&notes).unwrap(), "not a story");
+}
+
+#[test]
+fn fmt_check_lists_unformatted_files_without_rewriting() {
+    let root = temp_case_dir("fabulate_fmt_check_smoke");
+    fs::create_dir_all(&root).expect("create temp dir");
+
+    let messy = root.join("messy.fab");
+    let tidy = root.join("tidy.fab");
+    fs::write(&messy, MESSY_STORY).expect("write messy");
+    fs::write(&tidy, FORMATTED_STORY).expect("write tidy");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_fabulate"))
+        .arg("fmt")
+        .arg("--check")
+        .arg(&messy)
+        .arg(&tidy)
+        .output()
+        .expect("run fabulate fmt --check");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(65), "stderr={stderr}");
+    assert_eq!(stdout.trim(), messy.display().to_string());
+    assert!(
+        stderr.contains("1 file(s) are not formatted"),
+        "stderr={stderr}"
+    );
+    assert_eq!(fs::read_to_string(&messy).unwrap(), MESSY_STORY);
+}
+
+#[test]
+fn fmt_reports_syntax_errors() {
+    let root = temp_case_dir("fabulate_fmt_error_smoke");
+    fs::create_dir_all(&root).expect("create temp dir");
+
+    let entry = root.join("broken.fab");
+    fs::write(&entry, "Story {}\n# intro\n* \"Hi\" {\n").expect("write entry");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_fabulate"))
+        .arg("fmt")
+        .arg("--color")
+        .arg("never")
+        .arg(&entry)
+        .output()
+        .expect("run fabulate fmt");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(65), "stderr={stderr}");
+    assert!(stderr.contains("broken.fab:"), "stderr={stderr}");
+}
@@ -0,0 +1,103 @@
+use std::{fs, path::Path, process::Command};
+
+use fabc_reg_test::temp_case_dir;
+
+fn write_story(root: &Path) -> std::path::PathBuf {
+    fs::create_dir_all(root).expect("create temp dir");
+
+    let entry = root.join("story.fab");
+    fs::write(
+        &entry,
+        "module \"./branch.fab\" as branch;\n\nStory { start: \"intro\" }\n\n# intro\n- \"Go on\" { next: () => { goto branch.start; } }\n\n# orphan\n* \"Nobody comes here\"\n\n# stuck\n- \"Wait\"\n",
+    )
+    .expect("write entry");
+    fs::write(
+        root.join("branch.fab"),
+        "Story {}\n\n# start\n* \"On\" { next: () => { goto @end; } }\n\n# end\n* \"Done\"\n",
+    )
+    .expect("write import");
+
+    entry
+}
+
+fn graph(entry: &Path, format: &str) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_fabulate"))
+        .arg("graph")
+        .arg(entry)
+        .arg("--format")
+        .arg(format)
+        .output()
+        .expect("run fabulate graph");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "stderr={stderr}");
+    assert!(stderr.contains("Unreachable part"), "stderr={stderr}");
+    String::from_utf8(output.stdout).expect("utf-8 output")
+}
+
+#[test]
+fn graph_emits_dot_with_clusters_and_highlights() {
+    let entry = write_story(&temp_case_dir("fabulate_graph_dot_smoke"));
+
+    let dot = graph(&entry, "dot");
+
+    assert!(dot.starts_with("digraph story {"), "dot={dot}");
+    assert!(dot.contains("subgraph \"cluster_branch\""), "dot={dot}");
+    assert!(
+        dot.contains("\"intro\" -> \"branch.start\" [label=\"Go on\"];"),
+        "dot={dot}"
+    );
+    assert!(
+        dot.contains("\"branch.end\" [label=\"end\", peripheries=2];"),
+        "dot={dot}"
+    );
+    assert!(
+        dot.contains("\"stuck\" [label=\"stuck\", shape=doubleoctagon, color=red, style=dashed"),
+        "dot={dot}"
+    );
+    assert!(dot.contains("\"orphan\" [label=\"orphan\", peripheries=2, style=dashed"));
+}
+
+#[test]
+fn graph_emits_mermaid_flowchart() {
+    let entry = write_story(&temp_case_dir("fabulate_graph_mermaid_smoke"));
+
+    let mermaid = graph(&entry, "mermaid");
+
+    assert!(mermaid.starts_with("flowchart LR\n"), "mermaid={mermaid}");
+    assert!(
+        mermaid.contains("subgraph ns1[\"branch\"]"),
+        "mermaid={mermaid}"
+    );
+    assert!(mermaid.contains("-->|\"Go on\"|"), "mermaid={mermaid}");
+    assert!(mermaid.contains("([\"end\"])"), "mermaid={mermaid}");
+    assert!(mermaid.contains("[[\"stuck\"]]"), "mermaid={mermaid}");
+    assert!(
+        mermaid.contains("classDef unreachable"),
+        "mermaid={mermaid}"
+    );
+}
+
+#[test]
+fn graph_emits_json() {
+    let entry = write_story(&temp_case_dir("fabulate_graph_json_smoke"));
+
+    let report: serde_json::Value =
+        serde_json::from_str(&graph(&entry, "json")).expect("valid json graph");
+
+    assert_eq!(report["start"], "intro");
+    let orphan = report["parts"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|part| part["name"] == "orphan")
+        .expect("orphan part");
+    assert_eq!(orphan["unreachable"], true);
+    assert_eq!(orphan["ending"], true);
+    assert_eq!(orphan["dead_end"], false);
+    assert!(report["edges"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|edge| edge["from"] == "intro" && edge["label"] == "Go on"));
+}
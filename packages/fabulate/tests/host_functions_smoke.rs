@@ -0,0 +1,89 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+use fabc_reg_test::temp_case_dir;
+
+const STORY: &str = "Story { start: \"intro\" }\n\n# intro\n- \"Ask around\" { next: () => { context.rep = reputation(\"guild\"); } }\n";
+
+const MANIFEST: &str =
+    r#"{ "reputation": { "parameters": ["string"], "returns": "number", "result": 3 } }"#;
+
+fn write_story(root: &Path) -> (PathBuf, PathBuf) {
+    fs::create_dir_all(root).expect("create temp dir");
+    let entry = root.join("story.fab");
+    let manifest = root.join("host.json");
+    fs::write(&entry, STORY).expect("write story");
+    fs::write(&manifest, MANIFEST).expect("write manifest");
+    (entry, manifest)
+}
+
+fn fabulate(args: &[&Path], command: &str, extra: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_fabulate"))
+        .arg(command)
+        .args(args)
+        .args(extra)
+        .output()
+        .expect("run fabulate")
+}
+
+#[test]
+fn check_accepts_host_functions_declared_in_a_manifest() {
+    let (entry, manifest) = write_story(&temp_case_dir("fabulate_host_functions_check_smoke"));
+
+    let undeclared = fabulate(&[&entry], "check", &["--color", "never"]);
+    let stderr = String::from_utf8_lossy(&undeclared.stderr);
+    assert_eq!(undeclared.status.code(), Some(65), "stderr={stderr}");
+
+    let declared = fabulate(
+        &[&entry, Path::new("--host-functions"), &manifest],
+        "check",
+        &[],
+    );
+    let stderr = String::from_utf8_lossy(&declared.stderr);
+    assert!(declared.status.success(), "stderr={stderr}");
+}
+
+#[test]
+fn simulate_calls_host_functions_through_their_manifest_results() {
+    let (entry, manifest) = write_story(&temp_case_dir("fabulate_host_functions_simulate_smoke"));
+
+    let output = fabulate(
+        &[&entry, Path::new("--host-functions"), &manifest],
+        "simulate",
+        &["--runs", "5", "--strategy", "first"],
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        output.status.success(),
+        "stdout={stdout} stderr={}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(
+        stdout.contains("  rep: min 3 / mean 3.00 / max 3"),
+        "stdout={stdout}"
+    );
+}
+
+#[test]
+fn invalid_manifests_are_data_errors() {
+    let root = temp_case_dir("fabulate_host_functions_invalid_smoke");
+    let (entry, manifest) = write_story(&root);
+    fs::write(&manifest, r#"{ "reputation": { "returns": "float" } }"#).expect("write manifest");
+
+    let output = fabulate(
+        &[&entry, Path::new("--host-functions"), &manifest],
+        "graph",
+        &[],
+    );
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(65), "stderr={stderr}");
+    assert!(
+        stderr.contains("invalid host function manifest"),
+        "stderr={stderr}"
+    );
+}
@@ -0,0 +1,176 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::Write,
+    path::Path,
+    process::{Command, Output, Stdio},
+};
+
+use fabc::{CompiledBundleManifest, Compiler, COMPILED_BUNDLE_FORMAT_VERSION};
+use fabc_reg_test::temp_case_dir;
+
+#[test]
+fn play_command_saves_and_resumes_sessions() {
+    let root = temp_case_dir("fabulate_play_save_smoke");
+    fs::create_dir_all(&root).expect("create temp dir");
+
+    let entry = root.join("story.fab");
+    let bundle_output = root.join("bundle");
+    let slot = root.join("slot");
+
+    fs::write(
+        &entry,
+        r#"
+        Story { start: "intro" }
+
+        # intro
+        - "Enter the cave" {
+            next: () => {
+                context.torch = { lit: true };
+                goto fork;
+            }
+        }
+
+        # fork
+        - "Go left" {
+            next: () => { goto left; }
+        }
+        - "Go right" {
+            next: () => { goto right; }
+        }
+
+        # left
+        * "You went left"
+
+        # right
+        * "You went right"
+        "#,
+    )
+    .expect("write entry story");
+    write_interpreted_bundle(&entry, &bundle_output);
+
+    let output = run_play(
+        &bundle_output,
+        None,
+        format!("1\n:save {}\n:quit\n", slot.display()).as_bytes(),
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("1. Enter the cave"));
+    assert!(stdout.contains("2. Go right"));
+    assert!(stdout.contains("Saved to"));
+    assert!(!stdout.contains("Story finished."));
+
+    let save_path = slot.with_extension("json");
+    let save = fs::read_to_string(&save_path).expect("read save file");
+    assert!(save.contains("\"fork\""));
+    assert!(save.contains("\"torch\""));
+
+    let output = run_play(&bundle_output, Some(&save_path), b"2\n");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!stdout.contains("Enter the cave"));
+    assert!(stdout.contains("You went right"));
+    assert!(stdout.contains("Story finished."));
+}
+
+#[test]
+fn play_command_saves_during_narration_in_piped_sessions() {
+    let root = temp_case_dir("fabulate_play_narration_save_smoke");
+    fs::create_dir_all(&root).expect("create temp dir");
+
+    let entry = root.join("story.fab");
+    let bundle_output = root.join("bundle");
+    let slot = root.join("slot");
+
+    fs::write(
+        &entry,
+        r#"
+        Story { start: "intro" }
+
+        # intro
+        * "You wake in the dark."
+        - "Light a match" {
+            next: () => { goto lit; }
+        }
+
+        # lit
+        * "The room is empty."
+        "#,
+    )
+    .expect("write entry story");
+    write_interpreted_bundle(&entry, &bundle_output);
+
+    let output = run_play(
+        &bundle_output,
+        None,
+        format!(":nonsense\n:save {}\n:quit\n", slot.display()).as_bytes(),
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stdout.contains("You wake in the dark."));
+    assert!(stdout.contains("Saved to"));
+    assert!(!stdout.contains("1. Light a match"));
+    assert!(stderr.contains("Unknown command."));
+
+    let output = run_play(&bundle_output, Some(&slot.with_extension("json")), b"\n1\n");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("You wake in the dark."));
+    assert!(stdout.contains("The room is empty."));
+    assert!(stdout.contains("Story finished."));
+}
+
+fn write_interpreted_bundle(entry: &Path, bundle_output: &Path) {
+    let program = Compiler.build_program(entry).expect("build story program");
+    let function_symbols = program
+        .functions
+        .iter()
+        .map(|function| (function.id, format!("fabc_fn_{}", function.id)))
+        .collect::<BTreeMap<_, _>>();
+    // Without the `.ll` file next to the manifest, `play` falls back to the interpreter.
+    let manifest = CompiledBundleManifest {
+        format_version: COMPILED_BUNDLE_FORMAT_VERSION,
+        module_name: "fabulate_play_save_smoke".to_string(),
+        program,
+        function_symbols,
+    };
+
+    fs::create_dir_all(bundle_output).expect("create bundle dir");
+    fs::write(
+        bundle_output.join("story.json"),
+        serde_json::to_vec_pretty(&manifest).expect("serialize manifest"),
+    )
+    .expect("write manifest");
+}
+
+fn run_play(bundle: &Path, resume: Option<&Path>, stdin: &[u8]) -> Output {
+    let mut command = Command::new(fabulate_bin());
+    command.arg("play").arg(bundle);
+    if let Some(resume) = resume {
+        command.arg("--resume").arg(resume);
+    }
+
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("spawn fabulate play");
+    child
+        .stdin
+        .as_mut()
+        .expect("play stdin")
+        .write_all(stdin)
+        .expect("write play input");
+
+    let output = child.wait_with_output().expect("wait for fabulate play");
+    assert!(
+        output.status.success(),
+        "fabulate play failed: stdout={} stderr={}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    output
+}
+
+fn fabulate_bin() -> String {
+    env!("CARGO_BIN_EXE_fabulate").to_owned()
+}
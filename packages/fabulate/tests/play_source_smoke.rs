@@ -0,0 +1,118 @@
+use std::{
+    fs,
+    io::Write,
+    path::Path,
+    process::{Command, Output, Stdio},
+};
+
+use fabc_reg_test::temp_case_dir;
+
+const STORY: &str = r#"
+Story { start: "intro" }
+
+# intro
+* "At the crossroads."
+- "Walk on" {
+    next: () => {
+        if (context.brave) {
+            goto @cave;
+        }
+        goto @home;
+    }
+}
+
+# cave
+! play_music("drips", 0.5)
+* "You enter the cave."
+
+# home
+* "You head home."
+"#;
+
+fn write_story(root: &Path) -> std::path::PathBuf {
+    fs::create_dir_all(root).expect("create temp dir");
+    let entry = root.join("story.fab");
+    fs::write(&entry, STORY).expect("write story");
+    entry
+}
+
+fn run_play(entry: &Path, args: &[&str], stdin: &[u8]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_fabulate"))
+        .arg("play")
+        .arg(entry)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("spawn fabulate play");
+    child
+        .stdin
+        .as_mut()
+        .expect("play stdin")
+        .write_all(stdin)
+        .expect("write play input");
+
+    child.wait_with_output().expect("wait for fabulate play")
+}
+
+#[test]
+fn play_compiles_fab_sources_with_initial_context() {
+    let entry = write_story(&temp_case_dir("fabulate_play_source_context_smoke"));
+
+    let output = run_play(&entry, &["--context", r#"{"brave": true}"#], b"\n1\n");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "stdout={stdout}");
+    assert!(stdout.contains("At the crossroads."), "stdout={stdout}");
+    assert!(stdout.contains("You enter the cave."), "stdout={stdout}");
+    assert!(stdout.contains("Story finished."), "stdout={stdout}");
+}
+
+#[test]
+fn play_prints_directives_in_story_order() {
+    let entry = write_story(&temp_case_dir("fabulate_play_source_directive_smoke"));
+
+    let output = run_play(&entry, &["--start", "cave"], b"");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "stdout={stdout}");
+    assert!(
+        stdout.starts_with("! play_music(\"drips\", 0.5)\nYou enter the cave.\n"),
+        "stdout={stdout}"
+    );
+
+    let output = run_play(&entry, &["--start", "cave", "--color", "always"], b"");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("\x1b[2m! play_music(\"drips\", 0.5)\x1b[0m"),
+        "stdout={stdout}"
+    );
+}
+
+#[test]
+fn play_starts_at_the_requested_part() {
+    let entry = write_story(&temp_case_dir("fabulate_play_source_start_smoke"));
+
+    let output = run_play(&entry, &["--start", "home"], b"");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "stdout={stdout}");
+    assert!(!stdout.contains("At the crossroads."), "stdout={stdout}");
+    assert!(stdout.contains("You head home."), "stdout={stdout}");
+}
+
+#[test]
+fn play_rejects_context_that_is_not_an_object() {
+    let entry = write_story(&temp_case_dir("fabulate_play_source_bad_context_smoke"));
+
+    let output = run_play(&entry, &["--context", "[1, 2]"], b"");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(65), "stderr={stderr}");
+    assert!(
+        stderr.contains("invalid story context: expected a JSON object"),
+        "stderr={stderr}"
+    );
+}
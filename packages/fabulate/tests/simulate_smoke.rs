@@ -0,0 +1,62 @@
+use std::{fs, path::Path, process::Command};
+
+use fabc_reg_test::temp_case_dir;
+
+const STORY: &str = "Story { start: \"intro\" }\n\n# intro\n- \"Trust\" { weight: 9, next: () => { context.trust = 1; goto @friends; } }\n- \"Doubt\" { weight: 1, next: () => { context.trust = -1; goto @alone; } }\n\n# friends\n* \"Friends\"\n\n# alone\n* \"Alone\"\n";
+
+fn simulate(entry: &Path, strategy: &str, seed: &str) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_fabulate"))
+        .arg("simulate")
+        .arg(entry)
+        .args(["--runs", "200", "--strategy", strategy, "--seed", seed])
+        .output()
+        .expect("run fabulate simulate");
+
+    let stdout = String::from_utf8(output.stdout).expect("utf-8 output");
+    assert!(
+        output.status.success(),
+        "stdout={stdout} stderr={}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    stdout
+}
+
+fn write_story(root: &Path) -> std::path::PathBuf {
+    fs::create_dir_all(root).expect("create temp dir");
+    let entry = root.join("story.fab");
+    fs::write(&entry, STORY).expect("write story");
+    entry
+}
+
+#[test]
+fn simulate_reports_endings_visits_and_context() {
+    let entry = write_story(&temp_case_dir("fabulate_simulate_first_smoke"));
+
+    let stdout = simulate(&entry, "first", "0");
+
+    assert!(
+        stdout.contains("simulated 200 run(s) with seed 0"),
+        "stdout={stdout}"
+    );
+    assert!(
+        stdout.contains("endings:\n  friends: 200 (100.0%)\n"),
+        "stdout={stdout}"
+    );
+    assert!(stdout.contains("  intro: 200 (100.0%)"), "stdout={stdout}");
+    assert!(
+        stdout.contains("  trust: min 1 / mean 1.00 / max 1"),
+        "stdout={stdout}"
+    );
+}
+
+#[test]
+fn weighted_simulation_is_deterministic_for_a_seed() {
+    let entry = write_story(&temp_case_dir("fabulate_simulate_weighted_smoke"));
+
+    let first = simulate(&entry, "weighted", "42");
+    let second = simulate(&entry, "weighted", "42");
+
+    assert_eq!(first, second);
+    assert!(first.contains("  alone: "), "stdout={first}");
+    assert!(first.contains("  trust: min -1 / mean "), "stdout={first}");
+}
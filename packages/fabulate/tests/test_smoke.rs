@@ -0,0 +1,84 @@
+use std::{fs, path::Path, process::Command};
+
+use fabc_reg_test::{temp_case_dir, COMPLEX_STORY};
+
+fn run_tests(path: &Path) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_fabulate"))
+        .arg("test")
+        .arg(path)
+        .output()
+        .expect("run fabulate test")
+}
+
+#[test]
+fn test_command_runs_playtests_found_in_directories() {
+    let root = temp_case_dir("fabulate_test_pass_smoke");
+    fs::create_dir_all(root.join("tests")).expect("create temp dir");
+
+    fs::write(root.join("story.fab"), COMPLEX_STORY).expect("write story");
+    fs::write(
+        root.join("tests").join("walkthrough.fabtest"),
+        "story \"../story.fab\"\n\ntest \"meets the villain\"\n[Hero] \"Hello there!\"\ncontext total == 30\nending part_2\n\ntest \"any ending\"\nending\n",
+    )
+    .expect("write playtest");
+
+    let output = run_tests(&root);
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "stdout={stdout}");
+    assert!(
+        stdout.contains("test meets the villain ... ok"),
+        "stdout={stdout}"
+    );
+    assert!(
+        stdout.contains("test result: 2 passed; 0 failed"),
+        "stdout={stdout}"
+    );
+}
+
+#[test]
+fn test_command_reports_failures_with_expected_and_found() {
+    let root = temp_case_dir("fabulate_test_fail_smoke");
+    fs::create_dir_all(&root).expect("create temp dir");
+
+    fs::write(root.join("story.fab"), COMPLEX_STORY).expect("write story");
+    let playtest = root.join("story.fabtest");
+    fs::write(
+        &playtest,
+        "test \"wrong ending\"\n[Hero] \"Hello there!\"\nending part_3\n",
+    )
+    .expect("write playtest");
+
+    let output = run_tests(&playtest);
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(output.status.code(), Some(65), "stdout={stdout}");
+    assert!(
+        stdout.contains("test wrong ending ... FAILED"),
+        "stdout={stdout}"
+    );
+    assert!(stdout.contains("story.fabtest:3"), "stdout={stdout}");
+    assert!(
+        stdout.contains("expected: ending part_3"),
+        "stdout={stdout}"
+    );
+    assert!(stdout.contains("found: ending part_2"), "stdout={stdout}");
+}
+
+#[test]
+fn test_command_rejects_malformed_playtests() {
+    let root = temp_case_dir("fabulate_test_invalid_smoke");
+    fs::create_dir_all(&root).expect("create temp dir");
+
+    let playtest = root.join("story.fabtest");
+    fs::write(&playtest, "[Hero] \"Hello there!\"\n").expect("write playtest");
+
+    let output = run_tests(&playtest);
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(65), "stderr={stderr}");
+    assert!(
+        stderr.contains("story.fabtest:1: steps must follow a `test \"name\"` line"),
+        "stderr={stderr}"
+    );
+}